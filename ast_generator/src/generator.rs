@@ -3,6 +3,7 @@ use std::{
     fs::{read_dir, File},
     io::{LineWriter, Result, Write},
     path::PathBuf,
+    process::Command,
 };
 
 /// Generates dummy ast file with some code function, defines default struct and methods
@@ -23,7 +24,7 @@ use std::{
 ///     left: Box<dyn Expr<T> + 'static>,
 ///     operator: Token,
 ///     right: Box<dyn Expr<T> + 'static>,
-///     _marker: marker::PhantomData<T>,
+///     _marker: marker::PhantomData<fn() -> T>,
 /// }
 ///
 /// impl <T> for Binary<T> {
@@ -41,10 +42,91 @@ pub struct GenerateAst {
     out_dir: PathBuf,
 }
 
+/// A node of the generated ast, its fields are public and passed to the constructor in order
+///
+/// # Example
+/// ```
+/// let node = Node::new("Grouping", vec![("expression", "Box<dyn Expr<T, V>>")])
+///     .doc("Expression between parentheses");
+/// ```
+pub struct Node<'a> {
+    name: &'a str,
+    fields: Vec<(&'a str, &'a str)>,
+    doc: Option<&'a str>,
+    field_docs: Vec<(&'a str, &'a str)>,
+    display: Option<&'a str>,
+    private_fields: Vec<PrivateField<'a>>,
+    methods: Option<&'a str>,
+}
+
+/// A field of a node which isn't passed to the constructor, it starts from `init`
+struct PrivateField<'a> {
+    name: &'a str,
+    field_type: &'a str,
+    init: &'a str,
+    doc: &'a str,
+}
+
+impl<'a> Node<'a> {
+    pub fn new(name: &'a str, fields: Vec<(&'a str, &'a str)>) -> Self {
+        Node {
+            name,
+            fields,
+            doc: None,
+            field_docs: Vec::new(),
+            display: None,
+            private_fields: Vec::new(),
+            methods: None,
+        }
+    }
+
+    /// documents the struct of the node, every line of `doc` is a line of the doc comment
+    pub fn doc(mut self, doc: &'a str) -> Self {
+        self.doc = Some(doc);
+        self
+    }
+
+    /// documents a field of the node
+    pub fn field_doc(mut self, field: &'a str, doc: &'a str) -> Self {
+        self.field_docs.push((field, doc));
+        self
+    }
+
+    /// body of the `Display::fmt` function of the node, by default the fields are written
+    /// separated by a space, see `GenerateAst::define_display`
+    pub fn display(mut self, body: &'a str) -> Self {
+        self.display = Some(body);
+        self
+    }
+
+    /// adds a field only the node's own methods see, the constructor sets it to `init`
+    pub fn private_field(
+        mut self,
+        name: &'a str,
+        field_type: &'a str,
+        init: &'a str,
+        doc: &'a str,
+    ) -> Self {
+        self.private_fields.push(PrivateField {
+            name,
+            field_type,
+            init,
+            doc,
+        });
+        self
+    }
+
+    /// methods written in the impl block of the constructor
+    pub fn methods(mut self, methods: &'a str) -> Self {
+        self.methods = Some(methods);
+        self
+    }
+}
+
 /// public methods
 impl GenerateAst {
     /// Creates struct for ready to be used for the provided path
-    /// checks if the `path` exist and empty, error if not
+    /// checks if the `path` exist, error if not
     ///
     /// # Example
     ///
@@ -59,20 +141,15 @@ impl GenerateAst {
     /// * The provided `path` doesn't exist
     /// * The `path` points at a non-directory file.
     pub fn for_path(path: PathBuf) -> Result<GenerateAst> {
-        // Check if the path exist
-        let mut dir = read_dir(&path)?;
-        if dir.next().is_none() {
-            return Err(Error::new(
-                ErrorKind::InvalidInput,
-                "directory doesn't exist",
-            ));
-        }
+        // Check if the path exist and is a directory
+        read_dir(&path)?;
         Ok(GenerateAst { out_dir: path })
     }
 }
 
 impl GenerateAst {
-    /// Expression ast generator
+    /// Expression ast generator, `uses` are imported next to the imports every file needs and
+    /// `aliases` are written before the nodes
     ///
     /// # Expression Grammar
     /// ```
@@ -91,23 +168,35 @@ impl GenerateAst {
     pub fn define_expr_ast(
         &self,
         base_name: &str,
-        types: &Vec<(&str, Vec<(&str, &str)>)>,
+        uses: &[&str],
+        aliases: &[&str],
+        types: &[Node],
     ) -> Result<()> {
         let dir_str = self.out_dir.to_str();
         if dir_str.is_none() {
             return Err(Error::new(ErrorKind::InvalidInput, "path cannot be empty"));
         }
         let file_path = format!("{}/{}.rs", &dir_str.unwrap(), &base_name.to_lowercase());
-        let file = File::create(file_path)?;
+        let file = File::create(&file_path)?;
         let mut writer = LineWriter::new(file);
 
+        writer.write_all(b"use crate::shared::ThreadSafe;\n")?;
         writer.write_all(b"use crate::token;\n")?;
-        writer.write_all(b"use std::fmt::{Display, Formatter, Result};\n")?;
+        writer.write_all(b"use std::fmt::{Debug, Display, Formatter, Result};\n")?;
         writer.write_all(b"use std::marker;\n")?;
-        writer.write_all(b"\n")?;
+        self.define_header(&mut writer, uses, aliases)?;
+
+        // ExprKind
+        self.define_kind(&mut writer, base_name, "<'a, T, V>", "<T, V>", types)?;
 
         // Expr
-        self.define_trait(&mut writer, base_name, "<T, V: Visitor<T>>", "&V")?;
+        self.define_trait(
+            &mut writer,
+            base_name,
+            "<T, V: Visitor<T>>",
+            "&mut V",
+            "ExprKind<'_, T, V>",
+        )?;
 
         // Visitor
         self.define_expr_visitor(&mut writer, base_name, types)?;
@@ -116,10 +205,11 @@ impl GenerateAst {
         self.define_expr_types(&mut writer, base_name, types)?;
         writer.flush()?;
 
-        Ok(())
+        format_file(&file_path)
     }
 
-    /// Statement ast generator
+    /// Statement ast generator, `uses` are imported next to the imports every file needs and
+    /// `aliases` are written before the nodes
     ///
     /// # Statement Grammar
     /// ```
@@ -134,24 +224,36 @@ impl GenerateAst {
     pub fn define_stmt_ast(
         &self,
         base_name: &str,
-        types: &Vec<(&str, Vec<(&str, &str)>)>,
+        uses: &[&str],
+        aliases: &[&str],
+        types: &[Node],
     ) -> Result<()> {
         let dir_str = self.out_dir.to_str();
         if dir_str.is_none() {
             return Err(Error::new(ErrorKind::InvalidInput, "path cannot be empty"));
         }
         let file_path = format!("{}/{}.rs", &dir_str.unwrap(), &base_name.to_lowercase());
-        let file = File::create(file_path)?;
+        let file = File::create(&file_path)?;
         let mut writer = LineWriter::new(file);
 
         writer.write_all(b"use crate::ast::expr::Expr;\n")?;
+        writer.write_all(b"use crate::shared::ThreadSafe;\n")?;
         writer.write_all(b"use crate::token;\n")?;
-        writer.write_all(b"use std::fmt::{Display, Formatter, Result};\n")?;
+        writer.write_all(b"use std::fmt::{Debug, Display, Formatter, Result};\n")?;
         writer.write_all(b"use std::marker;\n")?;
-        writer.write_all(b"\n")?;
-
-        // Expr
-        self.define_trait(&mut writer, base_name, "<T, U: Visitor<T, V>, V>", "&mut U")?;
+        self.define_header(&mut writer, uses, aliases)?;
+
+        // StmtKind
+        self.define_kind(&mut writer, base_name, "<'a, T, U, V>", "<T, U, V>", types)?;
+
+        // Stmt
+        self.define_trait(
+            &mut writer,
+            base_name,
+            "<T, U: Visitor<T, V>, V>",
+            "&mut U",
+            "StmtKind<'_, T, U, V>",
+        )?;
 
         // Visitor
         self.define_stmt_visitor(&mut writer, base_name, types)?;
@@ -160,23 +262,54 @@ impl GenerateAst {
         self.define_stmt_types(&mut writer, base_name, types)?;
         writer.flush()?;
 
-        Ok(())
+        format_file(&file_path)
     }
 }
 
 /// private methods
 impl GenerateAst {
+    /// the extra imports go in the same block as the default ones so rustfmt sorts them together
+    fn define_header(
+        &self,
+        writer: &mut LineWriter<File>,
+        uses: &[&str],
+        aliases: &[&str],
+    ) -> Result<()> {
+        for path in uses {
+            writer.write_all(format!("use {};", path).as_bytes())?;
+            writer.write_all(b"\n")?;
+        }
+        writer.write_all(b"\n")?;
+
+        for alias in aliases {
+            writer.write_all(alias.as_bytes())?;
+            writer.write_all(b"\n\n")?;
+        }
+
+        Ok(())
+    }
+
     fn define_trait(
         &self,
         writer: &mut LineWriter<File>,
         base_name: &str,
         generic: &str,
         visitor: &str,
+        kind: &str,
     ) -> Result<()> {
-        writer.write_all(format!("pub trait {}{}: Display {{", base_name, generic).as_bytes())?;
+        // `ThreadSafe` makes the boxed nodes `Send + Sync` with the `sync` feature
+        writer.write_all(
+            format!(
+                "pub trait {}{}: Display + ThreadSafe {{",
+                base_name, generic
+            )
+            .as_bytes(),
+        )?;
         writer.write_all(b"\n")?;
         writer.write_all(format!("    fn accept(&self, visitor: {}) -> T;", visitor).as_bytes())?;
         writer.write_all(b"\n")?;
+        writer.write_all(format!("    fn kind(&self) -> {};", kind).as_bytes())?;
+        writer.write_all(b"\n")?;
         writer.write_all(b"}")?;
 
         writer.write_all(b"\n\n")?;
@@ -184,23 +317,47 @@ impl GenerateAst {
         Ok(())
     }
 
+    /// enum borrowing any node of the file, a node hands itself out through `kind` so callers
+    /// can match on the concrete node behind a `dyn Expr`/`dyn Stmt`
+    fn define_kind(
+        &self,
+        writer: &mut LineWriter<File>,
+        base_name: &str,
+        generics: &str,
+        type_generics: &str,
+        types: &[Node],
+    ) -> Result<()> {
+        writer.write_all(format!("pub enum {}Kind{} {{", base_name, generics).as_bytes())?;
+        writer.write_all(b"\n")?;
+
+        for node in types {
+            writer
+                .write_all(format!("    {0}(&'a {0}{1}),", node.name, type_generics).as_bytes())?;
+            writer.write_all(b"\n")?;
+        }
+
+        writer.write_all(b"}")?;
+        writer.write_all(b"\n\n")?;
+        Ok(())
+    }
+
     fn define_stmt_visitor(
         &self,
         writer: &mut LineWriter<File>,
         base_name: &str,
-        types: &Vec<(&str, Vec<(&str, &str)>)>,
+        types: &[Node],
     ) -> Result<()> {
         writer.write_all(b"pub trait Visitor<T, V> {")?;
         writer.write_all(b"\n")?;
 
-        for (struct_name, ..) in types {
+        for node in types {
             writer.write_all(
                 format!(
                     "    fn visit_{}_{}(&mut self, {}: &{}<T, Self, V>) -> T;",
-                    struct_name.to_lowercase(),
+                    snake_case(node.name),
                     base_name.to_lowercase(),
                     base_name.to_lowercase(),
-                    struct_name
+                    node.name
                 )
                 .as_bytes(),
             )?;
@@ -217,19 +374,19 @@ impl GenerateAst {
         &self,
         writer: &mut LineWriter<File>,
         base_name: &str,
-        types: &Vec<(&str, Vec<(&str, &str)>)>,
+        types: &[Node],
     ) -> Result<()> {
         writer.write_all(b"pub trait Visitor<T> {")?;
         writer.write_all(b"\n")?;
 
-        for (struct_name, ..) in types {
+        for node in types {
             writer.write_all(
                 format!(
-                    "    fn visit_{}_{}(&self, {}: &{}<T, Self>) -> T;",
-                    struct_name.to_lowercase(),
+                    "    fn visit_{}_{}(&mut self, {}: &{}<T, Self>) -> T;",
+                    snake_case(node.name),
                     base_name.to_lowercase(),
                     base_name.to_lowercase(),
-                    struct_name
+                    node.name
                 )
                 .as_bytes(),
             )?;
@@ -246,11 +403,11 @@ impl GenerateAst {
         &self,
         writer: &mut LineWriter<File>,
         base_name: &str,
-        types: &Vec<(&str, Vec<(&str, &str)>)>,
+        types: &[Node],
     ) -> Result<()> {
         // The AST structs.
-        for (struct_name, struct_fields) in types {
-            self.define_stmt_type(writer, base_name, struct_name, struct_fields)?;
+        for node in types {
+            self.define_stmt_type(writer, base_name, node)?;
         }
 
         Ok(())
@@ -260,26 +417,26 @@ impl GenerateAst {
         &self,
         writer: &mut LineWriter<File>,
         base_name: &str,
-        struct_name: &str,
-        struct_fields: &Vec<(&str, &str)>,
+        node: &Node,
     ) -> Result<()> {
+        let struct_name = node.name;
+
         // struct definition
+        self.define_docs(writer, node.doc, "")?;
         writer.write_all(
             format!("pub struct {}<T, U: ?Sized, V: ?Sized> {{", struct_name).as_bytes(),
         )?;
         writer.write_all(b"\n")?;
 
         // Store parameters in fields.
-        for (name, field_type) in struct_fields {
-            writer.write_all(format!("    pub {}: {},", name, field_type).as_bytes())?;
-            writer.write_all(b"\n")?;
-        }
+        self.define_fields(writer, node)?;
 
-        writer.write_all(b"    _marker_1: marker::PhantomData<T>,")?;
+        // function pointer markers are `Send + Sync` whatever the result and visitor types
+        writer.write_all(b"    _marker_1: marker::PhantomData<fn() -> T>,")?;
         writer.write_all(b"\n")?;
-        writer.write_all(b"    _marker_2: marker::PhantomData<U>,")?;
+        writer.write_all(b"    _marker_2: marker::PhantomData<fn() -> U>,")?;
         writer.write_all(b"\n")?;
-        writer.write_all(b"    _marker_3: marker::PhantomData<V>,")?;
+        writer.write_all(b"    _marker_3: marker::PhantomData<fn() -> V>,")?;
         writer.write_all(b"\n")?;
         writer.write_all(b"}")?;
 
@@ -289,7 +446,8 @@ impl GenerateAst {
         writer.write_all(format!("impl<T, U, V> {struct_name}<T, U, V> {{").as_bytes())?;
         writer.write_all(b"\n")?;
         // Pass arguments to constructor
-        let arguments = struct_fields
+        let arguments = node
+            .fields
             .iter()
             .map(|(a, b)| format!("{}: {}", a, b))
             .collect::<Vec<String>>()
@@ -299,20 +457,29 @@ impl GenerateAst {
 
         writer.write_all(format!("        {} {{", struct_name).as_bytes())?;
         writer.write_all(b"\n")?;
-        for (name, ..) in struct_fields {
+        for (name, ..) in &node.fields {
             writer.write_all(format!("            {},", name).as_bytes())?;
             writer.write_all(b"\n")?;
         }
-        writer.write_all(b"            _marker_1: marker::PhantomData::default(),")?;
+        for field in &node.private_fields {
+            writer.write_all(format!("            {}: {},", field.name, field.init).as_bytes())?;
+            writer.write_all(b"\n")?;
+        }
+        writer.write_all(b"            _marker_1: marker::PhantomData,")?;
         writer.write_all(b"\n")?;
-        writer.write_all(b"            _marker_2: marker::PhantomData::default(),")?;
+        writer.write_all(b"            _marker_2: marker::PhantomData,")?;
         writer.write_all(b"\n")?;
-        writer.write_all(b"            _marker_3: marker::PhantomData::default(),")?;
+        writer.write_all(b"            _marker_3: marker::PhantomData,")?;
         writer.write_all(b"\n")?;
         writer.write_all(b"        }")?;
         writer.write_all(b"\n")?;
         writer.write_all(b"    }")?;
         writer.write_all(b"\n")?;
+        if let Some(methods) = node.methods {
+            writer.write_all(b"\n")?;
+            writer.write_all(methods.as_bytes())?;
+            writer.write_all(b"\n")?;
+        }
         writer.write_all(b"}")?;
 
         writer.write_all(b"\n\n")?;
@@ -331,62 +498,37 @@ impl GenerateAst {
         writer.write_all(
             format!(
                 "\t\tvisitor.visit_{}_{}(self)",
-                struct_name.to_lowercase(),
+                snake_case(struct_name),
                 base_name.to_lowercase()
             )
             .as_bytes(),
         )?;
         writer.write_all(b"\n")?;
         writer.write_all(b"    }")?;
-        writer.write_all(b"\n")?;
-        writer.write_all(b"}")?;
-
         writer.write_all(b"\n\n")?;
-
-        // struct display trait impl
-        writer.write_all(
-            format!(
-                "impl<T, U: Visitor<T, V>, V> Display for {}<T, U, V> {{",
-                struct_name
-            )
-            .as_bytes(),
-        )?;
-        writer.write_all(b"\n")?;
-        writer.write_all(b"    fn fmt(&self, f: &mut Formatter<'_>) -> Result {")?;
+        writer.write_all(b"    fn kind(&self) -> StmtKind<'_, T, U, V> {")?;
         writer.write_all(b"\n")?;
-
-        let inner_brace: String = struct_fields
-            .iter()
-            .map(|_| "{}".to_string())
-            .collect::<Vec<_>>()
-            .join(" ");
-        let field_ref: String = struct_fields
-            .iter()
-            .map(|(a, _)| format!("self.{}", a))
-            .collect::<Vec<_>>()
-            .join(", ");
-
-        writer.write_all(
-            format!("        write!(f, \"{}\", {})", inner_brace, field_ref,).as_bytes(),
-        )?;
+        writer.write_all(format!("        StmtKind::{}(self)", struct_name).as_bytes())?;
         writer.write_all(b"\n")?;
         writer.write_all(b"    }")?;
-
         writer.write_all(b"\n")?;
         writer.write_all(b"}")?;
+
         writer.write_all(b"\n\n")?;
-        Ok(())
+
+        self.define_display(writer, node, "<T, U: Visitor<T, V>, V>", "<T, U, V>")?;
+        self.define_debug(writer, struct_name, "<T, U: Visitor<T, V>, V>", "<T, U, V>")
     }
 
     fn define_expr_types(
         &self,
         writer: &mut LineWriter<File>,
         base_name: &str,
-        types: &Vec<(&str, Vec<(&str, &str)>)>,
+        types: &[Node],
     ) -> Result<()> {
         // The AST structs.
-        for (struct_name, struct_fields) in types {
-            self.define_expr_type(writer, base_name, struct_name, struct_fields)?;
+        for node in types {
+            self.define_expr_type(writer, base_name, node)?;
         }
 
         Ok(())
@@ -396,22 +538,22 @@ impl GenerateAst {
         &self,
         writer: &mut LineWriter<File>,
         base_name: &str,
-        struct_name: &str,
-        struct_fields: &Vec<(&str, &str)>,
+        node: &Node,
     ) -> Result<()> {
+        let struct_name = node.name;
+
         // struct definition
+        self.define_docs(writer, node.doc, "")?;
         writer.write_all(format!("pub struct {}<T, V: ?Sized> {{", struct_name).as_bytes())?;
         writer.write_all(b"\n")?;
 
         // Store parameters in fields.
-        for (name, field_type) in struct_fields {
-            writer.write_all(format!("    pub {}: {},", name, field_type).as_bytes())?;
-            writer.write_all(b"\n")?;
-        }
+        self.define_fields(writer, node)?;
 
-        writer.write_all(b"    _marker_1: marker::PhantomData<T>,")?;
+        // function pointer markers are `Send + Sync` whatever the result and visitor types
+        writer.write_all(b"    _marker_1: marker::PhantomData<fn() -> T>,")?;
         writer.write_all(b"\n")?;
-        writer.write_all(b"    _marker_2: marker::PhantomData<V>,")?;
+        writer.write_all(b"    _marker_2: marker::PhantomData<fn() -> V>,")?;
         writer.write_all(b"\n")?;
         writer.write_all(b"}")?;
 
@@ -421,7 +563,8 @@ impl GenerateAst {
         writer.write_all(format!("impl<T, V> {struct_name}<T, V> {{").as_bytes())?;
         writer.write_all(b"\n")?;
         // Pass arguments to constructor
-        let arguments = struct_fields
+        let arguments = node
+            .fields
             .iter()
             .map(|(a, b)| format!("{}: {}", a, b))
             .collect::<Vec<String>>()
@@ -431,18 +574,27 @@ impl GenerateAst {
 
         writer.write_all(format!("        {} {{", struct_name).as_bytes())?;
         writer.write_all(b"\n")?;
-        for (name, ..) in struct_fields {
+        for (name, ..) in &node.fields {
             writer.write_all(format!("            {},", name).as_bytes())?;
             writer.write_all(b"\n")?;
         }
-        writer.write_all(b"            _marker_1: marker::PhantomData::default(),")?;
+        for field in &node.private_fields {
+            writer.write_all(format!("            {}: {},", field.name, field.init).as_bytes())?;
+            writer.write_all(b"\n")?;
+        }
+        writer.write_all(b"            _marker_1: marker::PhantomData,")?;
         writer.write_all(b"\n")?;
-        writer.write_all(b"            _marker_2: marker::PhantomData::default(),")?;
+        writer.write_all(b"            _marker_2: marker::PhantomData,")?;
         writer.write_all(b"\n")?;
         writer.write_all(b"        }")?;
         writer.write_all(b"\n")?;
         writer.write_all(b"    }")?;
         writer.write_all(b"\n")?;
+        if let Some(methods) = node.methods {
+            writer.write_all(b"\n")?;
+            writer.write_all(methods.as_bytes())?;
+            writer.write_all(b"\n")?;
+        }
         writer.write_all(b"}")?;
 
         writer.write_all(b"\n\n")?;
@@ -456,28 +608,84 @@ impl GenerateAst {
             .as_bytes(),
         )?;
         writer.write_all(b"\n")?;
-        writer.write_all(b"    fn accept(&self, visitor: &V) -> T {")?;
+        writer.write_all(b"    fn accept(&self, visitor: &mut V) -> T {")?;
         writer.write_all(b"\n")?;
         writer.write_all(
             format!(
                 "\t\tvisitor.visit_{}_{}(self)",
-                struct_name.to_lowercase(),
+                snake_case(struct_name),
                 base_name.to_lowercase()
             )
             .as_bytes(),
         )?;
         writer.write_all(b"\n")?;
         writer.write_all(b"    }")?;
+        writer.write_all(b"\n\n")?;
+        writer.write_all(b"    fn kind(&self) -> ExprKind<'_, T, V> {")?;
+        writer.write_all(b"\n")?;
+        writer.write_all(format!("        ExprKind::{}(self)", struct_name).as_bytes())?;
+        writer.write_all(b"\n")?;
+        writer.write_all(b"    }")?;
         writer.write_all(b"\n")?;
         writer.write_all(b"}")?;
 
         writer.write_all(b"\n\n")?;
 
-        // struct display trait impl
+        self.define_display(writer, node, "<T, V: Visitor<T>>", "<T, V>")?;
+        self.define_debug(writer, struct_name, "<T, V: Visitor<T>>", "<T, V>")
+    }
+
+    /// public fields of the node in the order of the constructor arguments, then its private
+    /// fields
+    fn define_fields(&self, writer: &mut LineWriter<File>, node: &Node) -> Result<()> {
+        for (name, field_type) in &node.fields {
+            let doc = node
+                .field_docs
+                .iter()
+                .find(|(field, _)| field == name)
+                .map(|(_, doc)| *doc);
+            self.define_docs(writer, doc, "    ")?;
+            writer.write_all(format!("    pub {}: {},", name, field_type).as_bytes())?;
+            writer.write_all(b"\n")?;
+        }
+
+        for field in &node.private_fields {
+            self.define_docs(writer, Some(field.doc), "    ")?;
+            writer.write_all(format!("    {}: {},", field.name, field.field_type).as_bytes())?;
+            writer.write_all(b"\n")?;
+        }
+
+        Ok(())
+    }
+
+    fn define_docs(
+        &self,
+        writer: &mut LineWriter<File>,
+        doc: Option<&str>,
+        indent: &str,
+    ) -> Result<()> {
+        for line in doc.iter().flat_map(|doc| doc.lines()) {
+            writer.write_all(format!("{}/// {}", indent, line).trim_end().as_bytes())?;
+            writer.write_all(b"\n")?;
+        }
+
+        Ok(())
+    }
+
+    /// `Display` impl writing the node with the body given by `Node::display` or by default its
+    /// fields separated by a space. Lists are joined with `, ` and a missing optional field is
+    /// left out.
+    fn define_display(
+        &self,
+        writer: &mut LineWriter<File>,
+        node: &Node,
+        generics: &str,
+        type_generics: &str,
+    ) -> Result<()> {
         writer.write_all(
             format!(
-                "impl<T, V: Visitor<T>> Display for {}<T, V> {{",
-                struct_name
+                "impl{} Display for {}{} {{",
+                generics, node.name, type_generics
             )
             .as_bytes(),
         )?;
@@ -485,26 +693,321 @@ impl GenerateAst {
         writer.write_all(b"    fn fmt(&self, f: &mut Formatter<'_>) -> Result {")?;
         writer.write_all(b"\n")?;
 
-        let inner_brace: String = struct_fields
-            .iter()
-            .map(|_| "{}".to_string())
-            .collect::<Vec<_>>()
-            .join(" ");
-        let field_ref: String = struct_fields
-            .iter()
-            .map(|(a, _)| format!("self.{}", a))
-            .collect::<Vec<_>>()
-            .join(", ");
+        match node.display {
+            Some(body) => writer.write_all(body.as_bytes())?,
+            None => {
+                let mut field_ref = Vec::with_capacity(node.fields.len());
+                for (name, field_type) in &node.fields {
+                    if field_type.starts_with("Vec<") {
+                        writer.write_all(
+                            format!(
+                                "        let {0} = self.{0}.iter().map(|e| e.to_string()).collect::<Vec<_>>().join(\", \");",
+                                name
+                            )
+                            .as_bytes(),
+                        )?;
+                        writer.write_all(b"\n")?;
+                        field_ref.push(name.to_string());
+                    } else if field_type.starts_with("Option<") {
+                        writer.write_all(
+                            format!(
+                                "        let {0} = self.{0}.iter().map(|e| format!(\" {{}}\", e)).collect::<String>();",
+                                name
+                            )
+                            .as_bytes(),
+                        )?;
+                        writer.write_all(b"\n")?;
+                        field_ref.push(name.to_string());
+                    } else {
+                        field_ref.push(format!("self.{}", name));
+                    }
+                }
+
+                // optional fields bring their own leading space
+                let inner_brace: String = node
+                    .fields
+                    .iter()
+                    .enumerate()
+                    .map(
+                        |(i, (_, field_type))| match (i, field_type.starts_with("Option<")) {
+                            (0, _) | (_, true) => "{}",
+                            _ => " {}",
+                        },
+                    )
+                    .collect();
+
+                writer.write_all(
+                    format!(
+                        "        write!(f, \"{}\", {})",
+                        inner_brace,
+                        field_ref.join(", ")
+                    )
+                    .as_bytes(),
+                )?;
+            }
+        }
+        writer.write_all(b"\n")?;
+        writer.write_all(b"    }")?;
+
+        writer.write_all(b"\n")?;
+        writer.write_all(b"}")?;
+        writer.write_all(b"\n\n")?;
+        Ok(())
+    }
 
+    /// `Debug` impl going through `Display`, a derive would need every visitor and result type
+    /// to be `Debug` as the boxed `dyn Expr`/`dyn Stmt` fields are only `Display`
+    fn define_debug(
+        &self,
+        writer: &mut LineWriter<File>,
+        struct_name: &str,
+        generics: &str,
+        type_generics: &str,
+    ) -> Result<()> {
         writer.write_all(
-            format!("        write!(f, \"{}\", {})", inner_brace, field_ref,).as_bytes(),
+            format!(
+                "impl{} Debug for {}{} {{",
+                generics, struct_name, type_generics
+            )
+            .as_bytes(),
         )?;
         writer.write_all(b"\n")?;
+        writer.write_all(b"    fn fmt(&self, f: &mut Formatter<'_>) -> Result {")?;
+        writer.write_all(b"\n")?;
+        writer
+            .write_all(format!("        write!(f, \"{}({{}})\", self)", struct_name).as_bytes())?;
+        writer.write_all(b"\n")?;
         writer.write_all(b"    }")?;
-
         writer.write_all(b"\n")?;
         writer.write_all(b"}")?;
         writer.write_all(b"\n\n")?;
         Ok(())
     }
 }
+
+/// converts a node name to the snake case used in the visitor methods, `IndexSet` becomes
+/// `index_set`
+fn snake_case(name: &str) -> String {
+    let mut snake = String::with_capacity(name.len() + 2);
+    for (i, c) in name.chars().enumerate() {
+        if c.is_uppercase() {
+            if i > 0 {
+                snake.push('_');
+            }
+            snake.extend(c.to_lowercase());
+        } else {
+            snake.push(c);
+        }
+    }
+    snake
+}
+
+/// Formats a generated file in place with the `rustfmt` cli
+///
+/// # Errors
+///
+/// * `rustfmt` isn't installed
+/// * `rustfmt` fails on the file, the generated code doesn't parse
+fn format_file(file_path: &str) -> Result<()> {
+    let status = Command::new("rustfmt")
+        .args(["--edition", "2021", file_path])
+        .status()
+        .map_err(|err| match err.kind() {
+            ErrorKind::NotFound => Error::new(
+                ErrorKind::NotFound,
+                "rustfmt is not installed, add it with `rustup component add rustfmt`",
+            ),
+            _ => err,
+        })?;
+
+    if !status.success() {
+        return Err(Error::other(format!(
+            "rustfmt failed to format {}",
+            file_path
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod generator_tests {
+    use super::*;
+    use std::fs;
+
+    fn binary() -> Vec<Node<'static>> {
+        vec![Node::new(
+            "Binary",
+            vec![
+                ("left", "Box<dyn Expr<T, V>>"),
+                ("operator", "token::Token"),
+                ("right", "Box<dyn Expr<T, V>>"),
+            ],
+        )]
+    }
+
+    fn print() -> Vec<Node<'static>> {
+        vec![Node::new(
+            "Print",
+            vec![("expression", "Box<dyn Expr<T, V>>")],
+        )]
+    }
+
+    #[test]
+    fn generated_nodes_implement_debug() {
+        let dir = std::env::temp_dir().join(format!("ast-generator-debug-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let generator = GenerateAst::for_path(dir.clone()).unwrap();
+        generator
+            .define_expr_ast("Expr", &[], &[], &binary())
+            .unwrap();
+        generator
+            .define_stmt_ast("Stmt", &[], &[], &print())
+            .unwrap();
+
+        let expr = fs::read_to_string(dir.join("expr.rs")).unwrap();
+        assert!(expr.contains("use std::fmt::{Debug, Display, Formatter, Result};"));
+        assert!(expr.contains("impl<T, V: Visitor<T>> Debug for Binary<T, V> {"));
+        assert!(expr.contains("write!(f, \"Binary({})\", self)"));
+
+        let stmt = fs::read_to_string(dir.join("stmt.rs")).unwrap();
+        assert!(stmt.contains("impl<T, U: Visitor<T, V>, V> Debug for Print<T, U, V> {"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn generated_files_are_formatted() {
+        let dir = std::env::temp_dir().join(format!("ast-generator-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let generator = GenerateAst::for_path(dir.clone()).unwrap();
+        generator
+            .define_expr_ast("Expr", &[], &[], &binary())
+            .unwrap();
+        generator
+            .define_stmt_ast("Stmt", &[], &[], &print())
+            .unwrap();
+
+        for name in ["expr.rs", "stmt.rs"] {
+            let path = dir.join(name);
+            let source = fs::read_to_string(&path).unwrap();
+            assert!(!source.contains('\t'), "{}", name);
+            assert!(source.contains("Debug for "), "{}", name);
+
+            // formatting once more doesn't change anything
+            let check = Command::new("rustfmt")
+                .args(["--edition", "2021", "--check"])
+                .arg(&path)
+                .output()
+                .unwrap();
+            assert!(check.status.success(), "{}", name);
+        }
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn visitor_methods_are_snake_case() {
+        let dir = std::env::temp_dir().join(format!("ast-generator-visit-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let generator = GenerateAst::for_path(dir.clone()).unwrap();
+        let types = [Node::new(
+            "IndexSet",
+            vec![("value", "Box<dyn Expr<T, V>>")],
+        )];
+        generator.define_expr_ast("Expr", &[], &[], &types).unwrap();
+
+        let expr = fs::read_to_string(dir.join("expr.rs")).unwrap();
+        assert!(expr.contains("fn visit_index_set_expr(&mut self, expr: &IndexSet<T, Self>) -> T;"));
+        assert!(expr.contains("visitor.visit_index_set_expr(self)"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn display_joins_list_and_optional_fields() {
+        let dir =
+            std::env::temp_dir().join(format!("ast-generator-display-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let generator = GenerateAst::for_path(dir.clone()).unwrap();
+        let types = [
+            Node::new(
+                "Call",
+                vec![
+                    ("callee", "Box<dyn Expr<T, V>>"),
+                    ("arguments", "Vec<Box<dyn Expr<T, V>>>"),
+                    ("label", "Option<String>"),
+                ],
+            )
+            .doc("Call of a function\nwith its arguments")
+            .field_doc("label", "optional label"),
+            Node::new("Grouping", vec![("expression", "Box<dyn Expr<T, V>>")])
+                .display("write!(f, \"({})\", self.expression)"),
+        ];
+        generator.define_expr_ast("Expr", &[], &[], &types).unwrap();
+
+        let expr = fs::read_to_string(dir.join("expr.rs")).unwrap();
+        assert!(expr.contains("/// Call of a function\n/// with its arguments\npub struct Call"));
+        assert!(expr.contains("    /// optional label\n    pub label: Option<String>,"));
+        assert!(expr.contains(".join(\", \");"));
+        assert!(expr.contains(".map(|e| format!(\" {}\", e))"));
+        assert!(expr.contains("write!(f, \"{} {}{}\", self.callee, arguments, label)"));
+        assert!(expr.contains("write!(f, \"({})\", self.expression)"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn nodes_hand_out_their_kind() {
+        let dir = std::env::temp_dir().join(format!("ast-generator-kind-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let generator = GenerateAst::for_path(dir.clone()).unwrap();
+        generator
+            .define_expr_ast("Expr", &[], &[], &binary())
+            .unwrap();
+        generator
+            .define_stmt_ast("Stmt", &[], &[], &print())
+            .unwrap();
+
+        let expr = fs::read_to_string(dir.join("expr.rs")).unwrap();
+        assert!(expr.contains("pub enum ExprKind<'a, T, V> {\n    Binary(&'a Binary<T, V>),\n}"));
+        assert!(expr.contains("ExprKind::Binary(self)"));
+
+        let stmt = fs::read_to_string(dir.join("stmt.rs")).unwrap();
+        assert!(
+            stmt.contains("pub enum StmtKind<'a, T, U, V> {\n    Print(&'a Print<T, U, V>),\n}")
+        );
+        assert!(stmt.contains("fn kind(&self) -> StmtKind<'_, T, U, V>;"));
+        assert!(stmt.contains("StmtKind::Print(self)"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn private_fields_start_from_their_init() {
+        let dir =
+            std::env::temp_dir().join(format!("ast-generator-private-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let generator = GenerateAst::for_path(dir.clone()).unwrap();
+        let types = [Node::new("Literal", vec![("value", "token::Literal")])
+            .private_field("cache", "OnceCell<String>", "OnceCell::new()", "cached text")
+            .methods("pub fn text(&self) -> &str {\n    self.cache.get_or_init(|| self.value.to_string())\n}")];
+        generator
+            .define_expr_ast("Expr", &["std::cell::OnceCell"], &[], &types)
+            .unwrap();
+
+        let expr = fs::read_to_string(dir.join("expr.rs")).unwrap();
+        assert!(expr.contains("use std::cell::OnceCell;"));
+        assert!(expr.contains("    /// cached text\n    cache: OnceCell<String>,"));
+        assert!(expr.contains("            cache: OnceCell::new(),"));
+        assert!(expr.contains("    pub fn text(&self) -> &str {"));
+        assert!(!expr.contains("pub cache"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,365 @@
+use crate::ast::expr::{
+    Assign, Binary, Call, Expr, Grouping, Index, IndexSet, Lambda, List, Literal, Logical, Map,
+    Unary, Variable,
+};
+use crate::ast::stmt::{
+    Block, Const, Expression, ForEach, Function, If, Let, Print, Return, Stmt, While,
+};
+use crate::ast::{expr, stmt};
+use crate::token::{LiteralKind, Token};
+
+/// Dumps the AST as JSON, every node is an object whose `type` is the name of the node and
+/// whose other keys are the fields of the node
+///
+/// # Example
+///
+/// ```
+/// use oxa::{ast::{expr, json}, token};
+///
+/// let expr = expr::Binary::new(
+///     Box::new(expr::Literal::new(token::Literal::from(1))),
+///     token::Token::new(token::TokenKind::Plus, "+", None, 1, 1),
+///     Box::new(expr::Literal::new(token::Literal::from(2))),
+/// );
+/// let mut json = json::AstJson {};
+///
+/// assert_eq!(
+///     json.expr_to_json(&expr),
+///     r#"{"type":"Binary","operator":"+","left":{"type":"Literal","value":1},"right":{"type":"Literal","value":2}}"#
+/// );
+/// ```
+pub struct AstJson {}
+
+impl expr::Visitor<String> for AstJson {
+    fn visit_assign_expr(&mut self, expr: &Assign<String, Self>) -> String {
+        let value = expr.value.accept(self);
+        node("Assign", &[("name", lexeme(&expr.name)), ("value", value)])
+    }
+
+    fn visit_binary_expr(&mut self, expr: &Binary<String, Self>) -> String {
+        let left = expr.left.accept(self);
+        let right = expr.right.accept(self);
+        node(
+            "Binary",
+            &[
+                ("operator", lexeme(&expr.operator)),
+                ("left", left),
+                ("right", right),
+            ],
+        )
+    }
+
+    fn visit_call_expr(&mut self, expr: &Call<String, Self>) -> String {
+        let callee = expr.callee.accept(self);
+        let arguments = self.exprs(&expr.arguments);
+        node("Call", &[("callee", callee), ("arguments", arguments)])
+    }
+
+    fn visit_grouping_expr(&mut self, expr: &Grouping<String, Self>) -> String {
+        let expression = expr.expression.accept(self);
+        node("Grouping", &[("expression", expression)])
+    }
+
+    fn visit_index_expr(&mut self, expr: &Index<String, Self>) -> String {
+        let object = expr.object.accept(self);
+        let index = expr.index.accept(self);
+        node("Index", &[("object", object), ("index", index)])
+    }
+
+    fn visit_index_set_expr(&mut self, expr: &IndexSet<String, Self>) -> String {
+        let target = expr.target.accept(self);
+        let value = expr.value.accept(self);
+        node("IndexSet", &[("target", target), ("value", value)])
+    }
+
+    fn visit_list_expr(&mut self, expr: &List<String, Self>) -> String {
+        let elements = self.exprs(&expr.elements);
+        node("List", &[("elements", elements)])
+    }
+
+    fn visit_literal_expr(&mut self, expr: &Literal<String, Self>) -> String {
+        let value = match &expr.value.value {
+            LiteralKind::Number(n) => n.to_string(),
+            LiteralKind::Float(f) if f.is_finite() => f.to_string(),
+            LiteralKind::Float(_) | LiteralKind::Nil => String::from("null"),
+            LiteralKind::String(s) => string(s),
+            LiteralKind::Bool(b) => b.to_string(),
+        };
+        node("Literal", &[("value", value)])
+    }
+
+    fn visit_lambda_expr(&mut self, expr: &Lambda<String, Self>) -> String {
+        let params = array(expr.params.iter().map(lexeme));
+        let body = self.stmts(expr.body.iter());
+        node("Lambda", &[("params", params), ("body", body)])
+    }
+
+    fn visit_logical_expr(&mut self, expr: &Logical<String, Self>) -> String {
+        let left = expr.left.accept(self);
+        let right = expr.right.accept(self);
+        node(
+            "Logical",
+            &[
+                ("operator", lexeme(&expr.operator)),
+                ("left", left),
+                ("right", right),
+            ],
+        )
+    }
+
+    fn visit_map_expr(&mut self, expr: &Map<String, Self>) -> String {
+        let entries = expr
+            .entries
+            .iter()
+            .map(|(key, value)| {
+                let key = key.accept(self);
+                let value = value.accept(self);
+                format!("{{\"key\":{},\"value\":{}}}", key, value)
+            })
+            .collect::<Vec<_>>();
+        node("Map", &[("entries", array(entries))])
+    }
+
+    fn visit_unary_expr(&mut self, expr: &Unary<String, Self>) -> String {
+        let right = expr.right.accept(self);
+        node(
+            "Unary",
+            &[("operator", lexeme(&expr.operator)), ("right", right)],
+        )
+    }
+
+    fn visit_variable_expr(&mut self, expr: &Variable<String, Self>) -> String {
+        node("Variable", &[("name", lexeme(&expr.name))])
+    }
+}
+
+impl stmt::Visitor<String, Self> for AstJson {
+    fn visit_expression_stmt(&mut self, stmt: &Expression<String, Self, Self>) -> String {
+        let expression = stmt.expression.accept(self);
+        node("Expression", &[("expression", expression)])
+    }
+
+    fn visit_print_stmt(&mut self, stmt: &Print<String, Self, Self>) -> String {
+        let expression = stmt.expression.accept(self);
+        node("Print", &[("expression", expression)])
+    }
+
+    fn visit_let_stmt(&mut self, stmt: &Let<String, Self, Self>) -> String {
+        let initializer = stmt.initializer.accept(self);
+        node(
+            "Let",
+            &[("name", lexeme(&stmt.name)), ("initializer", initializer)],
+        )
+    }
+
+    fn visit_const_stmt(&mut self, stmt: &Const<String, Self, Self>) -> String {
+        let initializer = stmt.initializer.accept(self);
+        node(
+            "Const",
+            &[("name", lexeme(&stmt.name)), ("initializer", initializer)],
+        )
+    }
+
+    fn visit_block_stmt(&mut self, stmt: &Block<String, Self, Self>) -> String {
+        let statements = self.stmts(stmt.statements.iter());
+        node("Block", &[("statements", statements)])
+    }
+
+    fn visit_for_each_stmt(&mut self, stmt: &ForEach<String, Self, Self>) -> String {
+        let iterable = stmt.iterable.accept(self);
+        let body = stmt.body.accept(self);
+        node(
+            "ForEach",
+            &[
+                ("variable", lexeme(&stmt.variable)),
+                ("iterable", iterable),
+                ("body", body),
+            ],
+        )
+    }
+
+    fn visit_function_stmt(&mut self, stmt: &Function<String, Self, Self>) -> String {
+        let params = array(stmt.params.iter().map(lexeme));
+        let body = self.stmts(stmt.body.iter());
+        node(
+            "Function",
+            &[
+                ("name", lexeme(&stmt.name)),
+                ("params", params),
+                ("body", body),
+            ],
+        )
+    }
+
+    fn visit_if_stmt(&mut self, stmt: &If<String, Self, Self>) -> String {
+        let condition = stmt.condition.accept(self);
+        let then_branch = stmt.then_branch.accept(self);
+        let else_branch = match &stmt.else_branch {
+            Some(else_branch) => else_branch.accept(self),
+            None => String::from("null"),
+        };
+        node(
+            "If",
+            &[
+                ("condition", condition),
+                ("then_branch", then_branch),
+                ("else_branch", else_branch),
+            ],
+        )
+    }
+
+    fn visit_return_stmt(&mut self, stmt: &Return<String, Self, Self>) -> String {
+        let value = stmt.value.accept(self);
+        node("Return", &[("value", value)])
+    }
+
+    fn visit_while_stmt(&mut self, stmt: &While<String, Self, Self>) -> String {
+        let condition = stmt.condition.accept(self);
+        let body = stmt.body.accept(self);
+        node("While", &[("condition", condition), ("body", body)])
+    }
+}
+
+impl AstJson {
+    pub fn expr_to_json(&mut self, expr: &dyn Expr<String, Self>) -> String {
+        expr.accept(self)
+    }
+
+    pub fn stmt_to_json(&mut self, stmt: &dyn Stmt<String, Self, Self>) -> String {
+        stmt.accept(self)
+    }
+
+    /// dumps a whole program as a JSON array of statements
+    pub fn program_to_json(&mut self, statements: &[Box<dyn Stmt<String, Self, Self>>]) -> String {
+        self.stmts(statements.iter())
+    }
+
+    fn exprs(&mut self, exprs: &[Box<dyn Expr<String, Self>>]) -> String {
+        let exprs = exprs.iter().map(|e| e.accept(self)).collect::<Vec<_>>();
+        array(exprs)
+    }
+
+    fn stmts<'a>(
+        &mut self,
+        stmts: impl Iterator<Item = &'a Box<dyn Stmt<String, Self, Self>>>,
+    ) -> String {
+        let stmts = stmts.map(|s| s.accept(self)).collect::<Vec<_>>();
+        array(stmts)
+    }
+}
+
+/// JSON object of a node, `type` comes first and the fields keep their order
+fn node(kind: &str, fields: &[(&str, String)]) -> String {
+    let mut string = format!("{{\"type\":{}", self::string(kind));
+
+    for (key, value) in fields {
+        string.push_str(&format!(",{}:{}", self::string(key), value));
+    }
+
+    string.push('}');
+    string
+}
+
+fn array(items: impl IntoIterator<Item = String>) -> String {
+    format!("[{}]", items.into_iter().collect::<Vec<_>>().join(","))
+}
+
+fn lexeme(token: &Token) -> String {
+    string(&token.lexeme)
+}
+
+/// JSON string literal, quotes, backslashes and control characters are escaped
+fn string(s: &str) -> String {
+    let mut string = String::with_capacity(s.len() + 2);
+    string.push('"');
+
+    for c in s.chars() {
+        match c {
+            '"' => string.push_str("\\\""),
+            '\\' => string.push_str("\\\\"),
+            '\n' => string.push_str("\\n"),
+            '\r' => string.push_str("\\r"),
+            '\t' => string.push_str("\\t"),
+            c if c.is_control() => string.push_str(&format!("\\u{:04x}", c as u32)),
+            c => string.push(c),
+        }
+    }
+
+    string.push('"');
+    string
+}
+
+#[cfg(test)]
+mod json_tests {
+    use crate::ast::expr::{Binary, Literal};
+    use crate::ast::json::AstJson;
+    use crate::parser::Parser;
+    use crate::scanner::ScannerBuilder;
+    use crate::token;
+    use crate::token::{Token, TokenKind};
+
+    fn program_to_json(source: &str) -> String {
+        let tokens = ScannerBuilder::default()
+            .source(source)
+            .build()
+            .scan_tokens()
+            .unwrap();
+        let statements = Parser::from_tokens(&tokens).parse().unwrap();
+        AstJson {}.program_to_json(&statements)
+    }
+
+    #[test]
+    fn binary_expr_to_json() {
+        let expr = Binary::new(
+            Box::new(Literal::new(token::Literal::from(1))),
+            Token::new(TokenKind::Plus, "+", None, 1, 1),
+            Box::new(Literal::new(token::Literal::from(2))),
+        );
+
+        assert_eq!(
+            AstJson {}.expr_to_json(&expr),
+            r#"{"type":"Binary","operator":"+","left":{"type":"Literal","value":1},"right":{"type":"Literal","value":2}}"#
+        );
+    }
+
+    #[test]
+    fn literals_to_json() {
+        assert_eq!(
+            program_to_json("\"a \\\"b\\\"\\n\"; 2.5; true; nil;"),
+            concat!(
+                r#"[{"type":"Expression","expression":{"type":"Literal","value":"a \"b\"\n"}},"#,
+                r#"{"type":"Expression","expression":{"type":"Literal","value":2.5}},"#,
+                r#"{"type":"Expression","expression":{"type":"Literal","value":true}},"#,
+                r#"{"type":"Expression","expression":{"type":"Literal","value":null}}]"#,
+            )
+        );
+    }
+
+    #[test]
+    fn statements_to_json() {
+        let json = program_to_json(
+            "fun f(a) { if (a) return -a; else return [a]; }
+            let m = {1: f(2)};
+            while (false) print m[1];
+            for (x in [1]) { x = (x) or 1; }",
+        );
+
+        assert_eq!(
+            json,
+            concat!(
+                r#"[{"type":"Function","name":"f","params":["a"],"body":["#,
+                r#"{"type":"If","condition":{"type":"Variable","name":"a"},"#,
+                r#""then_branch":{"type":"Return","value":{"type":"Unary","operator":"-","right":{"type":"Variable","name":"a"}}},"#,
+                r#""else_branch":{"type":"Return","value":{"type":"List","elements":[{"type":"Variable","name":"a"}]}}}]},"#,
+                r#"{"type":"Let","name":"m","initializer":{"type":"Map","entries":[{"key":{"type":"Literal","value":1},"#,
+                r#""value":{"type":"Call","callee":{"type":"Variable","name":"f"},"arguments":[{"type":"Literal","value":2}]}}]}},"#,
+                r#"{"type":"While","condition":{"type":"Literal","value":false},"body":{"type":"Print","expression":"#,
+                r#"{"type":"Index","object":{"type":"Variable","name":"m"},"index":{"type":"Literal","value":1}}}},"#,
+                r#"{"type":"ForEach","variable":"x","iterable":{"type":"List","elements":[{"type":"Literal","value":1}]},"#,
+                r#""body":{"type":"Block","statements":[{"type":"Expression","expression":{"type":"Assign","name":"x","#,
+                r#""value":{"type":"Logical","operator":"or","left":{"type":"Grouping","expression":{"type":"Variable","name":"x"}},"#,
+                r#""right":{"type":"Literal","value":1}}}}]}}]"#,
+            )
+        );
+    }
+}
@@ -1,3 +1,4 @@
 pub mod expr;
+pub mod json;
 pub mod printer;
 pub mod stmt;
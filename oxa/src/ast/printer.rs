@@ -1,17 +1,22 @@
-use crate::ast::expr::{Assign, Binary, Expr, Grouping, Literal, Unary, Variable};
-use crate::ast::stmt::{Const, Expression, Let, Print, Stmt};
+use crate::ast::expr::{
+    Assign, Binary, Call, Expr, Grouping, Index, IndexSet, Lambda, List, Literal, Logical, Map,
+    Unary, Variable,
+};
+use crate::ast::stmt::{
+    Block, Const, Expression, ForEach, Function, If, Let, Print, Return, Stmt, While,
+};
 use crate::ast::{expr, stmt};
 
 pub struct AstPrinter {}
 
 impl expr::Visitor<String> for AstPrinter {
-    fn visit_assign_expr(&self, expr: &Assign<String, Self>) -> String {
+    fn visit_assign_expr(&mut self, expr: &Assign<String, Self>) -> String {
         let value = expr.value.accept(self);
 
         format!("{} = {}", expr.name.lexeme, value)
     }
 
-    fn visit_binary_expr(&self, expr: &Binary<String, Self>) -> String {
+    fn visit_binary_expr(&mut self, expr: &Binary<String, Self>) -> String {
         parenthesize(
             self,
             &expr.operator.lexeme,
@@ -19,19 +24,77 @@ impl expr::Visitor<String> for AstPrinter {
         )
     }
 
-    fn visit_grouping_expr(&self, expr: &Grouping<String, Self>) -> String {
+    fn visit_call_expr(&mut self, expr: &Call<String, Self>) -> String {
+        let mut exprs = vec![expr.callee.as_ref()];
+        exprs.extend(expr.arguments.iter().map(|a| a.as_ref()));
+
+        parenthesize(self, "call", &exprs)
+    }
+
+    fn visit_grouping_expr(&mut self, expr: &Grouping<String, Self>) -> String {
         parenthesize(self, "group", &[expr.expression.as_ref()])
     }
 
-    fn visit_literal_expr(&self, expr: &Literal<String, Self>) -> String {
+    fn visit_index_expr(&mut self, expr: &Index<String, Self>) -> String {
+        parenthesize(self, "index", &[expr.object.as_ref(), expr.index.as_ref()])
+    }
+
+    fn visit_index_set_expr(&mut self, expr: &IndexSet<String, Self>) -> String {
+        let target = expr.target.accept(self);
+        let value = expr.value.accept(self);
+
+        format!("{} = {}", target, value)
+    }
+
+    fn visit_list_expr(&mut self, expr: &List<String, Self>) -> String {
+        let elements: Vec<_> = expr.elements.iter().map(|e| e.as_ref()).collect();
+        parenthesize(self, "list", &elements)
+    }
+
+    fn visit_literal_expr(&mut self, expr: &Literal<String, Self>) -> String {
         expr.value.to_string()
     }
 
-    fn visit_unary_expr(&self, expr: &Unary<String, Self>) -> String {
+    fn visit_lambda_expr(&mut self, expr: &Lambda<String, Self>) -> String {
+        let params = expr
+            .params
+            .iter()
+            .map(|p| p.lexeme.clone())
+            .collect::<Vec<_>>()
+            .join(" ");
+        let mut string = format!("(fun ({})", params);
+
+        for statement in expr.body.iter() {
+            string.push(' ');
+            string.push_str(&statement.accept(self));
+        }
+
+        string.push(')');
+        string
+    }
+
+    fn visit_logical_expr(&mut self, expr: &Logical<String, Self>) -> String {
+        parenthesize(
+            self,
+            &expr.operator.lexeme,
+            &[expr.left.as_ref(), expr.right.as_ref()],
+        )
+    }
+
+    fn visit_map_expr(&mut self, expr: &Map<String, Self>) -> String {
+        let entries: Vec<_> = expr
+            .entries
+            .iter()
+            .flat_map(|(k, v)| [k.as_ref(), v.as_ref()])
+            .collect();
+        parenthesize(self, "map", &entries)
+    }
+
+    fn visit_unary_expr(&mut self, expr: &Unary<String, Self>) -> String {
         parenthesize(self, &expr.operator.lexeme, &[expr.right.as_ref()])
     }
 
-    fn visit_variable_expr(&self, expr: &Variable<String, Self>) -> String {
+    fn visit_variable_expr(&mut self, expr: &Variable<String, Self>) -> String {
         expr.name.to_string()
     }
 }
@@ -56,10 +119,70 @@ impl stmt::Visitor<String, Self> for AstPrinter {
         let value = stmt.initializer.accept(self);
         format!("const {}", value)
     }
+
+    fn visit_block_stmt(&mut self, stmt: &Block<String, Self, Self>) -> String {
+        let mut string = String::from("(block");
+
+        for statement in &stmt.statements {
+            string.push(' ');
+            string.push_str(&statement.accept(self));
+        }
+
+        string.push(')');
+        string
+    }
+
+    fn visit_for_each_stmt(&mut self, stmt: &ForEach<String, Self, Self>) -> String {
+        let iterable = stmt.iterable.accept(self);
+        let body = stmt.body.accept(self);
+        format!("(for {} in {} {})", stmt.variable.lexeme, iterable, body)
+    }
+
+    fn visit_function_stmt(&mut self, stmt: &Function<String, Self, Self>) -> String {
+        let params = stmt
+            .params
+            .iter()
+            .map(|p| p.lexeme.clone())
+            .collect::<Vec<_>>()
+            .join(" ");
+        let mut string = format!("(fun {} ({})", stmt.name.lexeme, params);
+
+        for statement in stmt.body.iter() {
+            string.push(' ');
+            string.push_str(&statement.accept(self));
+        }
+
+        string.push(')');
+        string
+    }
+
+    fn visit_if_stmt(&mut self, stmt: &If<String, Self, Self>) -> String {
+        let condition = stmt.condition.accept(self);
+        let then_branch = stmt.then_branch.accept(self);
+
+        match &stmt.else_branch {
+            Some(else_branch) => {
+                let else_branch = else_branch.accept(self);
+                format!("(if {} {} {})", condition, then_branch, else_branch)
+            }
+            None => format!("(if {} {})", condition, then_branch),
+        }
+    }
+
+    fn visit_return_stmt(&mut self, stmt: &Return<String, Self, Self>) -> String {
+        let value = stmt.value.accept(self);
+        format!("return {}", value)
+    }
+
+    fn visit_while_stmt(&mut self, stmt: &While<String, Self, Self>) -> String {
+        let condition = stmt.condition.accept(self);
+        let body = stmt.body.accept(self);
+        format!("(while {} {})", condition, body)
+    }
 }
 
 impl AstPrinter {
-    pub fn print_expr(&self, expr: &dyn Expr<String, Self>) -> String {
+    pub fn print_expr(&mut self, expr: &dyn Expr<String, Self>) -> String {
         expr.accept(self)
     }
 
@@ -79,16 +202,16 @@ impl AstPrinter {
 /// use oxa::{ast::{expr, printer}, token};
 ///
 /// let expr = expr::Unary::new(
-///     token::Token::new(token::TokenKind::Plus, "+", None, 1),
+///     token::Token::new(token::TokenKind::Plus, "+", None, 1, 1),
 ///     Box::new(expr::Literal::new(token::Literal::from(2)))
 /// );
-/// let printer = printer::AstPrinter {};
-/// let value = printer::parenthesize(&printer, &expr.operator.lexeme, &[expr.right.as_ref()]);
+/// let mut printer = printer::AstPrinter {};
+/// let value = printer::parenthesize(&mut printer, &expr.operator.lexeme, &[expr.right.as_ref()]);
 ///
 /// assert_eq!(&value, "(+ 2)");
 /// ```
 pub fn parenthesize<V: expr::Visitor<String>>(
-    visitor: &V,
+    visitor: &mut V,
     name: &str,
     exprs: &[&dyn Expr<String, V>],
 ) -> String {
@@ -119,14 +242,14 @@ mod parenthesize_tests {
     fn parenthesize_binary_expr() {
         let expr = Binary::new(
             Box::new(Literal::new(token::Literal::from(1))),
-            Token::new(TokenKind::Plus, "+", None, 1),
+            Token::new(TokenKind::Plus, "+", None, 1, 1),
             Box::new(Literal::new(token::Literal::from(2))),
         );
 
-        let printer = AstPrinter {};
+        let mut printer = AstPrinter {};
 
         let value = parenthesize(
-            &printer,
+            &mut printer,
             &expr.operator.lexeme,
             &[expr.left.as_ref(), expr.right.as_ref()],
         );
@@ -138,15 +261,15 @@ mod parenthesize_tests {
     fn print_expr_test() {
         let expr = Binary::new(
             Box::new(Unary::new(
-                Token::new(TokenKind::Minus, "-", None, 1),
+                Token::new(TokenKind::Minus, "-", None, 1, 1),
                 Box::new(Literal::new(token::Literal::from(123))),
             )),
-            Token::new(TokenKind::Star, "*", None, 1),
+            Token::new(TokenKind::Star, "*", None, 1, 1),
             Box::new(Grouping::new(Box::new(Literal::new(token::Literal::from(
                 45.67,
             ))))),
         );
-        let printer = AstPrinter {};
+        let mut printer = AstPrinter {};
         let value = printer.print_expr(&expr);
         assert_eq!(&value, "(* (- 123) (group 45.67))");
     }
@@ -155,7 +278,7 @@ mod parenthesize_tests {
     fn print_stmt_test() {
         let expr = Binary::new(
             Box::new(Literal::new(token::Literal::from(1))),
-            Token::new(TokenKind::Plus, "+", None, 1),
+            Token::new(TokenKind::Plus, "+", None, 1, 1),
             Box::new(Literal::new(token::Literal::from(2))),
         );
 
@@ -169,11 +292,15 @@ mod parenthesize_tests {
     fn print_variable_test() {
         let expr = Binary::new(
             Box::new(Literal::new(token::Literal::from(1))),
-            Token::new(TokenKind::Plus, "+", None, 1),
+            Token::new(TokenKind::Plus, "+", None, 1, 1),
             Box::new(Literal::new(token::Literal::from(2))),
         );
 
-        let print_stmt = Let::new(Token::new(TokenKind::Let, "let", None, 1), Box::new(expr));
+        let print_stmt = Let::new(
+            Token::new(TokenKind::Let, "let", None, 1, 1),
+            Box::new(expr),
+            None,
+        );
         let mut printer = AstPrinter {};
         let value = printer.print_stmt(&print_stmt);
         assert_eq!(&value, "let (+ 1 2)");
@@ -183,19 +310,31 @@ mod parenthesize_tests {
     fn evaluate_assign_expr_test() {
         let expr = Binary::new(
             Box::new(Literal::new(token::Literal::from(1))),
-            Token::new(TokenKind::Plus, "+", None, 1),
+            Token::new(TokenKind::Plus, "+", None, 1, 1),
             Box::new(Literal::new(token::Literal::from(2))),
         );
 
         let assign = Assign::new(
-            Token::new(TokenKind::Identifier, "a", None, 1),
+            Token::new(TokenKind::Identifier, "a", None, 1, 1),
             Box::new(expr),
         );
 
-        let printer = AstPrinter {};
+        let mut printer = AstPrinter {};
 
         let value = printer.print_expr(&assign);
 
         assert_eq!(&value, "a = (+ 1 2)");
     }
+
+    #[test]
+    fn debug_nodes() {
+        let expr = Binary::new(
+            Box::new(Literal::new(token::Literal::from(1))),
+            Token::new(TokenKind::Plus, "+", None, 1, 1),
+            Box::new(Literal::new(token::Literal::from(2))),
+        );
+        let print_stmt = Print::<String, AstPrinter, AstPrinter>::new(Box::new(expr));
+
+        assert_eq!(format!("{:?}", print_stmt), "Print(1 + 2)");
+    }
 }
@@ -1,10 +1,29 @@
 use crate::ast::expr::Expr;
+use crate::shared::Handle;
+use crate::shared::ThreadSafe;
 use crate::token;
-use std::fmt::{Display, Formatter, Result};
+use std::fmt::{Debug, Display, Formatter, Result};
 use std::marker;
 
-pub trait Stmt<T, U: Visitor<T, V>, V>: Display {
+/// Statements of a function body, shared between the declaration and the runtime function
+pub type FunctionBody<T, U, V> = Handle<Vec<Box<dyn Stmt<T, U, V>>>>;
+
+pub enum StmtKind<'a, T, U, V> {
+    Expression(&'a Expression<T, U, V>),
+    Print(&'a Print<T, U, V>),
+    Let(&'a Let<T, U, V>),
+    Const(&'a Const<T, U, V>),
+    Block(&'a Block<T, U, V>),
+    ForEach(&'a ForEach<T, U, V>),
+    Function(&'a Function<T, U, V>),
+    If(&'a If<T, U, V>),
+    Return(&'a Return<T, U, V>),
+    While(&'a While<T, U, V>),
+}
+
+pub trait Stmt<T, U: Visitor<T, V>, V>: Display + ThreadSafe {
     fn accept(&self, visitor: &mut U) -> T;
+    fn kind(&self) -> StmtKind<'_, T, U, V>;
 }
 
 pub trait Visitor<T, V> {
@@ -12,22 +31,28 @@ pub trait Visitor<T, V> {
     fn visit_print_stmt(&mut self, stmt: &Print<T, Self, V>) -> T;
     fn visit_let_stmt(&mut self, stmt: &Let<T, Self, V>) -> T;
     fn visit_const_stmt(&mut self, stmt: &Const<T, Self, V>) -> T;
+    fn visit_block_stmt(&mut self, stmt: &Block<T, Self, V>) -> T;
+    fn visit_for_each_stmt(&mut self, stmt: &ForEach<T, Self, V>) -> T;
+    fn visit_function_stmt(&mut self, stmt: &Function<T, Self, V>) -> T;
+    fn visit_if_stmt(&mut self, stmt: &If<T, Self, V>) -> T;
+    fn visit_return_stmt(&mut self, stmt: &Return<T, Self, V>) -> T;
+    fn visit_while_stmt(&mut self, stmt: &While<T, Self, V>) -> T;
 }
 
 pub struct Expression<T, U: ?Sized, V: ?Sized> {
     pub expression: Box<dyn Expr<T, V>>,
-    _marker_1: marker::PhantomData<T>,
-    _marker_2: marker::PhantomData<U>,
-    _marker_3: marker::PhantomData<V>,
+    _marker_1: marker::PhantomData<fn() -> T>,
+    _marker_2: marker::PhantomData<fn() -> U>,
+    _marker_3: marker::PhantomData<fn() -> V>,
 }
 
 impl<T, U, V> Expression<T, U, V> {
     pub fn new(expression: Box<dyn Expr<T, V>>) -> Self {
         Expression {
             expression,
-            _marker_1: marker::PhantomData::default(),
-            _marker_2: marker::PhantomData::default(),
-            _marker_3: marker::PhantomData::default(),
+            _marker_1: marker::PhantomData,
+            _marker_2: marker::PhantomData,
+            _marker_3: marker::PhantomData,
         }
     }
 }
@@ -36,6 +61,10 @@ impl<T, U: Visitor<T, V>, V> Stmt<T, U, V> for Expression<T, U, V> {
     fn accept(&self, visitor: &mut U) -> T {
         visitor.visit_expression_stmt(self)
     }
+
+    fn kind(&self) -> StmtKind<'_, T, U, V> {
+        StmtKind::Expression(self)
+    }
 }
 
 impl<T, U: Visitor<T, V>, V> Display for Expression<T, U, V> {
@@ -44,20 +73,26 @@ impl<T, U: Visitor<T, V>, V> Display for Expression<T, U, V> {
     }
 }
 
+impl<T, U: Visitor<T, V>, V> Debug for Expression<T, U, V> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "Expression({})", self)
+    }
+}
+
 pub struct Print<T, U: ?Sized, V: ?Sized> {
     pub expression: Box<dyn Expr<T, V>>,
-    _marker_1: marker::PhantomData<T>,
-    _marker_2: marker::PhantomData<U>,
-    _marker_3: marker::PhantomData<V>,
+    _marker_1: marker::PhantomData<fn() -> T>,
+    _marker_2: marker::PhantomData<fn() -> U>,
+    _marker_3: marker::PhantomData<fn() -> V>,
 }
 
 impl<T, U, V> Print<T, U, V> {
     pub fn new(expression: Box<dyn Expr<T, V>>) -> Self {
         Print {
             expression,
-            _marker_1: marker::PhantomData::default(),
-            _marker_2: marker::PhantomData::default(),
-            _marker_3: marker::PhantomData::default(),
+            _marker_1: marker::PhantomData,
+            _marker_2: marker::PhantomData,
+            _marker_3: marker::PhantomData,
         }
     }
 }
@@ -66,6 +101,10 @@ impl<T, U: Visitor<T, V>, V> Stmt<T, U, V> for Print<T, U, V> {
     fn accept(&self, visitor: &mut U) -> T {
         visitor.visit_print_stmt(self)
     }
+
+    fn kind(&self) -> StmtKind<'_, T, U, V> {
+        StmtKind::Print(self)
+    }
 }
 
 impl<T, U: Visitor<T, V>, V> Display for Print<T, U, V> {
@@ -74,22 +113,35 @@ impl<T, U: Visitor<T, V>, V> Display for Print<T, U, V> {
     }
 }
 
+impl<T, U: Visitor<T, V>, V> Debug for Print<T, U, V> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "Print({})", self)
+    }
+}
+
 pub struct Let<T, U: ?Sized, V: ?Sized> {
     pub name: token::Token,
     pub initializer: Box<dyn Expr<T, V>>,
-    _marker_1: marker::PhantomData<T>,
-    _marker_2: marker::PhantomData<U>,
-    _marker_3: marker::PhantomData<V>,
+    /// comment right above the declaration, only kept when the scanner keeps comments
+    pub leading_comment: Option<String>,
+    _marker_1: marker::PhantomData<fn() -> T>,
+    _marker_2: marker::PhantomData<fn() -> U>,
+    _marker_3: marker::PhantomData<fn() -> V>,
 }
 
 impl<T, U, V> Let<T, U, V> {
-    pub fn new(name: token::Token, initializer: Box<dyn Expr<T, V>>) -> Self {
+    pub fn new(
+        name: token::Token,
+        initializer: Box<dyn Expr<T, V>>,
+        leading_comment: Option<String>,
+    ) -> Self {
         Let {
             name,
             initializer,
-            _marker_1: marker::PhantomData::default(),
-            _marker_2: marker::PhantomData::default(),
-            _marker_3: marker::PhantomData::default(),
+            leading_comment,
+            _marker_1: marker::PhantomData,
+            _marker_2: marker::PhantomData,
+            _marker_3: marker::PhantomData,
         }
     }
 }
@@ -98,6 +150,10 @@ impl<T, U: Visitor<T, V>, V> Stmt<T, U, V> for Let<T, U, V> {
     fn accept(&self, visitor: &mut U) -> T {
         visitor.visit_let_stmt(self)
     }
+
+    fn kind(&self) -> StmtKind<'_, T, U, V> {
+        StmtKind::Let(self)
+    }
 }
 
 impl<T, U: Visitor<T, V>, V> Display for Let<T, U, V> {
@@ -106,12 +162,18 @@ impl<T, U: Visitor<T, V>, V> Display for Let<T, U, V> {
     }
 }
 
+impl<T, U: Visitor<T, V>, V> Debug for Let<T, U, V> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "Let({})", self)
+    }
+}
+
 pub struct Const<T, U: ?Sized, V: ?Sized> {
     pub name: token::Token,
     pub initializer: Box<dyn Expr<T, V>>,
-    _marker_1: marker::PhantomData<T>,
-    _marker_2: marker::PhantomData<U>,
-    _marker_3: marker::PhantomData<V>,
+    _marker_1: marker::PhantomData<fn() -> T>,
+    _marker_2: marker::PhantomData<fn() -> U>,
+    _marker_3: marker::PhantomData<fn() -> V>,
 }
 
 impl<T, U, V> Const<T, U, V> {
@@ -119,9 +181,9 @@ impl<T, U, V> Const<T, U, V> {
         Const {
             name,
             initializer,
-            _marker_1: marker::PhantomData::default(),
-            _marker_2: marker::PhantomData::default(),
-            _marker_3: marker::PhantomData::default(),
+            _marker_1: marker::PhantomData,
+            _marker_2: marker::PhantomData,
+            _marker_3: marker::PhantomData,
         }
     }
 }
@@ -130,6 +192,10 @@ impl<T, U: Visitor<T, V>, V> Stmt<T, U, V> for Const<T, U, V> {
     fn accept(&self, visitor: &mut U) -> T {
         visitor.visit_const_stmt(self)
     }
+
+    fn kind(&self) -> StmtKind<'_, T, U, V> {
+        StmtKind::Const(self)
+    }
 }
 
 impl<T, U: Visitor<T, V>, V> Display for Const<T, U, V> {
@@ -137,3 +203,304 @@ impl<T, U: Visitor<T, V>, V> Display for Const<T, U, V> {
         write!(f, "{} {}", self.name, self.initializer)
     }
 }
+
+impl<T, U: Visitor<T, V>, V> Debug for Const<T, U, V> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "Const({})", self)
+    }
+}
+
+pub struct Block<T, U: ?Sized, V: ?Sized> {
+    pub statements: Vec<Box<dyn Stmt<T, U, V>>>,
+    _marker_1: marker::PhantomData<fn() -> T>,
+    _marker_2: marker::PhantomData<fn() -> U>,
+    _marker_3: marker::PhantomData<fn() -> V>,
+}
+
+impl<T, U, V> Block<T, U, V> {
+    pub fn new(statements: Vec<Box<dyn Stmt<T, U, V>>>) -> Self {
+        Block {
+            statements,
+            _marker_1: marker::PhantomData,
+            _marker_2: marker::PhantomData,
+            _marker_3: marker::PhantomData,
+        }
+    }
+}
+
+impl<T, U: Visitor<T, V>, V> Stmt<T, U, V> for Block<T, U, V> {
+    fn accept(&self, visitor: &mut U) -> T {
+        visitor.visit_block_stmt(self)
+    }
+
+    fn kind(&self) -> StmtKind<'_, T, U, V> {
+        StmtKind::Block(self)
+    }
+}
+
+impl<T, U: Visitor<T, V>, V> Display for Block<T, U, V> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        let statements = self
+            .statements
+            .iter()
+            .map(|s| s.to_string())
+            .collect::<Vec<_>>()
+            .join(" ");
+        write!(f, "{{ {} }}", statements)
+    }
+}
+
+impl<T, U: Visitor<T, V>, V> Debug for Block<T, U, V> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "Block({})", self)
+    }
+}
+
+pub struct ForEach<T, U: ?Sized, V: ?Sized> {
+    pub variable: token::Token,
+    pub iterable: Box<dyn Expr<T, V>>,
+    pub body: Box<dyn Stmt<T, U, V>>,
+    _marker_1: marker::PhantomData<fn() -> T>,
+    _marker_2: marker::PhantomData<fn() -> U>,
+    _marker_3: marker::PhantomData<fn() -> V>,
+}
+
+impl<T, U, V> ForEach<T, U, V> {
+    pub fn new(
+        variable: token::Token,
+        iterable: Box<dyn Expr<T, V>>,
+        body: Box<dyn Stmt<T, U, V>>,
+    ) -> Self {
+        ForEach {
+            variable,
+            iterable,
+            body,
+            _marker_1: marker::PhantomData,
+            _marker_2: marker::PhantomData,
+            _marker_3: marker::PhantomData,
+        }
+    }
+}
+
+impl<T, U: Visitor<T, V>, V> Stmt<T, U, V> for ForEach<T, U, V> {
+    fn accept(&self, visitor: &mut U) -> T {
+        visitor.visit_for_each_stmt(self)
+    }
+
+    fn kind(&self) -> StmtKind<'_, T, U, V> {
+        StmtKind::ForEach(self)
+    }
+}
+
+impl<T, U: Visitor<T, V>, V> Display for ForEach<T, U, V> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "{} in {} {}", self.variable, self.iterable, self.body)
+    }
+}
+
+impl<T, U: Visitor<T, V>, V> Debug for ForEach<T, U, V> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "ForEach({})", self)
+    }
+}
+
+pub struct Function<T, U: ?Sized, V: ?Sized> {
+    pub name: token::Token,
+    pub params: Vec<token::Token>,
+    pub body: FunctionBody<T, U, V>,
+    /// comment right above the declaration, only kept when the scanner keeps comments
+    pub leading_comment: Option<String>,
+    _marker_1: marker::PhantomData<fn() -> T>,
+    _marker_2: marker::PhantomData<fn() -> U>,
+    _marker_3: marker::PhantomData<fn() -> V>,
+}
+
+impl<T, U, V> Function<T, U, V> {
+    pub fn new(
+        name: token::Token,
+        params: Vec<token::Token>,
+        body: FunctionBody<T, U, V>,
+        leading_comment: Option<String>,
+    ) -> Self {
+        Function {
+            name,
+            params,
+            body,
+            leading_comment,
+            _marker_1: marker::PhantomData,
+            _marker_2: marker::PhantomData,
+            _marker_3: marker::PhantomData,
+        }
+    }
+}
+
+impl<T, U: Visitor<T, V>, V> Stmt<T, U, V> for Function<T, U, V> {
+    fn accept(&self, visitor: &mut U) -> T {
+        visitor.visit_function_stmt(self)
+    }
+
+    fn kind(&self) -> StmtKind<'_, T, U, V> {
+        StmtKind::Function(self)
+    }
+}
+
+impl<T, U: Visitor<T, V>, V> Display for Function<T, U, V> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        let params = self
+            .params
+            .iter()
+            .map(|p| p.lexeme.clone())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let body = self
+            .body
+            .iter()
+            .map(|s| s.to_string())
+            .collect::<Vec<_>>()
+            .join(" ");
+        write!(f, "{}({}) {{ {} }}", self.name, params, body)
+    }
+}
+
+impl<T, U: Visitor<T, V>, V> Debug for Function<T, U, V> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "Function({})", self)
+    }
+}
+
+pub struct If<T, U: ?Sized, V: ?Sized> {
+    pub condition: Box<dyn Expr<T, V>>,
+    pub then_branch: Box<dyn Stmt<T, U, V>>,
+    pub else_branch: Option<Box<dyn Stmt<T, U, V>>>,
+    _marker_1: marker::PhantomData<fn() -> T>,
+    _marker_2: marker::PhantomData<fn() -> U>,
+    _marker_3: marker::PhantomData<fn() -> V>,
+}
+
+impl<T, U, V> If<T, U, V> {
+    pub fn new(
+        condition: Box<dyn Expr<T, V>>,
+        then_branch: Box<dyn Stmt<T, U, V>>,
+        else_branch: Option<Box<dyn Stmt<T, U, V>>>,
+    ) -> Self {
+        If {
+            condition,
+            then_branch,
+            else_branch,
+            _marker_1: marker::PhantomData,
+            _marker_2: marker::PhantomData,
+            _marker_3: marker::PhantomData,
+        }
+    }
+}
+
+impl<T, U: Visitor<T, V>, V> Stmt<T, U, V> for If<T, U, V> {
+    fn accept(&self, visitor: &mut U) -> T {
+        visitor.visit_if_stmt(self)
+    }
+
+    fn kind(&self) -> StmtKind<'_, T, U, V> {
+        StmtKind::If(self)
+    }
+}
+
+impl<T, U: Visitor<T, V>, V> Display for If<T, U, V> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        let else_branch = self
+            .else_branch
+            .iter()
+            .map(|e| format!(" {}", e))
+            .collect::<String>();
+        write!(f, "{} {}{}", self.condition, self.then_branch, else_branch)
+    }
+}
+
+impl<T, U: Visitor<T, V>, V> Debug for If<T, U, V> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "If({})", self)
+    }
+}
+
+pub struct Return<T, U: ?Sized, V: ?Sized> {
+    pub keyword: token::Token,
+    pub value: Box<dyn Expr<T, V>>,
+    _marker_1: marker::PhantomData<fn() -> T>,
+    _marker_2: marker::PhantomData<fn() -> U>,
+    _marker_3: marker::PhantomData<fn() -> V>,
+}
+
+impl<T, U, V> Return<T, U, V> {
+    pub fn new(keyword: token::Token, value: Box<dyn Expr<T, V>>) -> Self {
+        Return {
+            keyword,
+            value,
+            _marker_1: marker::PhantomData,
+            _marker_2: marker::PhantomData,
+            _marker_3: marker::PhantomData,
+        }
+    }
+}
+
+impl<T, U: Visitor<T, V>, V> Stmt<T, U, V> for Return<T, U, V> {
+    fn accept(&self, visitor: &mut U) -> T {
+        visitor.visit_return_stmt(self)
+    }
+
+    fn kind(&self) -> StmtKind<'_, T, U, V> {
+        StmtKind::Return(self)
+    }
+}
+
+impl<T, U: Visitor<T, V>, V> Display for Return<T, U, V> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "{} {}", self.keyword, self.value)
+    }
+}
+
+impl<T, U: Visitor<T, V>, V> Debug for Return<T, U, V> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "Return({})", self)
+    }
+}
+
+pub struct While<T, U: ?Sized, V: ?Sized> {
+    pub condition: Box<dyn Expr<T, V>>,
+    pub body: Box<dyn Stmt<T, U, V>>,
+    _marker_1: marker::PhantomData<fn() -> T>,
+    _marker_2: marker::PhantomData<fn() -> U>,
+    _marker_3: marker::PhantomData<fn() -> V>,
+}
+
+impl<T, U, V> While<T, U, V> {
+    pub fn new(condition: Box<dyn Expr<T, V>>, body: Box<dyn Stmt<T, U, V>>) -> Self {
+        While {
+            condition,
+            body,
+            _marker_1: marker::PhantomData,
+            _marker_2: marker::PhantomData,
+            _marker_3: marker::PhantomData,
+        }
+    }
+}
+
+impl<T, U: Visitor<T, V>, V> Stmt<T, U, V> for While<T, U, V> {
+    fn accept(&self, visitor: &mut U) -> T {
+        visitor.visit_while_stmt(self)
+    }
+
+    fn kind(&self) -> StmtKind<'_, T, U, V> {
+        StmtKind::While(self)
+    }
+}
+
+impl<T, U: Visitor<T, V>, V> Display for While<T, U, V> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "{} {}", self.condition, self.body)
+    }
+}
+
+impl<T, U: Visitor<T, V>, V> Debug for While<T, U, V> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "While({})", self)
+    }
+}
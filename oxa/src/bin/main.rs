@@ -1,6 +1,7 @@
-use oxa::{errors::exit_with_return_code, oxa::OxaBuilder};
+use oxa::{errors::exit_with_return_code, oxa::OxaBuilder, oxa::STACK_SIZE};
 
 use std::env;
+use std::thread;
 
 fn main() {
     if cfg!(debug_assertions) {
@@ -9,21 +10,44 @@ fn main() {
         setup_logger(log::LevelFilter::Info)
     }
 
-    let mut oxa = OxaBuilder::default().build();
+    // deep recursion in a script reports an error at the call depth limit instead of
+    // overflowing the stack of the main thread
+    thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(run)
+        .expect("failed to start the interpreter thread")
+        .join()
+        .expect("the interpreter thread panicked");
+}
 
-    let args: Vec<String> = env::args().collect();
-    match args.len() {
-        i if i > 2 => {
-            println!("Usage: oxa [script]");
-        }
-        2 => {
+fn run() {
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    let flag_count = args.iter().take_while(|arg| arg.starts_with("--")).count();
+    let flags: Vec<String> = args.drain(..flag_count).collect();
+    let dump_tokens = flags.iter().any(|flag| flag == "--tokens");
+    let dump_ast = flags.iter().any(|flag| flag == "--ast");
+    let known_flags = flags
+        .iter()
+        .all(|flag| flag == "--tokens" || flag == "--ast");
+
+    let mut oxa = OxaBuilder::default()
+        .dump_tokens(dump_tokens)
+        .dump_ast(dump_ast)
+        .build();
+
+    match args.as_slice() {
+        [script] if known_flags => {
             log::info!("Starting with a file");
-            exit_with_return_code(oxa.run_file(&args[1]));
+            exit_with_return_code(oxa.run_file(script));
         }
-        _ => {
+        [] if flags.is_empty() => {
             log::info!("Starting with prompt");
             exit_with_return_code(oxa.run_prompt());
         }
+        _ => {
+            eprintln!("Usage: oxa [--tokens | --ast] [script]");
+            std::process::exit(64);
+        }
     }
 }
 
@@ -0,0 +1,189 @@
+use crate::ast::stmt;
+use crate::environment::Environment;
+use crate::errors::ErrorCode;
+use crate::interpreter::{Interpreter, ResultObject};
+use crate::object::Object;
+use crate::shared::{share, Shared, ThreadSafe};
+use crate::token::Token;
+use std::fmt;
+
+pub type FunctionBody = stmt::FunctionBody<ResultObject, Interpreter, Interpreter>;
+
+/// Signature of a function implemented in rust, `paren` is the closing parenthesis of the call
+/// and is used to locate errors.
+#[cfg(not(feature = "sync"))]
+pub type NativeFn = dyn Fn(&mut Interpreter, &Token, &[Object]) -> ResultObject;
+
+/// Signature of a function implemented in rust, `paren` is the closing parenthesis of the call
+/// and is used to locate errors.
+#[cfg(feature = "sync")]
+pub type NativeFn = dyn Fn(&mut Interpreter, &Token, &[Object]) -> ResultObject + Send + Sync;
+
+/// Number of arguments a callable accepts
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Arity {
+    Exact(usize),
+    /// inclusive range, used for optional arguments
+    Range(usize, usize),
+    AtLeast(usize),
+}
+
+impl Arity {
+    /// returns true if a call with `count` arguments is allowed
+    pub fn accepts(&self, count: usize) -> bool {
+        match *self {
+            Arity::Exact(n) => count == n,
+            Arity::Range(min, max) => (min..=max).contains(&count),
+            Arity::AtLeast(min) => count >= min,
+        }
+    }
+}
+
+impl fmt::Display for Arity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Arity::Exact(n) => write!(f, "{}", n),
+            Arity::Range(min, max) => write!(f, "{} to {}", min, max),
+            Arity::AtLeast(min) => write!(f, "at least {}", min),
+        }
+    }
+}
+
+/// Anything that can be invoked with a list of arguments from the source code
+pub trait Callable {
+    /// number of arguments the callable expects
+    fn arity(&self) -> Arity;
+
+    fn call(
+        &self,
+        interpreter: &mut Interpreter,
+        paren: &Token,
+        arguments: Vec<Object>,
+    ) -> ResultObject;
+}
+
+/// A user defined function declared with the `fun` keyword.
+///
+/// The function holds on to the environment it was declared in (`closure`), the body is
+/// executed in a new environment enclosed by the closure where the parameters are bounded to
+/// the call arguments. Since the closure is shared rather than copied, the function name itself
+/// is visible in the body, allowing recursive calls.
+pub struct Function {
+    pub name: Token,
+    params: Vec<Token>,
+    body: FunctionBody,
+    closure: Shared<Environment>,
+}
+
+impl Function {
+    pub fn new(
+        name: Token,
+        params: Vec<Token>,
+        body: FunctionBody,
+        closure: Shared<Environment>,
+    ) -> Self {
+        Function {
+            name,
+            params,
+            body,
+            closure,
+        }
+    }
+}
+
+impl Callable for Function {
+    fn arity(&self) -> Arity {
+        Arity::Exact(self.params.len())
+    }
+
+    fn call(
+        &self,
+        interpreter: &mut Interpreter,
+        _paren: &Token,
+        arguments: Vec<Object>,
+    ) -> ResultObject {
+        let mut environment = Environment::with_enclosing(self.closure.clone());
+        for (param, argument) in self.params.iter().zip(arguments) {
+            environment.define(&param.lexeme, argument);
+        }
+
+        match interpreter.execute_block(&self.body, share(environment)) {
+            Ok(_) => Ok(Object::default()),
+            Err(ErrorCode::Return(value)) => Ok(value),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+impl fmt::Debug for Function {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<fn {}>", self.name.lexeme)
+    }
+}
+
+impl fmt::Display for Function {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<fn {}>", self.name.lexeme)
+    }
+}
+
+/// Functions are only equal to themselves
+impl PartialEq for Function {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+/// A function implemented in rust and registered in the global environment by the interpreter
+pub struct NativeFunction {
+    pub name: String,
+    arity: Arity,
+    function: Box<NativeFn>,
+}
+
+impl NativeFunction {
+    pub fn new<F>(name: &str, arity: Arity, function: F) -> Self
+    where
+        F: Fn(&mut Interpreter, &Token, &[Object]) -> ResultObject + ThreadSafe + 'static,
+    {
+        NativeFunction {
+            name: name.to_string(),
+            arity,
+            function: Box::new(function),
+        }
+    }
+}
+
+impl Callable for NativeFunction {
+    fn arity(&self) -> Arity {
+        self.arity
+    }
+
+    fn call(
+        &self,
+        interpreter: &mut Interpreter,
+        paren: &Token,
+        arguments: Vec<Object>,
+    ) -> ResultObject {
+        (self.function)(interpreter, paren, &arguments)
+    }
+}
+
+impl fmt::Debug for NativeFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<native {}>", self.name)
+    }
+}
+
+impl fmt::Display for NativeFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<native {}>", self.name)
+    }
+}
+
+/// Native functions are only equal to themselves
+impl PartialEq for NativeFunction {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
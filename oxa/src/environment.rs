@@ -1,45 +1,195 @@
+use crate::errors::ErrorCode;
 use crate::object::Object;
+use crate::shared::{access, share, Shared};
 use crate::token::Token;
-use std::cell::RefCell;
 use std::collections::HashMap;
-use std::rc::Rc;
 
 /// Environment contains all stored value during the program execution
 /// A variable is bounded to an executed value in runtime and the value can be retried using variable name
 /// `let a = "before";`
 ///
 /// Environment takes ownership of all the variables declared and only provide an reference ptr to the variable upon demand
+///
+/// Every block creates a new environment which points at the `enclosing` one, lookup of a variable
+/// walks up the chain until it finds the variable or reaches the global environment.
 #[derive(Debug, Default)]
 pub struct Environment {
-    values: HashMap<String, Rc<RefCell<Object>>>,
+    values: HashMap<String, Binding>,
+    enclosing: Option<Shared<Environment>>,
+}
+
+/// Value bound to a variable name, a `const` binding can't be assigned after its declaration
+#[derive(Debug)]
+struct Binding {
+    value: Shared<Object>,
+    constant: bool,
 }
 
 impl Environment {
+    /// Creates a new inner scope for the `enclosing` environment
+    pub fn with_enclosing(enclosing: Shared<Environment>) -> Self {
+        Environment {
+            values: HashMap::new(),
+            enclosing: Some(enclosing),
+        }
+    }
+
     /// Insert a declared variable to environment to store and can be retrieved later
-    pub fn define(&mut self, name: &str, value: Object) -> Rc<RefCell<Object>> {
-        // TODO: Add error handler which checks if the variable exist and is mutable before setting the value again.
-        let value = Rc::new(RefCell::new(value));
-        let ret_value = value.clone();
-        self.values.insert(name.to_string(), value);
-        ret_value
-    }
-
-    pub fn assign(&mut self, token: &Token, value: Object) -> Option<Rc<RefCell<Object>>> {
-        let name = &token.lexeme;
-        let value = Rc::new(RefCell::new(value));
-        let ret_value = value.clone();
+    pub fn define(&mut self, name: &str, value: Object) -> Shared<Object> {
+        self.bind(name, value, false)
+    }
+
+    /// Insert a declared constant, unlike a variable it can't be assigned afterwards. Only the
+    /// binding is constant, a list or map bound to it can still have its elements set
+    pub fn define_const(&mut self, name: &str, value: Object) -> Shared<Object> {
+        self.bind(name, value, true)
+    }
+
+    /// Updates the value of the nearest variable named after the token, fails if the variable
+    /// doesn't exist or was declared `const`
+    pub fn assign(&mut self, token: &Token, value: Object) -> Result<Shared<Object>, ErrorCode> {
+        let name: &str = &token.lexeme;
         match self.values.get(name) {
-            Some(_) => {
-                self.values.insert(name.to_string(), value);
-                Some(ret_value)
-            }
-            None => None,
+            Some(binding) if binding.constant => Err(ErrorCode::RuntimeError(
+                token.clone(),
+                format!("Cannot assign to constant '{}'", name),
+            )),
+            Some(_) => Ok(self.bind(name, value, false)),
+            None => match &self.enclosing {
+                Some(enclosing) => access(enclosing).assign(token, value),
+                None => Err(ErrorCode::RuntimeError(
+                    token.clone(),
+                    format!(
+                        "Cannot assign to undeclared variable '{}'; use 'let' first",
+                        name
+                    ),
+                )),
+            },
         }
     }
 
     /// Get a the `Object` value of a stored variable.
-    /// returns `None` if the variable doesn't exist in the environment and should be treated as error
-    pub fn get(&self, token: &Token) -> Option<Rc<RefCell<Object>>> {
-        self.values.get(&token.lexeme).cloned()
+    /// fails if the variable doesn't exist in the environment or any enclosing one
+    pub fn get(&self, token: &Token) -> Result<Shared<Object>, ErrorCode> {
+        match self.values.get(&*token.lexeme) {
+            Some(binding) => Ok(binding.value.clone()),
+            None => match &self.enclosing {
+                Some(enclosing) => access(enclosing).get(token),
+                None => Err(ErrorCode::RuntimeError(
+                    token.clone(),
+                    format!("Undefined variable '{}'.", token.lexeme),
+                )),
+            },
+        }
+    }
+
+    fn bind(&mut self, name: &str, value: Object, constant: bool) -> Shared<Object> {
+        let value = share(value);
+        let binding = Binding {
+            value: value.clone(),
+            constant,
+        };
+        self.values.insert(name.to_string(), binding);
+        value
+    }
+}
+
+#[cfg(test)]
+mod environment_tests {
+    use super::*;
+    use crate::token::TokenKind;
+
+    #[test]
+    fn assign_through_shared_value() {
+        let mut environment = Environment::default();
+        let name = Token::new(TokenKind::Identifier, "a", None, 1, 1);
+
+        let defined = environment.define("a", Object::from(1));
+        environment.assign(&name, Object::from(2)).unwrap();
+
+        assert_eq!(*access(&defined), Object::from(1));
+        assert_eq!(*access(&environment.get(&name).unwrap()), Object::from(2));
+    }
+
+    #[test]
+    fn inner_scope_reads_outer_variable() {
+        let outer = share(Environment::default());
+        access(&outer).define("a", Object::from("outer"));
+        let inner = Environment::with_enclosing(outer);
+        let name = Token::new(TokenKind::Identifier, "a", None, 1, 1);
+
+        assert_eq!(*access(&inner.get(&name).unwrap()), Object::from("outer"));
+    }
+
+    #[test]
+    fn assign_updates_outer_binding() {
+        let outer = share(Environment::default());
+        access(&outer).define("a", Object::from(1));
+        let mut inner = Environment::with_enclosing(outer.clone());
+        let name = Token::new(TokenKind::Identifier, "a", None, 1, 1);
+
+        assert!(inner.assign(&name, Object::from(2)).is_ok());
+        assert!(!inner.values.contains_key("a"));
+        assert_eq!(
+            *access(&access(&outer).get(&name).unwrap()),
+            Object::from(2)
+        );
+    }
+
+    #[test]
+    fn define_shadows_outer_binding() {
+        let outer = share(Environment::default());
+        access(&outer).define("a", Object::from(1));
+        let mut inner = Environment::with_enclosing(outer.clone());
+        let name = Token::new(TokenKind::Identifier, "a", None, 1, 1);
+
+        inner.define("a", Object::from(2));
+        assert_eq!(*access(&inner.get(&name).unwrap()), Object::from(2));
+        assert_eq!(
+            *access(&access(&outer).get(&name).unwrap()),
+            Object::from(1)
+        );
+    }
+
+    #[test]
+    fn error_assign_undeclared_variable() {
+        let mut inner = Environment::with_enclosing(Shared::default());
+        let name = Token::new(TokenKind::Identifier, "a", None, 1, 1);
+
+        assert!(inner.assign(&name, Object::from(1)).is_err());
+        match inner.get(&name) {
+            Err(ErrorCode::RuntimeError(_, message)) => {
+                assert_eq!(message, "Undefined variable 'a'.")
+            }
+            _ => panic!("expected an undefined variable error"),
+        }
+    }
+
+    #[test]
+    fn error_assign_constant() {
+        let outer = share(Environment::default());
+        access(&outer).define_const("a", Object::from(1));
+        let mut inner = Environment::with_enclosing(outer);
+        let name = Token::new(TokenKind::Identifier, "a", None, 1, 1);
+
+        match inner.assign(&name, Object::from(2)) {
+            Err(ErrorCode::RuntimeError(_, message)) => {
+                assert_eq!(message, "Cannot assign to constant 'a'")
+            }
+            _ => panic!("expected a constant assignment error"),
+        }
+        assert_eq!(*access(&inner.get(&name).unwrap()), Object::from(1));
+
+        // a shadowing variable in the inner scope can be assigned
+        inner.define("a", Object::from(3));
+        assert!(inner.assign(&name, Object::from(4)).is_ok());
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn environment_is_send() {
+        fn assert_send<T: Send>() {}
+
+        assert_send::<Environment>();
     }
 }
@@ -1,3 +1,4 @@
+use crate::object::Object;
 use crate::token::Token;
 
 use std::fmt;
@@ -12,9 +13,17 @@ pub enum ErrorCode {
     FileError(Error),
     IO(Error),
     InvalidTokenKey(char),
+    ConversionError(String),
     ProcessError,
+    ScannerError(usize, String),
     ParserError(Token, String),
+    /// Every syntax error found while parsing a source, in the order they were found
+    ParserErrors(Vec<ErrorCode>),
     RuntimeError(Token, String),
+    /// Number of errors reported while running a source which carried on after them
+    RuntimeErrors(usize),
+    /// Unwinds the interpreter out of a function body with the returned value
+    Return(Object),
     Unknown,
 }
 
@@ -22,11 +31,15 @@ impl ErrorCode {
     pub fn get_return_code(&self) -> i32 {
         match &self {
             Self::InvalidTokenKey(_) => 4,
+            Self::ConversionError(_) => 5,
             Self::FileError(_) => 10,
             Self::IO(_) => 11,
             Self::ProcessError => 12,
+            Self::ScannerError(_, _) => 6,
             Self::ParserError(_, _) => 3,
+            Self::ParserErrors(_) => 3,
             Self::RuntimeError(_, _) => 2,
+            Self::RuntimeErrors(_) => 2,
             _ => 1, // Everything != 0 will be treated as an error
         }
     }
@@ -38,9 +51,18 @@ impl fmt::Display for ErrorCode {
             Self::FileError(e) => write!(f, "file processing error: {:?}", e),
             Self::IO(e) => write!(f, "io error: {:?}", e),
             Self::ProcessError => write!(f, "process error"),
+            Self::ScannerError(l, m) => write!(f, "[line {}] {}", l, m),
             Self::InvalidTokenKey(t) => write!(f, "invalid token: {}", t),
+            Self::ConversionError(m) => write!(f, "conversion error: {}", m),
             Self::ParserError(t, m) => write!(f, "{}: {}", m, t),
+            Self::ParserErrors(errors) => {
+                let errors = errors.iter().map(|e| e.to_string()).collect::<Vec<_>>();
+                write!(f, "{}", errors.join("\n"))
+            }
             Self::RuntimeError(t, m) => write!(f, "{} {} \n [line {}]", m, t, t.line),
+            Self::RuntimeErrors(1) => write!(f, "1 runtime error"),
+            Self::RuntimeErrors(count) => write!(f, "{} runtime errors", count),
+            Self::Return(_) => write!(f, "'return' outside of a function"),
             Self::Unknown => write!(f, "unknown error"),
         }
     }
@@ -62,9 +84,31 @@ impl From<Error> for ErrorCode {
     }
 }
 
+/// Errors are equal when they are the same variant holding equal data, io errors only compare
+/// their `ErrorKind`
 impl PartialEq for ErrorCode {
     fn eq(&self, other: &Self) -> bool {
-        self == other
+        match (self, other) {
+            (Self::FileError(l), Self::FileError(r)) => l.kind() == r.kind(),
+            (Self::IO(l), Self::IO(r)) => l.kind() == r.kind(),
+            (Self::InvalidTokenKey(l), Self::InvalidTokenKey(r)) => l == r,
+            (Self::ConversionError(l), Self::ConversionError(r)) => l == r,
+            (Self::ProcessError, Self::ProcessError) => true,
+            (Self::ScannerError(l_line, l), Self::ScannerError(r_line, r)) => {
+                l_line == r_line && l == r
+            }
+            (Self::ParserError(l_token, l), Self::ParserError(r_token, r)) => {
+                l_token == r_token && l == r
+            }
+            (Self::ParserErrors(l), Self::ParserErrors(r)) => l == r,
+            (Self::RuntimeError(l_token, l), Self::RuntimeError(r_token, r)) => {
+                l_token == r_token && l == r
+            }
+            (Self::RuntimeErrors(l), Self::RuntimeErrors(r)) => l == r,
+            (Self::Return(l), Self::Return(r)) => l == r,
+            (Self::Unknown, Self::Unknown) => true,
+            _ => false,
+        }
     }
 }
 
@@ -84,3 +128,55 @@ pub fn exit_with_return_code(res: Result<(), ErrorCode>) {
         }
     }
 }
+
+#[cfg(test)]
+mod errors_tests {
+    use super::*;
+    use crate::token::TokenKind;
+    use std::io::ErrorKind;
+
+    #[test]
+    fn compare_error_codes() {
+        assert_eq!(ErrorCode::ProcessError, ErrorCode::ProcessError);
+        assert_ne!(ErrorCode::ProcessError, ErrorCode::Unknown);
+        assert_eq!(
+            ErrorCode::InvalidTokenKey('#'),
+            ErrorCode::InvalidTokenKey('#')
+        );
+        assert_ne!(
+            ErrorCode::InvalidTokenKey('#'),
+            ErrorCode::InvalidTokenKey('@')
+        );
+
+        let token = Token::new(TokenKind::Identifier, "a", None, 1, 1);
+        let other = Token::new(TokenKind::Identifier, "b", None, 1, 1);
+        assert_eq!(
+            ErrorCode::RuntimeError(token.clone(), "m".to_string()),
+            ErrorCode::RuntimeError(token.clone(), "m".to_string())
+        );
+        assert_ne!(
+            ErrorCode::RuntimeError(token.clone(), "m".to_string()),
+            ErrorCode::RuntimeError(other, "m".to_string())
+        );
+        assert_ne!(
+            ErrorCode::RuntimeError(token.clone(), "m".to_string()),
+            ErrorCode::ParserError(token, "m".to_string())
+        );
+    }
+
+    #[test]
+    fn compare_io_errors_by_kind() {
+        assert_eq!(
+            ErrorCode::IO(Error::new(ErrorKind::NotFound, "a")),
+            ErrorCode::IO(Error::new(ErrorKind::NotFound, "b"))
+        );
+        assert_ne!(
+            ErrorCode::IO(Error::new(ErrorKind::NotFound, "a")),
+            ErrorCode::IO(Error::new(ErrorKind::PermissionDenied, "a"))
+        );
+        assert_ne!(
+            ErrorCode::IO(Error::new(ErrorKind::NotFound, "a")),
+            ErrorCode::FileError(Error::new(ErrorKind::NotFound, "a"))
+        );
+    }
+}
@@ -1,29 +1,229 @@
 use crate::errors::ErrorCode;
 use crate::token::{Token, TokenKind};
+use std::cell::{Cell, RefCell};
 use std::fmt::Debug;
 
-// TODO: All reported error should be collected somewhere to log at once
+thread_local! {
+    /// name of the file being run, used to locate errors across several files
+    static SOURCE: RefCell<Option<String>> = const { RefCell::new(None) };
+    /// text of the source being run, used to quote the line an error is found on
+    static CODE: RefCell<Option<String>> = const { RefCell::new(None) };
+    /// number of warnings reported on this thread
+    static WARNINGS: Cell<usize> = const { Cell::new(0) };
+    /// number of errors reported on this thread
+    static ERRORS: Cell<usize> = const { Cell::new(0) };
+    /// messages reported on this thread and not flushed yet
+    static MESSAGES: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Collects the warnings and errors reported on the current thread, nothing is printed until
+/// `flush` writes the collected messages to stderr.
 #[derive(Debug, Default)]
 pub struct Reporter {}
 
 impl Reporter {
-    pub fn line_error(line: usize, message: &str) {
-        println!("[line {} Error : {}", line, message);
+    /// Sets the file name prefixed to the errors reported on this thread, `None` when the source
+    /// doesn't come from a file.
+    pub fn set_source(name: Option<&str>) {
+        SOURCE.with(|source| *source.borrow_mut() = name.map(String::from));
+    }
+
+    /// Sets the text of the source being run on this thread, errors located on one of its lines
+    /// quote the line with a caret under the column.
+    pub fn set_code(code: Option<&str>) {
+        CODE.with(|source| *source.borrow_mut() = code.map(String::from));
+    }
+
+    /// Reports a problem which doesn't stop the program from running
+    pub fn warn(line: usize, message: &str) {
+        WARNINGS.with(|count| count.set(count.get() + 1));
+        Self::report(format!(
+            "{} warning: {}",
+            Self::line_location(line),
+            message
+        ));
+    }
+
+    pub fn error(line: usize, message: &str) {
+        Self::count_error();
+        Self::report(format!("{} error: {}", Self::line_location(line), message));
+    }
+
+    /// Reports an error found at a position of the source, `[line 3:12] Error: ...`
+    pub fn line_error(line: usize, column: usize, message: &str) {
+        Self::count_error();
+        Self::report(format!(
+            "{} Error: {}{}",
+            Self::position(line, column),
+            message,
+            Self::snippet(line, column)
+        ));
+    }
+
+    /// number of warnings reported so far
+    pub fn warning_count() -> usize {
+        WARNINGS.with(Cell::get)
+    }
+
+    /// number of errors reported so far, warnings aren't counted
+    pub fn error_count() -> usize {
+        ERRORS.with(Cell::get)
+    }
+
+    /// true once an error, not a warning, was reported since the last reset
+    pub fn had_error() -> bool {
+        Self::error_count() > 0
+    }
+
+    /// messages reported so far and not flushed yet, warnings included
+    pub fn errors() -> Vec<String> {
+        MESSAGES.with(|messages| messages.borrow().clone())
+    }
+
+    /// writes the collected messages to stderr and forgets them
+    pub fn flush() {
+        for message in MESSAGES.with(|messages| messages.take()) {
+            eprintln!("{}", message);
+        }
+    }
+
+    /// resets the warning and error counters
+    pub fn reset_counts() {
+        WARNINGS.with(|count| count.set(0));
+        ERRORS.with(|count| count.set(0));
     }
 
     pub fn token_error(token: &Token, message: &str) {
+        Self::count_error();
+        let position = Self::position(token.line, token.column);
         if token.kind == TokenKind::Eof {
-            println!("{} at end {}", token.line, message);
+            Self::report(format!("{} Error at end: {}", position, message));
         } else {
-            println!("{} at '{}' {}", token.line, token.lexeme, message);
+            Self::report(format!(
+                "{} Error at '{}': {}{}",
+                position,
+                token.lexeme,
+                message,
+                Self::snippet(token.line, token.column)
+            ));
         }
     }
 
     pub fn arithmetic_error(ops: &str) {
-        println!("cannot perform arithmetic operation: {}", ops);
+        Self::count_error();
+        Self::report(format!("cannot perform arithmetic operation: {}", ops));
     }
 
     pub fn runtime_error(error: &ErrorCode) {
-        println!("Runtime error: {}", error);
+        Self::count_error();
+        match error {
+            // the position already locates the error, only the message follows it
+            ErrorCode::RuntimeError(token, message) => Self::report(format!(
+                "{} Runtime error: {}",
+                Self::position(token.line, token.column),
+                message
+            )),
+            _ => Self::report(format!("Runtime error: {}", error)),
+        }
+    }
+
+    fn report(message: String) {
+        MESSAGES.with(|messages| messages.borrow_mut().push(message));
+    }
+
+    fn count_error() {
+        ERRORS.with(|count| count.set(count.get() + 1));
+    }
+
+    /// `file:line:` when the source is a file, `[line n]` otherwise
+    fn line_location(line: usize) -> String {
+        Self::location(line).unwrap_or_else(|| format!("[line {}]", line))
+    }
+
+    /// `file:line:column:` when the source is a file, `[line n:column]` otherwise
+    fn position(line: usize, column: usize) -> String {
+        Self::location(line)
+            .map(|location| format!("{}{}:", location, column))
+            .unwrap_or_else(|| format!("[line {}:{}]", line, column))
+    }
+
+    /// the source line followed by a caret under the column, empty when the code isn't known
+    fn snippet(line: usize, column: usize) -> String {
+        CODE.with(|code| {
+            let code = code.borrow();
+            let Some(text) = code
+                .as_ref()
+                .and_then(|c| c.lines().nth(line.wrapping_sub(1)))
+            else {
+                return String::new();
+            };
+
+            // tabs are kept so the caret lines up with the quoted text
+            let indent: String = text
+                .chars()
+                .chain(std::iter::repeat(' '))
+                .take(column.saturating_sub(1))
+                .map(|c| if c == '\t' { '\t' } else { ' ' })
+                .collect();
+            format!("\n{}\n{}^", text, indent)
+        })
+    }
+
+    /// `file:line:` when the source is a file
+    fn location(line: usize) -> Option<String> {
+        SOURCE.with(|source| {
+            source
+                .borrow()
+                .as_ref()
+                .map(|name| format!("{}:{}:", name, line))
+        })
+    }
+}
+
+#[cfg(test)]
+mod reporter_tests {
+    use crate::errors::reporter::Reporter;
+    use crate::scanner::ScannerBuilder;
+
+    #[test]
+    fn collects_messages_until_flushed() {
+        Reporter::reset_counts();
+        Reporter::flush();
+
+        let mut scanner = ScannerBuilder::default().source("let a = 1;\n@").build();
+        scanner.scan_tokens().unwrap();
+
+        assert!(Reporter::had_error());
+        assert_eq!(
+            Reporter::errors(),
+            vec!["[line 2:1] Error: Unexpected character: @."]
+        );
+
+        Reporter::warn(3, "unused");
+        assert_eq!(Reporter::errors().len(), 2);
+        assert_eq!(Reporter::error_count(), 1);
+
+        Reporter::flush();
+        assert!(Reporter::errors().is_empty());
+    }
+
+    #[test]
+    fn quotes_the_line_of_an_error() {
+        Reporter::flush();
+        Reporter::set_code(Some("print 1;\nlet @ = 1;\n\tprint $;"));
+
+        Reporter::line_error(2, 5, "Unexpected character: @.");
+        Reporter::line_error(3, 8, "Unexpected character: $.");
+        Reporter::line_error(4, 1, "past the end");
+        Reporter::set_code(None);
+
+        assert_eq!(
+            Reporter::errors(),
+            vec![
+                "[line 2:5] Error: Unexpected character: @.\nlet @ = 1;\n    ^",
+                "[line 3:8] Error: Unexpected character: $.\n\tprint $;\n\t      ^",
+                "[line 4:1] Error: past the end",
+            ]
+        );
     }
 }
@@ -1,44 +1,98 @@
-use crate::ast::expr::{Assign, Binary, Expr, Grouping, Literal, Unary, Variable};
-use crate::ast::stmt::{Const, Expression, Let, Print, Stmt};
+use crate::ast::expr::{
+    Assign, Binary, Call, Expr, ExprKind, Grouping, Index, IndexSet, Lambda, List, Literal,
+    Logical, Map, Unary, Variable,
+};
+use crate::ast::stmt::{
+    Block, Const, Expression, ForEach, Function, If, Let, Print, Return, Stmt, StmtKind, While,
+};
 use crate::ast::{expr, stmt};
+use crate::callable::{self, Callable};
 use crate::environment::Environment;
 use crate::errors::reporter::Reporter;
 use crate::errors::ErrorCode;
+use crate::natives;
 use crate::object::{Object, ObjectKind, ObjectValue};
+use crate::shared::{access, share, Handle, Shared};
 use crate::token::{Token, TokenKind};
-use std::cell::RefCell;
-use std::rc::Rc;
+use std::collections::HashMap;
+use std::io::Write;
+use std::time::Instant;
+
+/// Default number of nested calls allowed before the interpreter reports a stack overflow
+pub const DEFAULT_MAX_CALL_DEPTH: usize = 1000;
 
-#[derive(Default)]
 pub struct InterpreterBuilder {
-    environment: Rc<RefCell<Environment>>,
+    environment: Shared<Environment>,
+    max_call_depth: usize,
+    output: Box<dyn Write>,
+}
+
+impl Default for InterpreterBuilder {
+    fn default() -> Self {
+        InterpreterBuilder::new()
+    }
 }
 
 impl InterpreterBuilder {
     pub fn new() -> Self {
         InterpreterBuilder {
-            environment: Rc::new(RefCell::new(Environment::default())),
+            environment: share(Environment::default()),
+            max_call_depth: DEFAULT_MAX_CALL_DEPTH,
+            output: Box::new(std::io::stdout()),
         }
     }
 
-    pub fn environment(mut self, environment: Rc<RefCell<Environment>>) -> Self {
+    pub fn environment(mut self, environment: Shared<Environment>) -> Self {
         self.environment = environment;
         self
     }
 
+    /// Number of nested function calls allowed before a call fails with a stack overflow error
+    /// rather than exhausting the host stack.
+    pub fn max_call_depth(mut self, max_call_depth: usize) -> Self {
+        self.max_call_depth = max_call_depth;
+        self
+    }
+
+    /// Sink receiving the program output of `print` and `write()`, the standard output by default
+    pub fn output(mut self, output: Box<dyn Write>) -> Self {
+        self.output = output;
+        self
+    }
+
     pub fn build(self) -> Interpreter {
-        Interpreter::new(self.environment)
+        for native in natives::globals() {
+            let name = native.name.clone();
+            access(&self.environment).define(&name, Object::from(native));
+        }
+
+        Interpreter::new(self.environment, self.max_call_depth, self.output)
     }
 }
 
 pub struct Interpreter {
-    environment: Rc<RefCell<Environment>>,
+    environment: Shared<Environment>,
+    call_depth: usize,
+    max_call_depth: usize,
+    output: Box<dyn Write>,
+    /// time the interpreter was built at, `clock()` counts the seconds from it
+    started: Instant,
 }
 
 /// constructor
 impl Interpreter {
-    fn new(environment: Rc<RefCell<Environment>>) -> Self {
-        Interpreter { environment }
+    fn new(
+        environment: Shared<Environment>,
+        max_call_depth: usize,
+        output: Box<dyn Write>,
+    ) -> Self {
+        Interpreter {
+            environment,
+            call_depth: 0,
+            max_call_depth,
+            output,
+            started: Instant::now(),
+        }
     }
 
     pub fn builder() -> InterpreterBuilder {
@@ -46,26 +100,19 @@ impl Interpreter {
     }
 }
 
-type ResultObject = Result<Object, ErrorCode>;
+pub type ResultObject = Result<Object, ErrorCode>;
 
 impl expr::Visitor<ResultObject> for Interpreter {
-    fn visit_assign_expr(&self, expr: &Assign<ResultObject, Self>) -> ResultObject {
+    fn visit_assign_expr(&mut self, expr: &Assign<ResultObject, Self>) -> ResultObject {
         let value = self.evaluate(expr.value.as_ref())?;
-        // let obj = self.environment.borrow_mut().assign(&expr.name, value);
-        match self.environment.borrow_mut().assign(&expr.name, value) {
-            // TODO: Update error to reference error to unknown variable
-            // "Undefined variable '" + name.lexeme + "'.");
-            None => Err(ErrorCode::ProcessError),
-            Some(obj) => {
-                let obj_borrow = obj.borrow_mut();
-                Ok(obj_borrow.to_owned())
-            }
-        }
+        let obj = access(&self.environment).assign(&expr.name, value)?;
+        let obj_borrow = access(&obj);
+        Ok(obj_borrow.to_owned())
     }
 
-    fn visit_binary_expr(&self, expr: &Binary<ResultObject, Self>) -> ResultObject {
-        let right = self.evaluate(expr.right.as_ref())?;
+    fn visit_binary_expr(&mut self, expr: &Binary<ResultObject, Self>) -> ResultObject {
         let left = self.evaluate(expr.left.as_ref())?;
+        let right = self.evaluate(expr.right.as_ref())?;
 
         match expr.operator.kind {
             TokenKind::Plus => {
@@ -84,20 +131,28 @@ impl expr::Visitor<ResultObject> for Interpreter {
                 check_numeric_operands(&expr.operator, &left, &right)?;
                 Ok(left * right)
             }
-            TokenKind::Greater => {
+            TokenKind::Percent => {
+                check_numeric_operands(&expr.operator, &left, &right)?;
+                Ok(left % right)
+            }
+            TokenKind::StarStar => {
                 check_numeric_operands(&expr.operator, &left, &right)?;
+                Ok(left.pow(right))
+            }
+            TokenKind::Greater => {
+                check_comparable_operands(&expr.operator, &left, &right)?;
                 Ok(Object::from(left > right))
             }
             TokenKind::GreaterEqual => {
-                check_numeric_operands(&expr.operator, &left, &right)?;
+                check_comparable_operands(&expr.operator, &left, &right)?;
                 Ok(Object::from(left >= right))
             }
             TokenKind::Less => {
-                check_numeric_operands(&expr.operator, &left, &right)?;
+                check_comparable_operands(&expr.operator, &left, &right)?;
                 Ok(Object::from(left < right))
             }
             TokenKind::LessEqual => {
-                check_numeric_operands(&expr.operator, &left, &right)?;
+                check_comparable_operands(&expr.operator, &left, &right)?;
                 Ok(Object::from(left <= right))
             }
             TokenKind::BangEqual => Ok(Object::from(left != right)),
@@ -109,42 +164,209 @@ impl expr::Visitor<ResultObject> for Interpreter {
         }
     }
 
-    fn visit_grouping_expr(&self, expr: &Grouping<ResultObject, Self>) -> ResultObject {
+    fn visit_call_expr(&mut self, expr: &Call<ResultObject, Self>) -> ResultObject {
+        let callee = self.evaluate(expr.callee.as_ref())?;
+
+        let mut arguments = Vec::with_capacity(expr.arguments.len());
+        for argument in &expr.arguments {
+            arguments.push(self.evaluate(argument.as_ref())?);
+        }
+
+        let function: Handle<dyn Callable> = match callee.value {
+            ObjectValue::Function(f) => f,
+            ObjectValue::NativeFunction(f) => f,
+            _ => {
+                return Err(ErrorCode::RuntimeError(
+                    expr.paren.clone(),
+                    "Can only call functions and classes.".to_string(),
+                ))
+            }
+        };
+
+        if !function.arity().accepts(arguments.len()) {
+            return Err(ErrorCode::RuntimeError(
+                expr.paren.clone(),
+                format!(
+                    "Expected {} arguments but got {}.",
+                    function.arity(),
+                    arguments.len()
+                ),
+            ));
+        }
+
+        if self.call_depth >= self.max_call_depth {
+            return Err(ErrorCode::RuntimeError(
+                expr.paren.clone(),
+                "stack overflow: recursion too deep".to_string(),
+            ));
+        }
+
+        self.call_depth += 1;
+        let result = function.call(self, &expr.paren, arguments);
+        self.call_depth -= 1;
+
+        result
+    }
+
+    fn visit_grouping_expr(&mut self, expr: &Grouping<ResultObject, Self>) -> ResultObject {
         self.evaluate(expr.expression.as_ref())
     }
 
-    fn visit_literal_expr(&self, expr: &Literal<ResultObject, Self>) -> ResultObject {
-        Ok(expr.value.clone().into())
+    fn visit_index_expr(&mut self, expr: &Index<ResultObject, Self>) -> ResultObject {
+        let object = self.evaluate(expr.object.as_ref())?;
+        let index = self.evaluate(expr.index.as_ref())?;
+
+        match &object.value {
+            ObjectValue::List(list) => {
+                let list = access(list);
+                let position = index_position(&expr.bracket, &index, list.len())?;
+                Ok(list[position].clone())
+            }
+            // A missing key reads as nil
+            ObjectValue::Map(map) => Ok(map_get(map, &index)),
+            // Strings are indexed by character and give a one character string
+            ObjectValue::String(string) => {
+                let position = index_position(&expr.bracket, &index, string.chars().count())?;
+                let character = string.chars().nth(position).unwrap_or_default();
+                Ok(Object::from(character.to_string().as_str()))
+            }
+            _ => Err(ErrorCode::RuntimeError(
+                expr.bracket.clone(),
+                format!(
+                    "Only lists, maps and strings can be indexed, found {}.",
+                    object.kind
+                ),
+            )),
+        }
     }
 
-    fn visit_unary_expr(&self, expr: &Unary<ResultObject, Self>) -> ResultObject {
+    fn visit_index_set_expr(&mut self, expr: &IndexSet<ResultObject, Self>) -> ResultObject {
+        let target = match expr.target.kind() {
+            ExprKind::Index(target) => target,
+            // The parser only builds an index assignment from an index expression
+            _ => return Err(ErrorCode::ProcessError),
+        };
+
+        let object = self.evaluate(target.object.as_ref())?;
+        let index = self.evaluate(target.index.as_ref())?;
+        let value = self.evaluate(expr.value.as_ref())?;
+
+        // `const` is shallow, it only forbids rebinding the name so the elements of a constant
+        // list or map can still be set
+        match &object.value {
+            ObjectValue::List(list) => {
+                let mut list = access(list);
+                let position = index_position(&target.bracket, &index, list.len())?;
+                list[position] = value.clone();
+            }
+            ObjectValue::Map(map) => {
+                check_map_key(&target.bracket, &index)?;
+                map_insert(map, index, value.clone());
+            }
+            _ => {
+                return Err(ErrorCode::RuntimeError(
+                    target.bracket.clone(),
+                    format!("Only lists and maps can be indexed, found {}.", object.kind),
+                ))
+            }
+        }
+
+        Ok(value)
+    }
+
+    fn visit_list_expr(&mut self, expr: &List<ResultObject, Self>) -> ResultObject {
+        let mut elements = Vec::with_capacity(expr.elements.len());
+        for element in &expr.elements {
+            elements.push(self.evaluate(element.as_ref())?);
+        }
+
+        Ok(Object::from(elements))
+    }
+
+    fn visit_literal_expr(&mut self, expr: &Literal<ResultObject, Self>) -> ResultObject {
+        Ok(expr.object().clone())
+    }
+
+    fn visit_lambda_expr(&mut self, expr: &Lambda<ResultObject, Self>) -> ResultObject {
+        // A lambda is a function without a name, it closes over the environment it is
+        // evaluated in just like a declared function
+        let name = Token::new(
+            TokenKind::Identifier,
+            "lambda",
+            None,
+            expr.keyword.line,
+            expr.keyword.column,
+        );
+        let function = callable::Function::new(
+            name,
+            expr.params.clone(),
+            expr.body.clone(),
+            self.environment.clone(),
+        );
+        Ok(Object::from(function))
+    }
+
+    /// the right operand is only evaluated when the left one doesn't decide the result, `or`
+    /// returns a truthy left operand and `and` a falsey one as they are
+    fn visit_logical_expr(&mut self, expr: &Logical<ResultObject, Self>) -> ResultObject {
+        let left = self.evaluate(expr.left.as_ref())?;
+
+        let short_circuit = match expr.operator.kind {
+            TokenKind::Or => left.is_truthy(),
+            _ => !left.is_truthy(),
+        };
+        if short_circuit {
+            return Ok(left);
+        }
+
+        self.evaluate(expr.right.as_ref())
+    }
+
+    // Keys holding a list are mutable through the shared list, see `Hash for Object`
+    #[allow(clippy::mutable_key_type)]
+    fn visit_map_expr(&mut self, expr: &Map<ResultObject, Self>) -> ResultObject {
+        let mut map = HashMap::with_capacity(expr.entries.len());
+        for (key, value) in &expr.entries {
+            let key = self.evaluate(key.as_ref())?;
+            check_map_key(&expr.brace, &key)?;
+            let value = self.evaluate(value.as_ref())?;
+            map.insert(key, value);
+        }
+
+        Ok(Object::from(map))
+    }
+
+    fn visit_unary_expr(&mut self, expr: &Unary<ResultObject, Self>) -> ResultObject {
         let right = self.evaluate(expr.right.as_ref())?;
 
         match expr.operator.kind {
             TokenKind::Minus => {
                 check_numeric_operand(&expr.operator, &right)?;
-                // return -(float)right;
                 match right.value {
-                    ObjectValue::Number(n) => Ok(Object::from(-n as f32)),
+                    ObjectValue::Number(n) => match n.checked_neg() {
+                        Some(n) => Ok(Object::from(n)),
+                        None => Ok(Object::from(-(n as f32))),
+                    },
                     ObjectValue::Float(f) => Ok(Object::from(-f)),
                     // TODO: Update error to correct type
                     _ => Err(ErrorCode::ProcessError),
                 }
             }
+            TokenKind::Plus => {
+                check_numeric_operand(&expr.operator, &right)?;
+                Ok(right)
+            }
 
-            TokenKind::Bang => Ok(self.is_truthy(&right)),
+            TokenKind::Bang => Ok(right.logical_not()),
             // TODO: Update error to correct type
             _ => Err(ErrorCode::ProcessError),
         }
     }
 
-    fn visit_variable_expr(&self, expr: &Variable<ResultObject, Self>) -> ResultObject {
-        match self.environment.borrow_mut().get(&expr.name) {
-            // TODO: Update error to reference error to unknown variable
-            // "Undefined variable '" + name.lexeme + "'.");
-            None => Err(ErrorCode::ProcessError),
-            Some(obj) => Ok(obj.borrow_mut().clone()),
-        }
+    fn visit_variable_expr(&mut self, expr: &Variable<ResultObject, Self>) -> ResultObject {
+        let obj = access(&self.environment).get(&expr.name)?;
+        let value = access(&obj).clone();
+        Ok(value)
     }
 }
 
@@ -159,28 +381,95 @@ impl stmt::Visitor<ResultObject, Self> for Interpreter {
 
     fn visit_print_stmt(&mut self, stmt: &Print<ResultObject, Self, Self>) -> ResultObject {
         let value = self.evaluate(stmt.expression.as_ref())?;
-        println!("{}", value);
+        writeln!(self.output, "{}", value).map_err(ErrorCode::IO)?;
         Ok(value)
     }
 
     fn visit_let_stmt(&mut self, stmt: &Let<ResultObject, Self, Self>) -> ResultObject {
-        let obj = self
-            .environment
-            .borrow_mut()
-            .define(&stmt.name.lexeme, self.evaluate(stmt.initializer.as_ref())?);
-        let obj_borrow = obj.borrow_mut();
+        let value = self.evaluate(stmt.initializer.as_ref())?;
+        let obj = access(&self.environment).define(&stmt.name.lexeme, value);
+        let obj_borrow = access(&obj);
         Ok(obj_borrow.to_owned())
     }
 
     fn visit_const_stmt(&mut self, stmt: &Const<ResultObject, Self, Self>) -> ResultObject {
-        // TODO: Make const immutable data and can't accept assign after initialisation
-        let obj = self
-            .environment
-            .borrow_mut()
-            .define(&stmt.name.lexeme, self.evaluate(stmt.initializer.as_ref())?);
-        let obj_borrow = obj.borrow_mut();
+        let value = self.evaluate(stmt.initializer.as_ref())?;
+        let obj = access(&self.environment).define_const(&stmt.name.lexeme, value);
+        let obj_borrow = access(&obj);
         Ok(obj_borrow.to_owned())
     }
+
+    fn visit_block_stmt(&mut self, stmt: &Block<ResultObject, Self, Self>) -> ResultObject {
+        let environment = Environment::with_enclosing(self.environment.clone());
+        self.execute_block(&stmt.statements, share(environment))
+    }
+
+    fn visit_for_each_stmt(&mut self, stmt: &ForEach<ResultObject, Self, Self>) -> ResultObject {
+        let iterable = self.evaluate(stmt.iterable.as_ref())?;
+        // Iterate over a snapshot so the body can change the list safely
+        let elements = match &iterable.value {
+            ObjectValue::List(list) => access(list).clone(),
+            _ => {
+                return Err(ErrorCode::RuntimeError(
+                    stmt.variable.clone(),
+                    format!("Can only iterate over lists, found {}.", iterable.kind),
+                ))
+            }
+        };
+
+        for element in elements {
+            // Every iteration gets its own binding of the loop variable
+            let mut environment = Environment::with_enclosing(self.environment.clone());
+            environment.define(&stmt.variable.lexeme, element);
+            self.execute_block(std::slice::from_ref(&stmt.body), share(environment))?;
+        }
+
+        Ok(Object::default())
+    }
+
+    fn visit_function_stmt(&mut self, stmt: &Function<ResultObject, Self, Self>) -> ResultObject {
+        // The closure is the environment the name is defined in, so the body can refer to
+        // the function itself once it is called
+        let function = callable::Function::new(
+            stmt.name.clone(),
+            stmt.params.clone(),
+            stmt.body.clone(),
+            self.environment.clone(),
+        );
+        let obj = access(&self.environment).define(&stmt.name.lexeme, Object::from(function));
+        let obj_borrow = access(&obj);
+        Ok(obj_borrow.to_owned())
+    }
+
+    fn visit_if_stmt(&mut self, stmt: &If<ResultObject, Self, Self>) -> ResultObject {
+        let condition = self.evaluate(stmt.condition.as_ref())?;
+
+        if condition.is_truthy() {
+            self.execute(stmt.then_branch.as_ref())
+        } else if let Some(else_branch) = &stmt.else_branch {
+            self.execute(else_branch.as_ref())
+        } else {
+            Ok(Object::default())
+        }
+    }
+
+    fn visit_return_stmt(&mut self, stmt: &Return<ResultObject, Self, Self>) -> ResultObject {
+        let value = self.evaluate(stmt.value.as_ref())?;
+        Err(ErrorCode::Return(value))
+    }
+
+    fn visit_while_stmt(&mut self, stmt: &While<ResultObject, Self, Self>) -> ResultObject {
+        loop {
+            let condition = self.evaluate(stmt.condition.as_ref())?;
+            if !condition.is_truthy() {
+                break;
+            }
+
+            self.execute(stmt.body.as_ref())?;
+        }
+
+        Ok(Object::default())
+    }
 }
 
 /// public method
@@ -189,9 +478,24 @@ impl Interpreter {
         &mut self,
         statements: &[Box<dyn Stmt<ResultObject, Self, Self>>],
     ) -> Result<Vec<Object>, ErrorCode> {
-        let mut vec = Vec::new();
+        // Top level functions are declared ahead of every other statement, so a function can be
+        // called before its declaration and functions can refer to each other.
+        let mut hoisted = Vec::with_capacity(statements.len());
         for statement in statements {
-            match self.execute(statement.as_ref()) {
+            hoisted.push(match statement.kind() {
+                StmtKind::Function(_) => Some(self.execute(statement.as_ref())),
+                _ => None,
+            });
+        }
+
+        let mut vec = Vec::new();
+        for (statement, hoisted) in statements.iter().zip(hoisted) {
+            let result = match hoisted {
+                Some(result) => result,
+                None => self.execute(statement.as_ref()),
+            };
+
+            match result {
                 Ok(v) => vec.push(v),
                 Err(e) => {
                     Reporter::runtime_error(&e);
@@ -205,24 +509,117 @@ impl Interpreter {
 
 /// private methods
 impl Interpreter {
-    fn execute(&mut self, stmt: &dyn Stmt<ResultObject, Self, Self>) -> ResultObject {
+    pub(crate) fn execute(&mut self, stmt: &dyn Stmt<ResultObject, Self, Self>) -> ResultObject {
         stmt.accept(self)
     }
 
-    fn evaluate(&self, expr: &dyn Expr<ResultObject, Self>) -> ResultObject {
+    /// time the interpreter was built at
+    pub(crate) fn started(&self) -> Instant {
+        self.started
+    }
+
+    /// sink receiving the program output
+    pub(crate) fn output(&mut self) -> &mut dyn Write {
+        self.output.as_mut()
+    }
+
+    /// swaps the sink receiving the program output, returns the previous one
+    pub(crate) fn replace_output(&mut self, output: Box<dyn Write>) -> Box<dyn Write> {
+        std::mem::replace(&mut self.output, output)
+    }
+
+    /// executes the statements in the given environment and restores the current environment
+    /// afterwards, even if one of the statements fails.
+    pub(crate) fn execute_block(
+        &mut self,
+        statements: &[Box<dyn Stmt<ResultObject, Self, Self>>],
+        environment: Shared<Environment>,
+    ) -> ResultObject {
+        let previous = std::mem::replace(&mut self.environment, environment);
+
+        let mut result = Ok(Object::default());
+        for statement in statements {
+            result = self.execute(statement.as_ref());
+            if result.is_err() {
+                break;
+            }
+        }
+
+        self.environment = previous;
+        result
+    }
+
+    fn evaluate(&mut self, expr: &dyn Expr<ResultObject, Self>) -> ResultObject {
         expr.accept(self)
     }
+}
 
-    /// checks the boolean equivalent of expression evaluation and returns a boolean object
-    fn is_truthy(&self, object: &Object) -> Object {
-        match object.kind {
-            ObjectKind::Nil => Object::from(false),
-            ObjectKind::Bool => object.clone(),
-            _ => Object::from(true),
+/// converts the index object to a position in a sequence of the given length, a negative index
+/// counts from the end so `-1` is the last element.
+fn index_position(bracket: &Token, index: &Object, len: usize) -> Result<usize, ErrorCode> {
+    let position = match index.value {
+        ObjectValue::Number(n) => n,
+        _ => {
+            return Err(ErrorCode::RuntimeError(
+                bracket.clone(),
+                format!("Index must be a number, found {}.", index.kind),
+            ))
         }
+    };
+
+    let from_start = if position < 0 {
+        usize::try_from(position.unsigned_abs())
+            .ok()
+            .and_then(|offset| len.checked_sub(offset))
+    } else {
+        usize::try_from(position).ok()
+    };
+
+    match from_start {
+        Some(from_start) if from_start < len => Ok(from_start),
+        _ => Err(ErrorCode::RuntimeError(
+            bracket.clone(),
+            format!("Index {} out of range for length {}.", position, len),
+        )),
+    }
+}
+
+/// a `NaN` key is never equal to itself, the entry could be stored but never found again
+fn check_map_key(token: &Token, key: &Object) -> Result<(), ErrorCode> {
+    match key.value {
+        ObjectValue::Float(f) if f.is_nan() => Err(ErrorCode::RuntimeError(
+            token.clone(),
+            "Map key can't be NaN.".to_string(),
+        )),
+        _ => Ok(()),
     }
 }
 
+/// value of the key in the map, nil when it's missing
+fn map_get(map: &Shared<HashMap<Object, Object>>, key: &Object) -> Object {
+    if matches!(key.kind, ObjectKind::List | ObjectKind::Map) {
+        // comparing a list or map key can read the map itself, as in `m[m]`, so it's looked up
+        // in a copy of the entries instead of holding the map
+        #[allow(clippy::mutable_key_type)]
+        let entries = access(map).clone();
+        return entries.get(key).cloned().unwrap_or_default();
+    }
+    access(map).get(key).cloned().unwrap_or_default()
+}
+
+/// sets the key of the map, like `map_get` a list or map key is inserted in a copy of the
+/// entries which then replaces them
+fn map_insert(map: &Shared<HashMap<Object, Object>>, key: Object, value: Object) {
+    if matches!(key.kind, ObjectKind::List | ObjectKind::Map) {
+        #[allow(clippy::mutable_key_type)]
+        let mut entries = access(map).clone();
+        entries.insert(key, value);
+        *access(map) = entries;
+        return;
+    }
+    access(map).insert(key, value);
+}
+
 fn check_numeric_operand(operator: &Token, right: &Object) -> Result<(), ErrorCode> {
     match right.kind {
         ObjectKind::Float | ObjectKind::Number => Ok(()),
@@ -257,11 +654,45 @@ fn check_numeric_operands(
     Ok(())
 }
 
+/// ordering is defined between two numbers, integer or float, and between two strings, comparing
+/// anything else such as `nil` is an error rather than a silent `false`
+fn check_comparable_operands(
+    operator: &Token,
+    left: &Object,
+    right: &Object,
+) -> Result<(), ErrorCode> {
+    let numeric = |o: &Object| o.is_kind(ObjectKind::Number) || o.is_kind(ObjectKind::Float);
+    if (numeric(left) && numeric(right))
+        || (left.is_kind(ObjectKind::String) && right.is_kind(ObjectKind::String))
+    {
+        return Ok(());
+    }
+
+    Err(ErrorCode::RuntimeError(
+        operator.clone(),
+        format!(
+            "Operands must be two numbers or two strings: {} {}",
+            left, right
+        ),
+    ))
+}
+
 fn check_numeric_or_string_operands(
     operator: &Token,
     left: &Object,
     right: &Object,
 ) -> Result<(), ErrorCode> {
+    if left.is_kind(ObjectKind::List) || right.is_kind(ObjectKind::List) {
+        if left.is_kind(ObjectKind::List) && right.is_kind(ObjectKind::List) {
+            return Ok(());
+        }
+
+        return Err(ErrorCode::RuntimeError(
+            operator.clone(),
+            format!("Operands must be two lists: {} {}", left, right),
+        ));
+    }
+
     match left.kind {
         ObjectKind::Float | ObjectKind::Number | ObjectKind::String => Ok(()),
         _ => Err(ErrorCode::RuntimeError(
@@ -288,21 +719,44 @@ fn check_numeric_or_string_operands(
 
 #[cfg(test)]
 mod interpreter_tests {
-    use crate::ast::expr::{Assign, Binary, Grouping, Literal, Unary};
-    use crate::ast::stmt::{Expression, Let, Print};
-    use crate::interpreter::{Interpreter, InterpreterBuilder, ResultObject};
-    use crate::object::Object;
+    use crate::ast::expr::{Assign, Binary, Grouping, Literal, Unary, Variable};
+    use crate::ast::stmt::{Expression, Let, Print, Stmt};
+    use crate::errors::ErrorCode;
+    use crate::interpreter::{
+        check_numeric_or_string_operands, Interpreter, InterpreterBuilder, ResultObject,
+    };
+    use crate::object::{Object, ObjectKind};
+    use crate::parser::Parser;
+    use crate::scanner::ScannerBuilder;
     use crate::token;
     use crate::token::{Token, TokenKind};
 
+    /// scans and parses the source, the statements can be run one by one with `execute`
+    fn parse_source(source: &str) -> Vec<Box<dyn Stmt<ResultObject, Interpreter, Interpreter>>> {
+        let tokens = ScannerBuilder::default()
+            .source(source)
+            .build()
+            .scan_tokens()
+            .unwrap();
+        Parser::from_tokens(&tokens).parse().unwrap()
+    }
+
+    /// scans, parses and interprets the source returning the value of every statement
+    fn interpret_source(source: &str) -> Vec<Object> {
+        let statements = parse_source(source);
+
+        let mut interpreter = InterpreterBuilder::new().build();
+        interpreter.interpret(&statements).unwrap()
+    }
+
     #[test]
     fn evaluate_unary_expr() {
         let unary: Unary<ResultObject, Interpreter> = Unary::new(
-            Token::new(TokenKind::Minus, "-", None, 1),
+            Token::new(TokenKind::Minus, "-", None, 1, 1),
             Box::new(Literal::new(token::Literal::from(1.0))),
         );
 
-        let interpreter = InterpreterBuilder::new().build();
+        let mut interpreter = InterpreterBuilder::new().build();
 
         let result = interpreter.evaluate(&unary).unwrap();
 
@@ -313,11 +767,11 @@ mod interpreter_tests {
     fn evaluate_binary_expr() {
         let expression: Binary<ResultObject, Interpreter> = Binary::new(
             Box::new(Literal::new(token::Literal::from(10))),
-            Token::new(TokenKind::Plus, "+", None, 1),
+            Token::new(TokenKind::Plus, "+", None, 1, 1),
             Box::new(Literal::new(token::Literal::from(10))),
         );
 
-        let interpreter = InterpreterBuilder::new().build();
+        let mut interpreter = InterpreterBuilder::new().build();
 
         let result = interpreter.evaluate(&expression).unwrap();
 
@@ -328,15 +782,15 @@ mod interpreter_tests {
     fn evaluate_complex_expr() {
         let expression: Binary<ResultObject, Interpreter> = Binary::new(
             Box::new(Literal::new(token::Literal::from(10))),
-            Token::new(TokenKind::Plus, "+", None, 1),
+            Token::new(TokenKind::Plus, "+", None, 1, 1),
             Box::new(Binary::new(
                 Box::new(Literal::new(token::Literal::from(10))),
-                Token::new(TokenKind::Star, "*", None, 1),
+                Token::new(TokenKind::Star, "*", None, 1, 1),
                 Box::new(Literal::new(token::Literal::from(10))),
             )),
         );
 
-        let interpreter = InterpreterBuilder::new().build();
+        let mut interpreter = InterpreterBuilder::new().build();
 
         let result = interpreter.evaluate(&expression).unwrap();
 
@@ -347,11 +801,11 @@ mod interpreter_tests {
     fn evaluate_string_and_number_expr() {
         let expression: Binary<ResultObject, Interpreter> = Binary::new(
             Box::new(Literal::new(token::Literal::from("string"))),
-            Token::new(TokenKind::Plus, "+", None, 1),
+            Token::new(TokenKind::Plus, "+", None, 1, 1),
             Box::new(Literal::new(token::Literal::from(10))),
         );
 
-        let interpreter = InterpreterBuilder::new().build();
+        let mut interpreter = InterpreterBuilder::new().build();
 
         let result = interpreter.evaluate(&expression).unwrap();
 
@@ -362,40 +816,55 @@ mod interpreter_tests {
     fn error_invalid_expr() {
         let expression: Binary<ResultObject, Interpreter> = Binary::new(
             Box::new(Literal::new(token::Literal::from("string"))),
-            Token::new(TokenKind::Minus, "-", None, 1),
+            Token::new(TokenKind::Minus, "-", None, 1, 1),
             Box::new(Literal::new(token::Literal::from(10))),
         );
 
-        let interpreter = InterpreterBuilder::new().build();
+        let mut interpreter = InterpreterBuilder::new().build();
 
         let result = interpreter.evaluate(&expression);
 
         assert!(result.is_err());
     }
 
+    #[test]
+    fn error_undefined_variable() {
+        let expression: Variable<ResultObject, Interpreter> =
+            Variable::new(Token::new(TokenKind::Identifier, "missing", None, 1, 1));
+
+        let mut interpreter = InterpreterBuilder::new().build();
+
+        match interpreter.evaluate(&expression) {
+            Err(ErrorCode::RuntimeError(token, message)) => {
+                assert_eq!(&*token.lexeme, "missing");
+                assert_eq!(message, "Undefined variable 'missing'.");
+            }
+            _ => panic!("expected an undefined variable error"),
+        }
+    }
+
     #[test]
     fn evaluate_grouped_expr() {
-        // TODO: !false should be evaluate to true
         let grouping: Grouping<ResultObject, Interpreter> = Grouping::new(Box::new(Unary::new(
-            Token::new(TokenKind::Bang, "!", None, 1),
+            Token::new(TokenKind::Bang, "!", None, 1, 1),
             Box::new(Literal::new(token::Literal::from(false))),
         )));
 
-        let interpreter = InterpreterBuilder::new().build();
+        let mut interpreter = InterpreterBuilder::new().build();
 
         let result = interpreter.evaluate(&grouping).unwrap();
 
-        assert_eq!(result, Object::from(false));
+        assert_eq!(result, Object::from(true));
     }
 
     #[test]
     fn execute_print_complex_expr() {
         let expression: Binary<ResultObject, Interpreter> = Binary::new(
             Box::new(Literal::new(token::Literal::from(10))),
-            Token::new(TokenKind::Plus, "+", None, 1),
+            Token::new(TokenKind::Plus, "+", None, 1, 1),
             Box::new(Binary::new(
                 Box::new(Literal::new(token::Literal::from(10))),
-                Token::new(TokenKind::Star, "*", None, 1),
+                Token::new(TokenKind::Star, "*", None, 1, 1),
                 Box::new(Literal::new(token::Literal::from(10))),
             )),
         );
@@ -413,10 +882,10 @@ mod interpreter_tests {
     fn execute_expression_complex_expr() {
         let expression: Binary<ResultObject, Interpreter> = Binary::new(
             Box::new(Literal::new(token::Literal::from(10))),
-            Token::new(TokenKind::Plus, "+", None, 1),
+            Token::new(TokenKind::Plus, "+", None, 1, 1),
             Box::new(Binary::new(
                 Box::new(Literal::new(token::Literal::from(10))),
-                Token::new(TokenKind::Star, "*", None, 1),
+                Token::new(TokenKind::Star, "*", None, 1, 1),
                 Box::new(Literal::new(token::Literal::from(10))),
             )),
         );
@@ -434,10 +903,10 @@ mod interpreter_tests {
     fn interpret_complex_expr() {
         let expression: Binary<ResultObject, Interpreter> = Binary::new(
             Box::new(Literal::new(token::Literal::from(10))),
-            Token::new(TokenKind::Plus, "+", None, 1),
+            Token::new(TokenKind::Plus, "+", None, 1, 1),
             Box::new(Binary::new(
                 Box::new(Literal::new(token::Literal::from(10))),
-                Token::new(TokenKind::Slash, "/", None, 1),
+                Token::new(TokenKind::Slash, "/", None, 1, 1),
                 Box::new(Literal::new(token::Literal::from(10))),
             )),
         );
@@ -447,7 +916,7 @@ mod interpreter_tests {
         let mut interpreter = InterpreterBuilder::new().build();
 
         let result = interpreter.interpret(&[Box::new(statement)]).unwrap();
-        let v = result.get(0).unwrap();
+        let v = result.first().unwrap();
 
         assert_eq!(v, &Object::from(11));
     }
@@ -456,23 +925,24 @@ mod interpreter_tests {
     fn interpret_variable_expr() {
         let expression: Binary<ResultObject, Interpreter> = Binary::new(
             Box::new(Literal::new(token::Literal::from(10))),
-            Token::new(TokenKind::Plus, "+", None, 1),
+            Token::new(TokenKind::Plus, "+", None, 1, 1),
             Box::new(Binary::new(
                 Box::new(Literal::new(token::Literal::from(10))),
-                Token::new(TokenKind::Slash, "/", None, 1),
+                Token::new(TokenKind::Slash, "/", None, 1, 1),
                 Box::new(Literal::new(token::Literal::from(10))),
             )),
         );
 
         let statement = Let::new(
-            Token::new(TokenKind::Identifier, "a", None, 1),
+            Token::new(TokenKind::Identifier, "a", None, 1, 1),
             Box::new(expression),
+            None,
         );
 
         let mut interpreter = InterpreterBuilder::new().build();
 
         let result = interpreter.interpret(&[Box::new(statement)]).unwrap();
-        let v = result.get(0).unwrap();
+        let v = result.first().unwrap();
 
         assert_eq!(v, &Object::from(11));
     }
@@ -482,20 +952,21 @@ mod interpreter_tests {
         let mut interpreter = InterpreterBuilder::new().build();
         let literal: Literal<ResultObject, Interpreter> = Literal::new(token::Literal::from(2));
         let statement: Let<ResultObject, Interpreter, Interpreter> = Let::new(
-            Token::new(TokenKind::Identifier, "a", None, 1),
+            Token::new(TokenKind::Identifier, "a", None, 1, 1),
             Box::new(literal),
+            None,
         );
 
         interpreter.interpret(&[Box::new(statement)]).unwrap();
 
         let expr = Binary::new(
             Box::new(Literal::new(token::Literal::from(1))),
-            Token::new(TokenKind::Plus, "+", None, 1),
+            Token::new(TokenKind::Plus, "+", None, 1, 1),
             Box::new(Literal::new(token::Literal::from(2))),
         );
 
         let assign = Assign::new(
-            Token::new(TokenKind::Identifier, "a", None, 1),
+            Token::new(TokenKind::Identifier, "a", None, 1, 1),
             Box::new(expr),
         );
 
@@ -505,4 +976,661 @@ mod interpreter_tests {
 
         assert_eq!(result, Object::from(3));
     }
+
+    #[test]
+    fn interpret_recursive_function() {
+        let result = interpret_source(
+            "fun fib(n) { if (n < 2) return n; return fib(n - 1) + fib(n - 2); }
+            fib(10) == 55;",
+        );
+
+        assert_eq!(result.last().unwrap(), &Object::from(true));
+    }
+
+    #[test]
+    fn interpret_hoisted_mutually_recursive_functions() {
+        let result = interpret_source(
+            "is_even(10) == true;
+            fun is_even(n) { if (n == 0) return true; return is_odd(n - 1); }
+            fun is_odd(n) { if (n == 0) return false; return is_even(n - 1); }",
+        );
+
+        assert_eq!(result.first().unwrap(), &Object::from(true));
+    }
+
+    #[test]
+    fn interpret_for_loop_closures_capture_each_iteration() {
+        let result = interpret_source(
+            "let first; let second; let third;
+            for (let i = 0; i < 3; i = i + 1) {
+                fun current() { return i; }
+                if (i == 0) first = current;
+                if (i == 1) second = current;
+                if (i == 2) third = current;
+            }
+            first(); second(); third();",
+        );
+
+        assert_eq!(
+            result[result.len() - 3..],
+            [Object::from(0), Object::from(1), Object::from(2)]
+        );
+    }
+
+    #[test]
+    fn interpret_while_loop() {
+        let result = interpret_source("let i = 0; while (i < 5) i = i + 1; i;");
+
+        assert_eq!(result.last().unwrap(), &Object::from(5));
+    }
+
+    #[test]
+    fn error_on_unbounded_recursion() {
+        // test threads have a smaller stack than the binary runs the interpreter with
+        let run = || {
+            let statements = parse_source("fun forever(n) { return forever(n + 1); } forever(0);");
+
+            let mut interpreter = InterpreterBuilder::new().build();
+            interpreter.execute(statements[0].as_ref()).unwrap();
+            let result = interpreter.execute(statements[1].as_ref());
+
+            assert!(
+                matches!(result, Err(ErrorCode::RuntimeError(_, ref m)) if m == "stack overflow: recursion too deep")
+            );
+            assert_eq!(interpreter.call_depth, 0);
+        };
+
+        std::thread::Builder::new()
+            .stack_size(crate::oxa::STACK_SIZE)
+            .spawn(run)
+            .unwrap()
+            .join()
+            .unwrap();
+    }
+
+    #[test]
+    fn binary_operands_evaluate_left_to_right() {
+        let mut oxa = crate::oxa::Oxa::builder().build();
+
+        assert_eq!(
+            oxa.run_capture(
+                "fun a() { print \"a\"; return 1; }
+                fun b() { print \"b\"; return 2; }
+                print a() + b();"
+            )
+            .unwrap(),
+            "a\nb\n3\n"
+        );
+    }
+
+    #[test]
+    fn interpret_list_concatenation() {
+        let result = interpret_source("[1, 2] + [3]; let list = [1]; list + [] == list;");
+
+        assert_eq!(result[0].to_string(), "[1, 2, 3]");
+        assert_eq!(result[2], Object::from(true));
+    }
+
+    #[test]
+    fn error_adding_number_to_list() {
+        let left = Object::from(vec![Object::from(1)]);
+        let operator = Token::new(TokenKind::Plus, "+", None, 1, 1);
+
+        let result = check_numeric_or_string_operands(&operator, &left, &Object::from(2));
+        assert!(matches!(result, Err(ErrorCode::RuntimeError(_, _))));
+        assert!(check_numeric_or_string_operands(&operator, &left, &left).is_ok());
+    }
+
+    #[test]
+    fn interpret_map_literal_and_indexing() {
+        let result = interpret_source(
+            "let m = { \"a\": 1, \"b\": 2 };
+            m[\"a\"];
+            m[\"missing\"];
+            m[\"a\"] = 3;
+            m[\"a\"];
+            m;",
+        );
+
+        assert_eq!(result[1], Object::from(1));
+        assert_eq!(result[2], Object::default());
+        assert_eq!(result[4], Object::from(3));
+        assert_eq!(result[5].to_string(), "{a: 3, b: 2}");
+    }
+
+    #[test]
+    fn interpret_self_containing_list_and_map() {
+        let result = interpret_source(
+            "let a = [1]; a[0] = a; a;
+            let b = [1]; b[0] = b; a == b;
+            let m = {}; m[\"self\"] = m; m;",
+        );
+
+        assert_eq!(result[2].to_string(), "[[...]]");
+        assert_eq!(result[5], Object::from(true));
+        assert_eq!(result[8].to_string(), "{self: {...}}");
+    }
+
+    #[test]
+    fn interpret_map_keyed_with_itself() {
+        let result = interpret_source(
+            "let m = {}; m[{}] = 0; m[m] = 1; m[[m]] = 2;
+            m[m]; m[[m]]; m[{}]; len(m);",
+        );
+
+        assert_eq!(result[4], Object::from(1));
+        assert_eq!(result[5], Object::from(2));
+        assert_eq!(result[6], Object::from(0));
+        assert_eq!(result[7], Object::from(3));
+    }
+
+    #[test]
+    fn error_on_nan_map_key() {
+        for source in [
+            "let nan = 2.0 ** 200 - 2.0 ** 200; { nan: 1 };",
+            "let nan = 2.0 ** 200 - 2.0 ** 200; let m = {}; m[nan] = 1;",
+        ] {
+            let statements = parse_source(source);
+
+            let mut interpreter = InterpreterBuilder::new().build();
+            let (last, rest) = statements.split_last().unwrap();
+            for statement in rest {
+                interpreter.execute(statement.as_ref()).unwrap();
+            }
+            match interpreter.execute(last.as_ref()) {
+                Err(ErrorCode::RuntimeError(_, message)) => {
+                    assert_eq!(message, "Map key can't be NaN.")
+                }
+                _ => panic!("expected a NaN key error in {}", source),
+            }
+        }
+    }
+
+    #[test]
+    fn interpret_list_indexing() {
+        let result = interpret_source("let list = [1, 2, 3]; list[1] = 5; list[1]; list[3];");
+
+        assert_eq!(result.len(), 3);
+        assert_eq!(result[2], Object::from(5));
+    }
+
+    #[test]
+    fn interpret_map_literal_statement_and_block() {
+        let result = interpret_source("{ \"a\": 1 }; { let a = 1; }");
+
+        assert_eq!(result[0].to_string(), "{a: 1}");
+        assert_eq!(result[1], Object::from(1));
+    }
+
+    #[test]
+    fn interpret_for_each_loop() {
+        let result = interpret_source("let sum = 0; for (x in [1, 2, 3]) sum = sum + x; sum;");
+
+        assert_eq!(result.last().unwrap(), &Object::from(6));
+    }
+
+    #[test]
+    fn error_iterating_a_number() {
+        let statements = parse_source("for (x in 5) print x;");
+
+        let mut interpreter = InterpreterBuilder::new().build();
+        let result = interpreter.execute(statements[0].as_ref());
+
+        assert!(matches!(result, Err(ErrorCode::RuntimeError(_, _))));
+    }
+
+    #[test]
+    fn interpret_negative_indices() {
+        let result = interpret_source(
+            "[10, 20, 30][-1] == 30; \"abc\"[-1]; \"abc\"[0]; [1, 2][-3]; let list = [1, 2]; list[-2] = 5; list;",
+        );
+
+        assert_eq!(result[0], Object::from(true));
+        assert_eq!(result[1], Object::from("c"));
+        assert_eq!(result[2], Object::from("a"));
+        // `[1, 2][-3]` is out of range and doesn't produce a value
+        assert_eq!(result.len(), 6);
+        assert_eq!(result[5].to_string(), "[5, 2]");
+    }
+
+    #[test]
+    fn interpret_remainder_and_power() {
+        let result = interpret_source(
+            "let x = 10; x %= 3; x == 1; let y = 3; y **= 2; y; 2 ** 3 ** 2; -2 ** 2; 7.5 % 2; 2 ** -1;",
+        );
+
+        assert_eq!(result[2], Object::from(true));
+        assert_eq!(result[5], Object::from(9));
+        // `**` is right associative
+        assert_eq!(result[6], Object::from(512));
+        assert_eq!(result[7], Object::from(-4));
+        assert_eq!(result[8], Object::from(1.5));
+        assert_eq!(result[9], Object::from(0.5));
+    }
+
+    #[test]
+    fn interpret_compound_assignment() {
+        let result = interpret_source(
+            "let a = 10; a += 5; a; a -= 3; a; a *= 2; a; a /= 4; a; let s = \"a\"; s += \"b\"; s;",
+        );
+
+        assert_eq!(result[2], Object::from(15));
+        assert_eq!(result[4], Object::from(12));
+        assert_eq!(result[6], Object::from(24));
+        assert_eq!(result[8], Object::from(6));
+        assert_eq!(result[11], Object::from("ab"));
+    }
+
+    #[test]
+    fn interpret_print_callables() {
+        let result = interpret_source("fun add(a, b) { return a + b; } print add; print string;");
+
+        assert_eq!(result[1].to_string(), "<fn add>");
+        assert_eq!(result[2].to_string(), "<native string>");
+    }
+
+    #[test]
+    fn error_assign_undeclared_variable() {
+        let statements = parse_source("x = 5;");
+
+        let mut interpreter = InterpreterBuilder::new().build();
+        match interpreter.execute(statements[0].as_ref()) {
+            Err(ErrorCode::RuntimeError(token, message)) => {
+                assert_eq!(&*token.lexeme, "x");
+                assert_eq!(
+                    message,
+                    "Cannot assign to undeclared variable 'x'; use 'let' first"
+                );
+            }
+            _ => panic!("expected an undeclared variable error"),
+        }
+    }
+
+    #[test]
+    fn evaluate_cached_literal() {
+        let literal: Literal<ResultObject, Interpreter> =
+            Literal::new(token::Literal::from("text"));
+        let mut interpreter = InterpreterBuilder::new().build();
+
+        let first = interpreter.evaluate(&literal).unwrap();
+        let mut second = interpreter.evaluate(&literal).unwrap();
+        assert_eq!(first, second);
+
+        second = second + Object::from(" changed");
+        assert_eq!(second, Object::from("text changed"));
+        assert_eq!(
+            interpreter.evaluate(&literal).unwrap(),
+            Object::from("text")
+        );
+
+        let result = interpret_source(
+            "let s = \"\"; for (let i = 0; i < 3; i = i + 1) { s = s + \"a\"; } s;",
+        );
+        assert_eq!(result.last(), Some(&Object::from("aaa")));
+    }
+
+    #[test]
+    fn interpret_comparison_results_compared_with_bools() {
+        let result = interpret_source(
+            "(1 < 2) == true; (1 > 2) == false; (1 < 2) == (2 < 3); (1 <= 1.5) == true; (1 < 2) == 1;",
+        );
+
+        assert_eq!(result[0], Object::from(true));
+        assert_eq!(result[1], Object::from(true));
+        assert_eq!(result[2], Object::from(true));
+        assert_eq!(result[3], Object::from(true));
+        // a bool is never equal to a number
+        assert_eq!(result[4], Object::from(false));
+    }
+
+    #[test]
+    fn execute_if_statement() {
+        assert_eq!(
+            interpret_source("if (1 < 2) print \"yes\";"),
+            vec![Object::from("yes")]
+        );
+        assert_eq!(
+            interpret_source("if (1 > 2) print \"yes\";"),
+            vec![Object::default()]
+        );
+    }
+
+    #[test]
+    fn execute_if_else_statement() {
+        assert_eq!(
+            interpret_source("if (1 < 2) print \"yes\"; else print \"no\";"),
+            vec![Object::from("yes")]
+        );
+        assert_eq!(
+            interpret_source("if (1 > 2) print \"yes\"; else print \"no\";"),
+            vec![Object::from("no")]
+        );
+    }
+
+    #[test]
+    fn dangling_else_binds_to_nearest_if() {
+        assert_eq!(
+            interpret_source("if (true) if (false) print \"inner\"; else print \"else\";"),
+            vec![Object::from("else")]
+        );
+        // the outer `if` has no `else` so nothing runs
+        assert_eq!(
+            interpret_source("if (false) if (true) print \"inner\"; else print \"else\";"),
+            vec![Object::default()]
+        );
+    }
+
+    #[test]
+    fn for_loop_matches_equivalent_while() {
+        let for_loop = interpret_source(
+            "let sum = 0; for (let i = 1; i <= 10; i = i + 1) sum = sum + i * i; sum;",
+        );
+        let while_loop = interpret_source(
+            "let sum = 0; { let i = 1; while (i <= 10) { sum = sum + i * i; i = i + 1; } } sum;",
+        );
+
+        assert_eq!(for_loop.last(), Some(&Object::from(385)));
+        assert_eq!(for_loop.last(), while_loop.last());
+    }
+
+    #[test]
+    fn for_loop_with_omitted_clauses() {
+        let result = interpret_source("let i = 0; for (; i < 4;) i = i + 1; i;");
+        assert_eq!(result.last(), Some(&Object::from(4)));
+
+        let result = interpret_source(
+            "fun first_square_over(n) { let i = 0; for (;;) { i = i + 1; if (i * i > n) return i; } }
+            first_square_over(50);",
+        );
+        assert_eq!(result.last(), Some(&Object::from(8)));
+    }
+
+    #[test]
+    fn block_scopes_shadow_outer_variables() {
+        let result = interpret_source("let a = 1; { let a = 2; print a; } a;");
+        assert_eq!(result[1], Object::from(2));
+        assert_eq!(result[2], Object::from(1));
+
+        let result = interpret_source("let a = 1; { { a = a + 1; } a = a * 10; } a;");
+        assert_eq!(result.last(), Some(&Object::from(20)));
+    }
+
+    #[test]
+    fn error_reading_variable_after_its_block() {
+        let statements = parse_source("{ let inner = 1; } inner;");
+
+        let mut interpreter = InterpreterBuilder::new().build();
+        assert_eq!(
+            interpreter.execute(statements[0].as_ref()).unwrap(),
+            Object::from(1)
+        );
+        assert!(interpreter.execute(statements[1].as_ref()).is_err());
+    }
+
+    #[test]
+    fn error_assign_constant() {
+        let statements = parse_source("const a = 1; a = 2; a;");
+
+        let mut interpreter = InterpreterBuilder::new().build();
+        interpreter.execute(statements[0].as_ref()).unwrap();
+        match interpreter.execute(statements[1].as_ref()) {
+            Err(ErrorCode::RuntimeError(token, message)) => {
+                assert_eq!(&*token.lexeme, "a");
+                assert_eq!(message, "Cannot assign to constant 'a'");
+            }
+            _ => panic!("expected a constant assignment error"),
+        }
+        assert_eq!(
+            interpreter.execute(statements[2].as_ref()).unwrap(),
+            Object::from(1)
+        );
+    }
+
+    #[test]
+    fn const_list_elements_can_be_set() {
+        let statements = parse_source("const a = [1, 2]; a = [3]; a[0] = 9; a;");
+
+        let mut interpreter = InterpreterBuilder::new().build();
+        interpreter.execute(statements[0].as_ref()).unwrap();
+        match interpreter.execute(statements[1].as_ref()) {
+            Err(ErrorCode::RuntimeError(_, message)) => {
+                assert_eq!(message, "Cannot assign to constant 'a'")
+            }
+            _ => panic!("expected a constant assignment error"),
+        }
+        assert_eq!(
+            interpreter.execute(statements[2].as_ref()).unwrap(),
+            Object::from(9)
+        );
+        assert_eq!(
+            interpreter
+                .execute(statements[3].as_ref())
+                .unwrap()
+                .to_string(),
+            "[9, 2]"
+        );
+    }
+
+    #[test]
+    fn chained_assignment_sets_every_variable() {
+        let result = interpret_source("let a; let b; a = b = 5; a; b;");
+        assert_eq!(result[2], Object::from(5));
+        assert_eq!(result[3], Object::from(5));
+        assert_eq!(result[4], Object::from(5));
+    }
+
+    #[test]
+    fn evaluate_bang_equal() {
+        let result = interpret_source("10 != 20; 10 != 10; \"a\" != \"b\"; nil != nil;");
+        assert_eq!(
+            result,
+            vec![
+                Object::from(true),
+                Object::from(false),
+                Object::from(true),
+                Object::from(false)
+            ]
+        );
+    }
+
+    #[test]
+    fn evaluate_division() {
+        let result = interpret_source("7 / 2; 8 / 2; 1 / 0;");
+        assert_eq!(result[0], Object::from(3.5));
+        assert!(result[1].is_kind(ObjectKind::Number));
+        assert_eq!(result[1], Object::from(4));
+        assert!(result[2].is_kind(ObjectKind::Nil));
+    }
+
+    #[test]
+    fn evaluate_remainder() {
+        let result = interpret_source("10 % 3; 10.5 % 2; -7 % 3; 10 % 0; 10.5 % 0.0;");
+        assert!(result[0].is_kind(ObjectKind::Number));
+        assert_eq!(result[0], Object::from(1));
+        assert_eq!(result[1], Object::from(0.5));
+        assert_eq!(result[2], Object::from(-1));
+        assert!(result[3].is_kind(ObjectKind::Nil));
+        assert!(result[4].is_kind(ObjectKind::Nil));
+    }
+
+    #[test]
+    fn evaluate_logical_operators() {
+        let result = interpret_source(
+            "1 and 2; nil and 2; false or \"a\"; 1 or 2; nil or false; true and false or 3;",
+        );
+        assert_eq!(
+            result,
+            vec![
+                Object::from(2),
+                Object::default(),
+                Object::from("a"),
+                Object::from(1),
+                Object::from(false),
+                Object::from(3)
+            ]
+        );
+    }
+
+    #[test]
+    fn logical_operators_short_circuit() {
+        // `undefined` would fail to evaluate, so the statement only succeeds if it is skipped
+        let result = interpret_source("true or undefined; false and undefined;");
+        assert_eq!(result, vec![Object::from(true), Object::from(false)]);
+
+        let result = interpret_source("let calls = 0; fun call() { calls = calls + 1; return true; } false and call(); true or call(); calls;");
+        assert_eq!(result.last(), Some(&Object::from(0)));
+    }
+
+    #[test]
+    fn evaluate_pipeline() {
+        let result = interpret_source(
+            "-5 |> abs; -5 |> abs |> string; let a = [3, 1] |> sort; a; fun twice(x) { return x * 2; } 1 + 2 |> twice;",
+        );
+        assert_eq!(result[0], Object::from(5));
+        assert_eq!(result[1], Object::from("5"));
+        assert_eq!(result[3].to_string(), "[1, 3]");
+        assert_eq!(result.last(), Some(&Object::from(6)));
+    }
+
+    #[test]
+    fn evaluate_lambda() {
+        let result = interpret_source(
+            "let add = fun(a, b) { return a + b; }; add(2, 3) == 5; ((x) => x * 2)(4); add;",
+        );
+        assert_eq!(result[1], Object::from(true));
+        assert_eq!(result[2], Object::from(8));
+        assert_eq!(result[3].to_string(), "<fn lambda>");
+    }
+
+    #[test]
+    fn lambda_captures_its_environment() {
+        let result = interpret_source(
+            "fun adder(n) { return (x) => x + n; } let add2 = adder(2); add2(3); \
+             fun apply(f, x) { return f(x); } apply(fun(x) { return x - 1; }, 1); \
+             fun counter() { let count = 0; return fun() { count = count + 1; return count; }; } \
+             let next = counter(); next(); next();",
+        );
+        assert_eq!(result[2], Object::from(5));
+        assert_eq!(result[4], Object::from(0));
+        assert_eq!(result.last(), Some(&Object::from(2)));
+    }
+
+    #[test]
+    fn return_early_from_loops() {
+        let result = interpret_source(
+            "fun find(list, wanted) { for (let i = 0; i < len(list); i = i + 1) { \
+               if (list[i] == wanted) return i; } return -1; } \
+             fun first_even(list) { for (x in list) { if (x % 2 == 0) { return x; } } } \
+             fun countdown(n) { while (true) { if (n == 0) return; n = n - 1; } } \
+             find([4, 5, 6], 5); find([4], 7); first_even([1, 3, 8, 10]); first_even([1]); \
+             countdown(3);",
+        );
+        assert_eq!(result[3], Object::from(1));
+        assert_eq!(result[4], Object::from(-1));
+        assert_eq!(result[5], Object::from(8));
+        assert_eq!(result[6], Object::default());
+        assert_eq!(result[7], Object::default());
+    }
+
+    #[test]
+    fn evaluate_string_ordering() {
+        let result = interpret_source(
+            "\"apple\" < \"banana\"; \"b\" <= \"a\"; \"Zebra\" < \"apple\"; \"ab\" >= \"a\";",
+        );
+        assert_eq!(
+            result,
+            vec![
+                Object::from(true),
+                Object::from(false),
+                Object::from(true),
+                Object::from(true)
+            ]
+        );
+    }
+
+    #[test]
+    fn error_comparing_string_with_number() {
+        let expression: Binary<ResultObject, Interpreter> = Binary::new(
+            Box::new(Literal::new(token::Literal::from("a"))),
+            Token::new(TokenKind::Less, "<", None, 1, 1),
+            Box::new(Literal::new(token::Literal::from(1))),
+        );
+
+        let mut interpreter = InterpreterBuilder::new().build();
+        match interpreter.evaluate(&expression) {
+            Err(ErrorCode::RuntimeError(token, message)) => {
+                assert_eq!(token.kind, TokenKind::Less);
+                assert_eq!(message, "Operands must be two numbers or two strings: a 1");
+            }
+            _ => panic!("expected a comparison error"),
+        }
+    }
+
+    #[test]
+    fn evaluate_nil_equality() {
+        let result =
+            interpret_source("nil == nil; nil == 0; nil == false; nil != \"\"; nil != nil;");
+        assert_eq!(
+            result,
+            vec![
+                Object::from(true),
+                Object::from(false),
+                Object::from(false),
+                Object::from(true),
+                Object::from(false)
+            ]
+        );
+    }
+
+    #[test]
+    fn error_ordering_nil() {
+        let statements = parse_source("nil < 1; 1 >= nil; nil <= nil;");
+
+        let mut interpreter = InterpreterBuilder::new().build();
+        for (statement, operator) in statements.iter().zip(["<", ">=", "<="]) {
+            match interpreter.execute(statement.as_ref()) {
+                Err(ErrorCode::RuntimeError(token, message)) => {
+                    assert_eq!(&*token.lexeme, operator);
+                    assert!(message.starts_with("Operands must be two numbers or two strings"));
+                }
+                _ => panic!("expected an error comparing nil with {}", operator),
+            }
+        }
+    }
+
+    #[test]
+    fn interpret_numbers_with_underscores() {
+        let values = interpret_source("1_000_000 + 1; 0.000_5 * 2;");
+
+        assert_eq!(values[0], Object::from(1_000_001));
+        assert_eq!(values[1], Object::from(0.001));
+    }
+
+    #[test]
+    fn interpret_unary_keeps_the_numeric_kind() {
+        let values = interpret_source("-5; -5.0; +5; +2.5; -(-5); +-5;");
+
+        assert!(values[0].is_kind(ObjectKind::Number));
+        assert_eq!(values[0].as_number(), Some(-5));
+        assert!(values[1].is_kind(ObjectKind::Float));
+        assert_eq!(values[1].as_float(), Some(-5.0));
+        assert_eq!(values[2].as_number(), Some(5));
+        assert_eq!(values[3].as_float(), Some(2.5));
+        assert_eq!(values[4].as_number(), Some(5));
+        assert_eq!(values[5].as_number(), Some(-5));
+    }
+
+    #[test]
+    fn error_unary_plus_on_non_number() {
+        let statements = parse_source("+\"a\";");
+        let mut interpreter = InterpreterBuilder::new().build();
+
+        match interpreter.execute(statements[0].as_ref()) {
+            Err(ErrorCode::RuntimeError(token, message)) => {
+                assert_eq!(token.kind, TokenKind::Plus);
+                assert_eq!(message, "Operand must be a number: a");
+            }
+            _ => panic!("expected a runtime error"),
+        }
+    }
 }
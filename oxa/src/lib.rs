@@ -2,9 +2,12 @@ pub mod ast;
 pub mod errors;
 pub mod object;
 pub mod oxa;
+pub mod shared;
 pub mod token;
 
+mod callable;
 mod environment;
 mod interpreter;
+mod natives;
 mod parser;
 mod scanner;
@@ -0,0 +1,910 @@
+use crate::callable::{Arity, Callable, NativeFunction};
+use crate::errors::ErrorCode;
+use crate::interpreter::{Interpreter, ResultObject};
+use crate::object::{Object, ObjectKind, ObjectValue};
+use crate::shared::{access, Handle};
+use crate::token::Token;
+use std::cmp::Ordering;
+
+/// returns every native function defined in the global environment of the interpreter
+pub fn globals() -> Vec<NativeFunction> {
+    vec![
+        NativeFunction::new("string", Arity::Range(1, 2), string),
+        NativeFunction::new("sort", Arity::Exact(1), sort),
+        NativeFunction::new("eq_ignore_case", Arity::Exact(2), eq_ignore_case),
+        NativeFunction::new("write", Arity::Exact(1), write),
+        NativeFunction::new("repr", Arity::Exact(1), repr),
+        NativeFunction::new("assert_eq", Arity::Range(2, 3), assert_eq),
+        NativeFunction::new("min", Arity::AtLeast(1), min),
+        NativeFunction::new("max", Arity::AtLeast(1), max),
+        NativeFunction::new("abs", Arity::Exact(1), abs),
+        NativeFunction::new("floor", Arity::Exact(1), floor),
+        NativeFunction::new("ceil", Arity::Exact(1), ceil),
+        NativeFunction::new("round", Arity::Exact(1), round),
+        NativeFunction::new("sqrt", Arity::Exact(1), sqrt),
+        NativeFunction::new("pow", Arity::Exact(2), pow),
+        NativeFunction::new("len", Arity::Exact(1), len),
+        NativeFunction::new("clock", Arity::Exact(0), clock),
+        NativeFunction::new("join", Arity::Exact(2), join),
+        NativeFunction::new("map", Arity::Exact(2), map),
+        NativeFunction::new("filter", Arity::Exact(2), filter),
+        NativeFunction::new("reduce", Arity::Exact(3), reduce),
+        NativeFunction::new("range", Arity::Range(1, 3), range),
+        NativeFunction::new("pad", Arity::Exact(2), pad),
+        NativeFunction::new("pad_left", Arity::Exact(2), pad_left),
+    ]
+}
+
+/// `string(value)` converts any value to its printed form, `string(n, radix)` formats an integer
+/// in base 2, 8, 10 or 16.
+fn string(_: &mut Interpreter, paren: &Token, arguments: &[Object]) -> ResultObject {
+    let value = &arguments[0];
+    let radix = match arguments.get(1) {
+        None => return Ok(Object::from(value.to_string().as_str())),
+        Some(radix) => radix,
+    };
+
+    let n = match value.value {
+        ObjectValue::Number(n) => n,
+        _ => {
+            return Err(native_error(
+                paren,
+                format!(
+                    "string() with a radix expects an integer, found {}",
+                    value.kind
+                ),
+            ))
+        }
+    };
+
+    let sign = if n < 0 { "-" } else { "" };
+    let n = n.unsigned_abs();
+    let digits = match radix.value {
+        ObjectValue::Number(2) => format!("{:b}", n),
+        ObjectValue::Number(8) => format!("{:o}", n),
+        ObjectValue::Number(10) => format!("{}", n),
+        ObjectValue::Number(16) => format!("{:x}", n),
+        _ => {
+            return Err(native_error(
+                paren,
+                format!("Unsupported radix {}, expected 2, 8, 10 or 16", radix),
+            ))
+        }
+    };
+
+    Ok(Object::from(format!("{}{}", sign, digits).as_str()))
+}
+
+/// `sort(list)` returns a new list sorted by `Object::total_cmp`, the given list is unchanged.
+fn sort(_: &mut Interpreter, paren: &Token, arguments: &[Object]) -> ResultObject {
+    let list = match &arguments[0].value {
+        ObjectValue::List(list) => list,
+        _ => {
+            return Err(native_error(
+                paren,
+                format!("sort() expects a list, found {}", arguments[0].kind),
+            ))
+        }
+    };
+
+    let mut sorted = access(list).clone();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    Ok(Object::from(sorted))
+}
+
+/// `clock()` returns the seconds elapsed since the interpreter started as a float, the
+/// difference of two calls times the code run in between.
+fn clock(interpreter: &mut Interpreter, _: &Token, _: &[Object]) -> ResultObject {
+    Ok(Object::from(interpreter.started().elapsed().as_secs_f32()))
+}
+
+/// `len(value)` returns the number of chars of a string, elements of a list or entries of a map.
+fn len(_: &mut Interpreter, paren: &Token, arguments: &[Object]) -> ResultObject {
+    let value = &arguments[0];
+    let length = match (&value.kind, &value.value) {
+        (ObjectKind::String, ObjectValue::String(s)) => s.chars().count(),
+        (ObjectKind::List, ObjectValue::List(list)) => access(list).len(),
+        (ObjectKind::Map, ObjectValue::Map(map)) => access(map).len(),
+        _ => {
+            return Err(native_error(
+                paren,
+                format!("Object of kind {} has no length", value.kind),
+            ))
+        }
+    };
+    Ok(Object::from(length as i32))
+}
+
+/// `join(list, separator)` returns the printed form of every element separated by `separator`.
+fn join(_: &mut Interpreter, paren: &Token, arguments: &[Object]) -> ResultObject {
+    match (&arguments[0].value, &arguments[1].value) {
+        (ObjectValue::List(list), ObjectValue::String(separator)) => {
+            // formatting a copy keeps the list unlocked for elements holding it
+            let elements = access(list).clone();
+            let elements = elements.iter().map(|e| e.to_string()).collect::<Vec<_>>();
+            Ok(Object::from(elements.join(separator).as_str()))
+        }
+        _ => Err(native_error(
+            paren,
+            format!(
+                "join() expects a list and a string, found {} and {}",
+                arguments[0].kind, arguments[1].kind
+            ),
+        )),
+    }
+}
+
+/// `map(list, function)` returns a new list holding `function(element)` for every element.
+fn map(interpreter: &mut Interpreter, paren: &Token, arguments: &[Object]) -> ResultObject {
+    let (elements, function) = list_and_callback("map", paren, arguments, 1)?;
+    let mut mapped = Vec::with_capacity(elements.len());
+    for element in elements {
+        mapped.push(function.call(interpreter, paren, vec![element])?);
+    }
+    Ok(Object::from(mapped))
+}
+
+/// `filter(list, function)` returns a new list of the elements for which `function(element)`
+/// is truthy.
+fn filter(interpreter: &mut Interpreter, paren: &Token, arguments: &[Object]) -> ResultObject {
+    let (elements, function) = list_and_callback("filter", paren, arguments, 1)?;
+    let mut kept = Vec::new();
+    for element in elements {
+        if function
+            .call(interpreter, paren, vec![element.clone()])?
+            .is_truthy()
+        {
+            kept.push(element);
+        }
+    }
+    Ok(Object::from(kept))
+}
+
+/// `reduce(list, function, initial)` folds the list from the left, every element is combined
+/// with the accumulated value by `function(accumulator, element)` starting from `initial`.
+fn reduce(interpreter: &mut Interpreter, paren: &Token, arguments: &[Object]) -> ResultObject {
+    let (elements, function) = list_and_callback("reduce", paren, arguments, 2)?;
+    let mut accumulator = arguments[2].clone();
+    for element in elements {
+        accumulator = function.call(interpreter, paren, vec![accumulator, element])?;
+    }
+    Ok(accumulator)
+}
+
+/// `range(end)`, `range(start, end)` and `range(start, end, step)` return the list of integers
+/// from `start`, zero by default, up to but excluding `end` counting by `step`, one by default.
+/// A negative step counts down.
+fn range(_: &mut Interpreter, paren: &Token, arguments: &[Object]) -> ResultObject {
+    let mut integers = Vec::with_capacity(arguments.len());
+    for argument in arguments {
+        match argument.value {
+            ObjectValue::Number(n) => integers.push(n as i64),
+            _ => {
+                return Err(native_error(
+                    paren,
+                    format!("range() expects integers, found {}", argument.kind),
+                ))
+            }
+        }
+    }
+
+    let (start, end, step) = match integers[..] {
+        [end] => (0, end, 1),
+        [start, end] => (start, end, 1),
+        [start, end, step] => (start, end, step),
+        _ => unreachable!("the arity allows 1 to 3 arguments"),
+    };
+    if step == 0 {
+        return Err(native_error(
+            paren,
+            "range() step cannot be zero".to_string(),
+        ));
+    }
+
+    let mut elements = Vec::new();
+    let mut current = start;
+    while (step > 0 && current < end) || (step < 0 && current > end) {
+        elements.push(Object::from(current as i32));
+        current += step;
+    }
+    Ok(Object::from(elements))
+}
+
+/// `eq_ignore_case(a, b)` returns true if both strings are equal regardless of their case.
+fn eq_ignore_case(_: &mut Interpreter, paren: &Token, arguments: &[Object]) -> ResultObject {
+    match (&arguments[0].value, &arguments[1].value) {
+        (ObjectValue::String(a), ObjectValue::String(b)) => {
+            Ok(Object::from(a.to_lowercase() == b.to_lowercase()))
+        }
+        _ => Err(native_error(
+            paren,
+            format!(
+                "eq_ignore_case() expects two strings, found {} and {}",
+                arguments[0].kind, arguments[1].kind
+            ),
+        )),
+    }
+}
+
+/// `write(value)` prints the value like `print` does but without a trailing newline.
+fn write(interpreter: &mut Interpreter, _: &Token, arguments: &[Object]) -> ResultObject {
+    write!(interpreter.output(), "{}", arguments[0]).map_err(ErrorCode::IO)?;
+    Ok(Object::default())
+}
+
+/// `repr(value)` returns the structural form of the value, see `Object::repr`.
+fn repr(_: &mut Interpreter, _: &Token, arguments: &[Object]) -> ResultObject {
+    Ok(Object::from(arguments[0].repr().as_str()))
+}
+
+/// `assert_eq(a, b, message?)` fails with both values' `repr` when `a == b` is false, the
+/// optional message is put in front of them.
+fn assert_eq(_: &mut Interpreter, paren: &Token, arguments: &[Object]) -> ResultObject {
+    let (left, right) = (&arguments[0], &arguments[1]);
+    if left == right {
+        return Ok(Object::default());
+    }
+
+    let message = match arguments.get(2) {
+        Some(message) => format!("{}: {} != {}", message, left.repr(), right.repr()),
+        None => format!("assert_eq() failed: {} != {}", left.repr(), right.repr()),
+    };
+    Err(native_error(paren, message))
+}
+
+/// `min(a, b, ...)` returns the smallest of the numeric arguments.
+fn min(_: &mut Interpreter, paren: &Token, arguments: &[Object]) -> ResultObject {
+    extreme("min", paren, arguments, Ordering::Less)
+}
+
+/// `max(a, b, ...)` returns the largest of the numeric arguments.
+fn max(_: &mut Interpreter, paren: &Token, arguments: &[Object]) -> ResultObject {
+    extreme("max", paren, arguments, Ordering::Greater)
+}
+
+/// returns the first argument ordered `wanted` against every other one, the earliest wins ties
+fn extreme(name: &str, paren: &Token, arguments: &[Object], wanted: Ordering) -> ResultObject {
+    if let Some(argument) = arguments
+        .iter()
+        .find(|a| !a.is_kind(ObjectKind::Number) && !a.is_kind(ObjectKind::Float))
+    {
+        return Err(native_error(
+            paren,
+            format!("{}() expects numbers, found {}", name, argument.kind),
+        ));
+    }
+
+    let mut result = &arguments[0];
+    for argument in &arguments[1..] {
+        if argument.partial_cmp(result) == Some(wanted) {
+            result = argument;
+        }
+    }
+    Ok(result.clone())
+}
+
+/// `abs(n)` returns the absolute value of a number keeping its kind, the absolute value of the
+/// smallest integer doesn't fit an integer and is a float.
+fn abs(_: &mut Interpreter, paren: &Token, arguments: &[Object]) -> ResultObject {
+    match arguments[0].value {
+        ObjectValue::Number(n) => Ok(n
+            .checked_abs()
+            .map_or_else(|| Object::from((n as f32).abs()), Object::from)),
+        ObjectValue::Float(f) => Ok(Object::from(f.abs())),
+        _ => Err(native_error(
+            paren,
+            format!("abs() expects a number, found {}", arguments[0].kind),
+        )),
+    }
+}
+
+/// `floor(n)` returns the largest integer less than or equal to the number.
+fn floor(_: &mut Interpreter, paren: &Token, arguments: &[Object]) -> ResultObject {
+    to_integer("floor", paren, &arguments[0], f32::floor)
+}
+
+/// `ceil(n)` returns the smallest integer greater than or equal to the number.
+fn ceil(_: &mut Interpreter, paren: &Token, arguments: &[Object]) -> ResultObject {
+    to_integer("ceil", paren, &arguments[0], f32::ceil)
+}
+
+/// `round(n)` returns the nearest integer, halfway numbers are rounded away from zero.
+fn round(_: &mut Interpreter, paren: &Token, arguments: &[Object]) -> ResultObject {
+    to_integer("round", paren, &arguments[0], f32::round)
+}
+
+/// `sqrt(n)` returns the square root of the number as a float, the square root of a negative
+/// number is an error rather than `NaN`.
+fn sqrt(_: &mut Interpreter, paren: &Token, arguments: &[Object]) -> ResultObject {
+    let n = as_float("sqrt", paren, &arguments[0])?;
+    if n < 0.0 {
+        return Err(native_error(
+            paren,
+            format!("sqrt() of a negative number {}", arguments[0]),
+        ));
+    }
+    Ok(Object::from(n.sqrt()))
+}
+
+/// `pow(base, exponent)` raises the base to the exponent, the result is always a float unlike
+/// `**` which keeps integers.
+fn pow(_: &mut Interpreter, paren: &Token, arguments: &[Object]) -> ResultObject {
+    let base = as_float("pow", paren, &arguments[0])?;
+    let exponent = as_float("pow", paren, &arguments[1])?;
+    Ok(Object::from(base.powf(exponent)))
+}
+
+fn as_float(name: &str, paren: &Token, value: &Object) -> Result<f32, ErrorCode> {
+    match value.value {
+        ObjectValue::Number(n) => Ok(n as f32),
+        ObjectValue::Float(f) => Ok(f),
+        _ => Err(native_error(
+            paren,
+            format!("{}() expects a number, found {}", name, value.kind),
+        )),
+    }
+}
+
+/// rounds a float to an integer, integers are returned unchanged and floats too large for an
+/// integer stay floats
+fn to_integer(name: &str, paren: &Token, value: &Object, rounding: fn(f32) -> f32) -> ResultObject {
+    match value.value {
+        ObjectValue::Number(_) => Ok(value.clone()),
+        ObjectValue::Float(f) => {
+            let rounded = rounding(f);
+            if rounded >= i32::MIN as f32 && rounded < i32::MAX as f32 {
+                Ok(Object::from(rounded as i32))
+            } else {
+                Ok(Object::from(rounded))
+            }
+        }
+        _ => Err(native_error(
+            paren,
+            format!("{}() expects a number, found {}", name, value.kind),
+        )),
+    }
+}
+
+/// `pad(value, width)` returns the printed form of the value followed by spaces up to `width`
+/// characters, longer values are returned unchanged.
+fn pad(_: &mut Interpreter, paren: &Token, arguments: &[Object]) -> ResultObject {
+    let width = pad_width("pad", paren, &arguments[1])?;
+    Ok(Object::from(
+        format!("{:<width$}", arguments[0].to_string()).as_str(),
+    ))
+}
+
+/// `pad_left(value, width)` returns the printed form of the value preceded by spaces up to
+/// `width` characters, longer values are returned unchanged.
+fn pad_left(_: &mut Interpreter, paren: &Token, arguments: &[Object]) -> ResultObject {
+    let width = pad_width("pad_left", paren, &arguments[1])?;
+    Ok(Object::from(
+        format!("{:>width$}", arguments[0].to_string()).as_str(),
+    ))
+}
+
+fn pad_width(name: &str, paren: &Token, width: &Object) -> Result<usize, ErrorCode> {
+    match width.value {
+        ObjectValue::Number(n) if n >= 0 => Ok(n as usize),
+        _ => Err(native_error(
+            paren,
+            format!("{}() expects a non-negative width, found {}", name, width),
+        )),
+    }
+}
+
+/// checks the arguments of a higher-order native, returns a copy of the list elements so the
+/// callback can modify the list, and the callable taking `parameters` arguments
+fn list_and_callback(
+    name: &str,
+    paren: &Token,
+    arguments: &[Object],
+    parameters: usize,
+) -> Result<(Vec<Object>, Handle<dyn Callable>), ErrorCode> {
+    let mismatch = || {
+        native_error(
+            paren,
+            format!(
+                "{}() expects a list and a function, found {} and {}",
+                name, arguments[0].kind, arguments[1].kind
+            ),
+        )
+    };
+
+    let function: Handle<dyn Callable> = match &arguments[1].value {
+        ObjectValue::Function(f) => f.clone(),
+        ObjectValue::NativeFunction(f) => f.clone(),
+        _ => return Err(mismatch()),
+    };
+    let list = match &arguments[0].value {
+        ObjectValue::List(list) => list,
+        _ => return Err(mismatch()),
+    };
+
+    if !function.arity().accepts(parameters) {
+        return Err(native_error(
+            paren,
+            format!(
+                "{}() expects a function taking {} argument{}, found one taking {}",
+                name,
+                parameters,
+                if parameters == 1 { "" } else { "s" },
+                function.arity()
+            ),
+        ));
+    }
+    Ok((access(list).clone(), function))
+}
+
+fn native_error(paren: &Token, message: String) -> ErrorCode {
+    ErrorCode::RuntimeError(paren.clone(), message)
+}
+
+#[cfg(test)]
+mod natives_tests {
+    use super::*;
+    use crate::interpreter::InterpreterBuilder;
+    use crate::oxa::Oxa;
+    use crate::token::TokenKind;
+
+    /// value of the last statement of the source
+    fn evaluate(source: &str) -> Object {
+        Oxa::builder().build().eval(source).unwrap().pop().unwrap()
+    }
+
+    #[test]
+    fn string_with_radix() {
+        assert_eq!(evaluate("string(255, 16) == \"ff\";"), Object::from(true));
+        assert_eq!(evaluate("string(10, 2) == \"1010\";"), Object::from(true));
+        assert_eq!(evaluate("string(0 - 8, 8);"), Object::from("-10"));
+        assert_eq!(evaluate("string(12.5);"), Object::from("12.5"));
+    }
+
+    #[test]
+    fn sort_list() {
+        assert_eq!(
+            evaluate("sort([3, 1, 2]) == [1, 2, 3];"),
+            Object::from(true)
+        );
+        assert_eq!(
+            evaluate("let list = [3, 1, 2]; sort(list); list;"),
+            evaluate("[3, 1, 2];")
+        );
+        assert_eq!(
+            evaluate("sort([\"b\", 2, nil, \"a\", true, 1.5, false]);").to_string(),
+            "[nil, false, true, 1.5, 2, a, b]"
+        );
+    }
+
+    #[test]
+    fn error_string_with_unsupported_radix() {
+        let mut interpreter = InterpreterBuilder::new().build();
+        let paren = Token::new(TokenKind::RightParen, ")", None, 1, 1);
+
+        let result = string(
+            &mut interpreter,
+            &paren,
+            &[Object::from(255), Object::from(3)],
+        );
+        assert!(matches!(result, Err(ErrorCode::RuntimeError(_, _))));
+
+        let result = string(
+            &mut interpreter,
+            &paren,
+            &[Object::from(2.5), Object::from(16)],
+        );
+        assert!(matches!(result, Err(ErrorCode::RuntimeError(_, _))));
+    }
+
+    #[test]
+    fn compare_strings_ignoring_case() {
+        assert_eq!(
+            evaluate("eq_ignore_case(\"Yes\", \"yes\") == true;"),
+            Object::from(true)
+        );
+        assert_eq!(
+            evaluate("eq_ignore_case(\"Yes\", \"no\");"),
+            Object::from(false)
+        );
+    }
+
+    #[test]
+    fn error_compare_number_ignoring_case() {
+        let mut interpreter = InterpreterBuilder::new().build();
+        let paren = Token::new(TokenKind::RightParen, ")", None, 1, 1);
+
+        let result = eq_ignore_case(
+            &mut interpreter,
+            &paren,
+            &[Object::from("1"), Object::from(1)],
+        );
+        assert!(matches!(result, Err(ErrorCode::RuntimeError(_, _))));
+    }
+
+    #[test]
+    fn write_without_newline() {
+        let mut oxa = Oxa::builder().build();
+
+        assert_eq!(
+            oxa.run_capture("write(\"a\"); write(\"b\"); print 1;")
+                .unwrap(),
+            "ab1\n"
+        );
+        // writing returns nil, an empty string keeps the test output clean
+        assert_eq!(evaluate("write(\"\");"), Object::default());
+    }
+
+    #[test]
+    fn pad_printed_values() {
+        assert_eq!(evaluate("pad(\"x\", 3) == \"x  \";"), Object::from(true));
+        assert_eq!(
+            evaluate("pad_left(\"x\", 3) == \"  x\";"),
+            Object::from(true)
+        );
+        assert_eq!(evaluate("pad(12, 4);"), Object::from("12  "));
+        assert_eq!(evaluate("pad_left(\"long\", 2);"), Object::from("long"));
+    }
+
+    #[test]
+    fn error_pad_with_invalid_width() {
+        let mut interpreter = InterpreterBuilder::new().build();
+        let paren = Token::new(TokenKind::RightParen, ")", None, 1, 1);
+
+        let result = pad(
+            &mut interpreter,
+            &paren,
+            &[Object::from("x"), Object::from(-1)],
+        );
+        assert!(matches!(result, Err(ErrorCode::RuntimeError(_, _))));
+
+        let result = pad_left(
+            &mut interpreter,
+            &paren,
+            &[Object::from("x"), Object::from("3")],
+        );
+        assert!(matches!(result, Err(ErrorCode::RuntimeError(_, _))));
+    }
+
+    #[test]
+    fn repr_of_values() {
+        assert_eq!(evaluate("repr(\"x\") == '\"x\"';"), Object::from(true));
+        assert_eq!(evaluate("repr(5) == \"5\";"), Object::from(true));
+        assert_eq!(evaluate("repr(nil) == \"nil\";"), Object::from(true));
+        assert_eq!(
+            evaluate("repr([1, \"1\", [nil]]);"),
+            Object::from("[1, \"1\", [nil]]")
+        );
+        assert_eq!(
+            evaluate("repr({\"a\": \"b\"});"),
+            Object::from("{\"a\": \"b\"}")
+        );
+    }
+
+    #[test]
+    fn min_and_max_of_numbers() {
+        assert_eq!(evaluate("min(3, 1, 2) == 1;"), Object::from(true));
+        assert_eq!(evaluate("max(3, 1, 2) == 3;"), Object::from(true));
+        assert_eq!(evaluate("min(2, 0.5);"), Object::from(0.5));
+        assert_eq!(evaluate("max(7);"), Object::from(7));
+    }
+
+    #[test]
+    fn error_min_of_non_numbers() {
+        let mut interpreter = InterpreterBuilder::new().build();
+        let paren = Token::new(TokenKind::RightParen, ")", None, 1, 1);
+
+        let result = min(
+            &mut interpreter,
+            &paren,
+            &[Object::from("a"), Object::from(1)],
+        );
+        match result {
+            Err(ErrorCode::RuntimeError(_, message)) => {
+                assert_eq!(message, "min() expects numbers, found string")
+            }
+            _ => panic!("expected a runtime error"),
+        }
+
+        // a call without arguments is rejected before reaching the native
+        let min = NativeFunction::new("min", Arity::AtLeast(1), min);
+        assert!(!crate::callable::Callable::arity(&min).accepts(0));
+    }
+
+    #[test]
+    fn math_natives() {
+        assert_eq!(evaluate("abs(-5) == 5;"), Object::from(true));
+        assert_eq!(evaluate("floor(2.7) == 2;"), Object::from(true));
+        assert_eq!(evaluate("ceil(2.1) == 3;"), Object::from(true));
+        assert_eq!(evaluate("round(2.5) == 3;"), Object::from(true));
+
+        assert!(evaluate("abs(-5);").is_kind(ObjectKind::Number));
+        assert_eq!(evaluate("abs(-2.5);"), Object::from(2.5));
+        assert!(evaluate("floor(-2.5);").is_kind(ObjectKind::Number));
+        assert_eq!(evaluate("floor(-2.5);"), Object::from(-3));
+        assert_eq!(evaluate("round(-2.5);"), Object::from(-3));
+        assert_eq!(evaluate("ceil(4);"), Object::from(4));
+        assert!(evaluate("round(100000.0 * 100000.0);").is_kind(ObjectKind::Float));
+    }
+
+    #[test]
+    fn error_math_natives_on_non_numbers() {
+        let mut interpreter = InterpreterBuilder::new().build();
+        let paren = Token::new(TokenKind::RightParen, ")", None, 1, 1);
+
+        for native in [abs, floor, ceil, round] {
+            let result = native(&mut interpreter, &paren, &[Object::from("1")]);
+            assert!(matches!(result, Err(ErrorCode::RuntimeError(_, _))));
+        }
+    }
+
+    #[test]
+    fn sqrt_and_pow() {
+        assert_eq!(evaluate("sqrt(9) == 3.0;"), Object::from(true));
+        assert_eq!(evaluate("pow(2, 10) == 1024.0;"), Object::from(true));
+        assert!(evaluate("sqrt(9);").is_kind(ObjectKind::Float));
+        assert!(evaluate("pow(2, 10);").is_kind(ObjectKind::Float));
+        assert_eq!(evaluate("pow(4, 0.5);"), Object::from(2.0));
+    }
+
+    #[test]
+    fn error_sqrt_of_negative_number() {
+        let mut interpreter = InterpreterBuilder::new().build();
+        let paren = Token::new(TokenKind::RightParen, ")", None, 1, 1);
+
+        let result = sqrt(&mut interpreter, &paren, &[Object::from(-4)]);
+        match result {
+            Err(ErrorCode::RuntimeError(_, message)) => {
+                assert_eq!(message, "sqrt() of a negative number -4")
+            }
+            _ => panic!("expected a runtime error"),
+        }
+
+        let result = pow(
+            &mut interpreter,
+            &paren,
+            &[Object::from(2), Object::from("2")],
+        );
+        assert!(matches!(result, Err(ErrorCode::RuntimeError(_, _))));
+    }
+
+    #[test]
+    fn join_list_elements() {
+        assert_eq!(
+            evaluate("join([1, 2, 3], \", \") == \"1, 2, 3\";"),
+            Object::from(true)
+        );
+        assert_eq!(evaluate("join([], \"-\") == \"\";"), Object::from(true));
+        assert_eq!(
+            evaluate("join([\"a\", nil, [1.5]], \"\");"),
+            Object::from("anil[1.5]")
+        );
+    }
+
+    #[test]
+    fn error_join_with_invalid_arguments() {
+        let mut interpreter = InterpreterBuilder::new().build();
+        let paren = Token::new(TokenKind::RightParen, ")", None, 1, 1);
+
+        let result = join(
+            &mut interpreter,
+            &paren,
+            &[Object::from("ab"), Object::from("")],
+        );
+        assert!(matches!(result, Err(ErrorCode::RuntimeError(_, _))));
+
+        let result = join(
+            &mut interpreter,
+            &paren,
+            &[Object::from(vec![Object::from(1)]), Object::from(1)],
+        );
+        assert!(matches!(result, Err(ErrorCode::RuntimeError(_, _))));
+    }
+
+    #[test]
+    fn range_of_integers() {
+        assert_eq!(evaluate("range(3) == [0, 1, 2];"), Object::from(true));
+        assert_eq!(evaluate("range(2, 5) == [2, 3, 4];"), Object::from(true));
+        assert_eq!(evaluate("range(0, 10, 4);"), evaluate("[0, 4, 8];"));
+        assert_eq!(evaluate("range(3, 0, 0 - 1);"), evaluate("[3, 2, 1];"));
+        assert_eq!(evaluate("range(5, 2);"), evaluate("[];"));
+        assert_eq!(
+            evaluate("let sum = 0; for (i in range(3)) sum = sum + i; sum;"),
+            Object::from(3)
+        );
+    }
+
+    #[test]
+    fn error_range_with_invalid_arguments() {
+        let mut interpreter = InterpreterBuilder::new().build();
+        let paren = Token::new(TokenKind::RightParen, ")", None, 1, 1);
+
+        let result = range(
+            &mut interpreter,
+            &paren,
+            &[Object::from(0), Object::from(3), Object::from(0)],
+        );
+        match result {
+            Err(ErrorCode::RuntimeError(_, message)) => {
+                assert_eq!(message, "range() step cannot be zero")
+            }
+            _ => panic!("expected a zero step error"),
+        }
+
+        let result = range(&mut interpreter, &paren, &[Object::from(2.5)]);
+        assert!(matches!(result, Err(ErrorCode::RuntimeError(_, _))));
+    }
+
+    #[test]
+    fn length_of_collections() {
+        assert_eq!(evaluate("len(\"hello\");"), Object::from(5));
+        assert_eq!(evaluate("len(\"abc\") == 3;"), Object::from(true));
+        assert_eq!(evaluate("len(\"héllo\");"), Object::from(5));
+        assert_eq!(evaluate("len([1, 2]) == 2;"), Object::from(true));
+        assert_eq!(evaluate("len({\"a\": 1}) == 1;"), Object::from(true));
+        assert_eq!(evaluate("len([]);"), Object::from(0));
+    }
+
+    #[test]
+    fn error_length_of_number() {
+        let mut interpreter = InterpreterBuilder::new().build();
+        let paren = Token::new(TokenKind::RightParen, ")", None, 1, 1);
+
+        match len(&mut interpreter, &paren, &[Object::from(5)]) {
+            Err(ErrorCode::RuntimeError(_, message)) => {
+                assert_eq!(message, "Object of kind number has no length")
+            }
+            _ => panic!("expected a runtime error"),
+        }
+
+        for value in [Object::from(true), Object::default()] {
+            let result = len(&mut interpreter, &paren, &[value]);
+            assert!(matches!(result, Err(ErrorCode::RuntimeError(_, _))));
+        }
+    }
+
+    #[test]
+    fn assert_eq_of_equal_values() {
+        assert_eq!(evaluate("assert_eq(1, 1);"), Object::default());
+        assert_eq!(
+            evaluate("assert_eq([1, \"a\"], [1, \"a\"]);"),
+            Object::default()
+        );
+    }
+
+    #[test]
+    fn error_assert_eq_of_different_values() {
+        let mut interpreter = InterpreterBuilder::new().build();
+        let paren = Token::new(TokenKind::RightParen, ")", None, 1, 1);
+
+        let result = assert_eq(
+            &mut interpreter,
+            &paren,
+            &[Object::from(1), Object::from(2)],
+        );
+        match result {
+            Err(ErrorCode::RuntimeError(_, message)) => {
+                assert_eq!(message, "assert_eq() failed: 1 != 2")
+            }
+            _ => panic!("expected a runtime error"),
+        }
+
+        let result = assert_eq(
+            &mut interpreter,
+            &paren,
+            &[Object::from("1"), Object::from(1), Object::from("sum")],
+        );
+        match result {
+            Err(ErrorCode::RuntimeError(_, message)) => {
+                assert_eq!(message, "sum: \"1\" != 1")
+            }
+            _ => panic!("expected a runtime error"),
+        }
+    }
+
+    #[test]
+    fn map_and_filter_lists() {
+        assert_eq!(
+            evaluate("map([1, 2, 3], fun(x) { return x * 2; }) == [2, 4, 6];"),
+            Object::from(true)
+        );
+        assert_eq!(
+            evaluate("filter([1, 2, 3, 4], fun(x) { return x % 2 == 0; }) == [2, 4];"),
+            Object::from(true)
+        );
+        assert_eq!(evaluate("map([1, -2], abs);").to_string(), "[1, 2]");
+        assert_eq!(evaluate("filter([], (x) => true);").to_string(), "[]");
+    }
+
+    #[test]
+    fn error_map_and_filter_arguments() {
+        let mut interpreter = InterpreterBuilder::new().build();
+        let paren = Token::new(TokenKind::RightParen, ")", None, 1, 1);
+
+        let result = map(
+            &mut interpreter,
+            &paren,
+            &[Object::from(1), Object::from(2)],
+        );
+        match result {
+            Err(ErrorCode::RuntimeError(_, message)) => assert_eq!(
+                message,
+                "map() expects a list and a function, found number and number"
+            ),
+            _ => panic!("expected a runtime error"),
+        }
+
+        let pow = Object::from(NativeFunction::new("pow", Arity::Exact(2), pow));
+        let result = filter(
+            &mut interpreter,
+            &paren,
+            &[Object::from(vec![Object::from(1)]), pow],
+        );
+        match result {
+            Err(ErrorCode::RuntimeError(_, message)) => assert_eq!(
+                message,
+                "filter() expects a function taking 1 argument, found one taking 2"
+            ),
+            _ => panic!("expected a runtime error"),
+        }
+    }
+
+    #[test]
+    fn reduce_lists() {
+        assert_eq!(
+            evaluate("reduce([1, 2, 3, 4], fun(a, b) { return a + b; }, 0) == 10;"),
+            Object::from(true)
+        );
+        assert_eq!(
+            evaluate("reduce([], fun(a, b) { return a + b; }, 7);"),
+            Object::from(7)
+        );
+        assert_eq!(
+            evaluate("reduce([\"b\", \"c\"], (a, b) => a + b, \"a\");"),
+            Object::from("abc")
+        );
+    }
+
+    #[test]
+    fn error_reduce_arguments() {
+        let mut interpreter = InterpreterBuilder::new().build();
+        let paren = Token::new(TokenKind::RightParen, ")", None, 1, 1);
+        let abs = Object::from(NativeFunction::new("abs", Arity::Exact(1), abs));
+
+        let result = reduce(
+            &mut interpreter,
+            &paren,
+            &[Object::from("list"), abs.clone(), Object::from(0)],
+        );
+        match result {
+            Err(ErrorCode::RuntimeError(_, message)) => assert_eq!(
+                message,
+                "reduce() expects a list and a function, found string and native function"
+            ),
+            _ => panic!("expected a runtime error"),
+        }
+
+        let result = reduce(
+            &mut interpreter,
+            &paren,
+            &[Object::from(vec![Object::from(1)]), abs, Object::from(0)],
+        );
+        match result {
+            Err(ErrorCode::RuntimeError(_, message)) => assert_eq!(
+                message,
+                "reduce() expects a function taking 2 arguments, found one taking 1"
+            ),
+            _ => panic!("expected a runtime error"),
+        }
+    }
+
+    #[test]
+    fn clock_counts_elapsed_seconds() {
+        let first = evaluate("clock();");
+        assert!(first.is_kind(ObjectKind::Float));
+        assert_eq!(
+            evaluate("let start = clock(); clock() >= start;"),
+            Object::from(true)
+        );
+        assert_eq!(evaluate("clock() >= 0;"), Object::from(true));
+    }
+}
@@ -1,9 +1,14 @@
+use crate::callable::{Function, NativeFunction};
 use crate::token::{Literal, LiteralKind};
 use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
 
 use crate::errors::reporter::Reporter;
+use crate::errors::ErrorCode;
+use crate::shared::{access, share, Handle, Shared};
 use std::fmt::{Display, Formatter, Result};
-use std::ops::{Add, Div, Mul, Sub};
+use std::ops::{Add, Div, Mul, Rem, Sub};
 
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub enum ObjectKind {
@@ -11,20 +16,59 @@ pub enum ObjectKind {
     Float,
     String,
     Bool,
+    Function,
+    NativeFunction,
+    List,
+    Map,
     #[default]
     Nil,
 }
 
-#[derive(Clone, Debug, Default, PartialEq)]
+/// The value held by an object.
+///
+/// # Numeric promotion
+///
+/// | operands          | `+` `-` `*` `%` `**`            | `/`                               |
+/// |-------------------|---------------------------------|-----------------------------------|
+/// | number, number    | number, float when it overflows | number when exact, float otherwise |
+/// | number, float     | float                           | float                             |
+/// | float, number     | float                           | float                             |
+/// | float, float      | float                           | float                             |
+///
+/// `**` with a negative integer exponent gives a float. `/` and `%` by zero report an arithmetic
+/// error and give nil.
+#[derive(Clone, Debug, Default)]
 pub enum ObjectValue {
     Number(i32),
     Float(f32),
     String(String),
     Bool(bool),
+    Function(Handle<Function>),
+    NativeFunction(Handle<NativeFunction>),
+    /// lists are shared by reference, copying a list value aliases the same elements
+    List(Shared<Vec<Object>>),
+    /// maps are shared by reference like lists
+    Map(Shared<HashMap<Object, Object>>),
     #[default]
     Nil,
 }
 
+impl Display for ObjectKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match &self {
+            ObjectKind::Number => write!(f, "number"),
+            ObjectKind::Float => write!(f, "float"),
+            ObjectKind::String => write!(f, "string"),
+            ObjectKind::Bool => write!(f, "bool"),
+            ObjectKind::Function => write!(f, "function"),
+            ObjectKind::NativeFunction => write!(f, "native function"),
+            ObjectKind::List => write!(f, "list"),
+            ObjectKind::Map => write!(f, "map"),
+            ObjectKind::Nil => write!(f, "nil"),
+        }
+    }
+}
+
 impl From<Literal> for ObjectKind {
     fn from(v: Literal) -> Self {
         match v.value {
@@ -56,6 +100,11 @@ impl Display for ObjectValue {
             ObjectValue::Number(n) => write!(f, "{}", n),
             ObjectValue::String(s) => write!(f, "{}", s),
             ObjectValue::Bool(b) => write!(f, "{}", b),
+            ObjectValue::Function(fun) => write!(f, "{}", fun),
+            ObjectValue::NativeFunction(fun) => write!(f, "{}", fun),
+            ObjectValue::List(_) | ObjectValue::Map(_) => {
+                write!(f, "{}", self.format_nested(false, &mut Vec::new()))
+            }
             ObjectValue::Nil => write!(f, "nil"),
         }
     }
@@ -67,7 +116,10 @@ impl Sub for ObjectValue {
     fn sub(self, rhs: Self) -> Self::Output {
         match self {
             ObjectValue::Number(l) => match rhs {
-                ObjectValue::Number(r) => ObjectValue::Number(l - r),
+                ObjectValue::Number(r) => match l.checked_sub(r) {
+                    Some(n) => ObjectValue::Number(n),
+                    None => ObjectValue::Float(l as f32 - r as f32),
+                },
                 ObjectValue::Float(r) => ObjectValue::Float(l as f32 - r),
                 _ => {
                     Reporter::arithmetic_error(&format!("{} - {}", l, rhs));
@@ -97,7 +149,10 @@ impl Mul for ObjectValue {
     fn mul(self, rhs: Self) -> Self::Output {
         match self {
             ObjectValue::Number(l) => match rhs {
-                ObjectValue::Number(r) => ObjectValue::Number(l * r),
+                ObjectValue::Number(r) => match l.checked_mul(r) {
+                    Some(n) => ObjectValue::Number(n),
+                    None => ObjectValue::Float(l as f32 * r as f32),
+                },
                 ObjectValue::Float(r) => ObjectValue::Float(l as f32 * r),
                 _ => {
                     Reporter::arithmetic_error(&format!("{} * {}", l, rhs));
@@ -125,28 +180,71 @@ impl Div for ObjectValue {
     type Output = Self;
 
     fn div(self, rhs: Self) -> Self::Output {
-        match self {
-            ObjectValue::Number(l) => match rhs {
-                ObjectValue::Number(r) => ObjectValue::Number(l / r),
-                ObjectValue::Float(r) => ObjectValue::Float(l as f32 / r),
+        match (&self, &rhs) {
+            // `checked_rem` is `None` for `i32::MIN / -1` which overflows
+            (ObjectValue::Number(l), ObjectValue::Number(r))
+                if *r != 0 && l.checked_rem(*r) == Some(0) =>
+            {
+                ObjectValue::Number(l / r)
+            }
+            _ => match (self.as_f32(), rhs.as_f32()) {
+                (Some(l), Some(r)) if r != 0.0 => ObjectValue::Float(l / r),
                 _ => {
-                    Reporter::arithmetic_error(&format!("{} / {}", l, rhs));
+                    Reporter::arithmetic_error(&format!("{} / {}", self, rhs));
                     ObjectValue::Nil
                 }
             },
+        }
+    }
+}
 
-            ObjectValue::Float(l) => match rhs {
-                ObjectValue::Number(r) => ObjectValue::Float(l / r as f32),
-                ObjectValue::Float(r) => ObjectValue::Float(l / r),
+impl Rem for ObjectValue {
+    type Output = Self;
+
+    fn rem(self, rhs: Self) -> Self::Output {
+        match (&self, &rhs) {
+            (ObjectValue::Number(l), ObjectValue::Number(r)) if *r != 0 => {
+                // only `i32::MIN % -1` overflows and its remainder is 0
+                ObjectValue::Number(l.checked_rem(*r).unwrap_or(0))
+            }
+            _ => match (self.as_f32(), rhs.as_f32()) {
+                (Some(l), Some(r)) if r != 0.0 => ObjectValue::Float(l % r),
                 _ => {
-                    Reporter::arithmetic_error(&format!("{} / {}", l, rhs));
+                    Reporter::arithmetic_error(&format!("{} % {}", self, rhs));
                     ObjectValue::Nil
                 }
             },
-            _ => {
-                Reporter::arithmetic_error(&format!("{} / {}", self, rhs));
-                ObjectValue::Nil
+        }
+    }
+}
+
+impl ObjectValue {
+    /// returns the value as a float if it is numeric
+    fn as_f32(&self) -> Option<f32> {
+        match *self {
+            ObjectValue::Number(n) => Some(n as f32),
+            ObjectValue::Float(f) => Some(f),
+            _ => None,
+        }
+    }
+
+    /// raises the value to the power of `rhs`, an integer raised to a non negative integer stays
+    /// an integer unless it overflows
+    fn pow(self, rhs: Self) -> Self {
+        match (&self, &rhs) {
+            (ObjectValue::Number(l), ObjectValue::Number(r)) if *r >= 0 => {
+                match l.checked_pow(*r as u32) {
+                    Some(n) => ObjectValue::Number(n),
+                    None => ObjectValue::Float((*l as f32).powf(*r as f32)),
+                }
             }
+            _ => match (self.as_f32(), rhs.as_f32()) {
+                (Some(l), Some(r)) => ObjectValue::Float(l.powf(r)),
+                _ => {
+                    Reporter::arithmetic_error(&format!("{} ** {}", self, rhs));
+                    ObjectValue::Nil
+                }
+            },
         }
     }
 }
@@ -157,7 +255,10 @@ impl Add for ObjectValue {
     fn add(self, rhs: Self) -> Self::Output {
         match self {
             ObjectValue::Number(l) => match rhs {
-                ObjectValue::Number(r) => ObjectValue::Number(l + r),
+                ObjectValue::Number(r) => match l.checked_add(r) {
+                    Some(n) => ObjectValue::Number(n),
+                    None => ObjectValue::Float(l as f32 + r as f32),
+                },
                 ObjectValue::Float(r) => ObjectValue::Float(l as f32 + r),
                 ObjectValue::String(r) => ObjectValue::String(format!("{}{}", l, r)),
                 _ => {
@@ -176,6 +277,20 @@ impl Add for ObjectValue {
                 }
             },
 
+            // Lists only concatenate with lists, appending a single value is written `list + [value]`
+            ObjectValue::List(l) => match rhs {
+                ObjectValue::List(r) => {
+                    let mut elements = access(&l).clone();
+                    let right = access(&r).clone();
+                    elements.extend(right);
+                    ObjectValue::List(share(elements))
+                }
+                _ => {
+                    Reporter::arithmetic_error(&format!("{} + {}", ObjectValue::List(l), rhs));
+                    ObjectValue::Nil
+                }
+            },
+
             ObjectValue::String(l) => match rhs {
                 ObjectValue::Number(r) => ObjectValue::String(format!("{}{}", l, r)),
                 ObjectValue::Float(r) => ObjectValue::String(format!("{}{}", l, r)),
@@ -193,157 +308,183 @@ impl Add for ObjectValue {
     }
 }
 
-impl PartialOrd for ObjectValue {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+/// Values are equal when they hold the same kind of value, except for numbers which are compared
+/// by their numeric value so `1 == 1.0`. Lists and maps are compared deeply by their elements,
+/// functions are only equal to themselves.
+impl PartialEq for ObjectValue {
+    fn eq(&self, other: &Self) -> bool {
+        self.eq_nested(other, &mut Vec::new())
+    }
+}
+
+impl ObjectValue {
+    /// address of a list or map, it identifies the container while walking nested values
+    fn container(&self) -> Option<*const ()> {
         match self {
-            ObjectValue::Number(l) => match other {
-                ObjectValue::Number(r) => {
-                    if l < r {
-                        return Some(Ordering::Less);
-                    }
-                    if l == r {
-                        return Some(Ordering::Equal);
-                    }
-                    if l > r {
-                        return Some(Ordering::Greater);
-                    }
-                    None
-                }
-                ObjectValue::Float(r) => {
-                    if l < &(*r as i32) {
-                        return Some(Ordering::Less);
-                    }
-                    if l == &(*r as i32) {
-                        return Some(Ordering::Equal);
-                    }
-                    if l > &(*r as i32) {
-                        return Some(Ordering::Greater);
-                    }
-                    None
-                }
-                _ => None,
-            },
-            ObjectValue::Float(l) => match other {
-                ObjectValue::Number(r) => {
-                    if l < &(*r as f32) {
-                        return Some(Ordering::Less);
-                    }
-                    if l == &(*r as f32) {
-                        return Some(Ordering::Equal);
-                    }
-                    if l > &(*r as f32) {
-                        return Some(Ordering::Greater);
-                    }
-                    None
+            ObjectValue::List(list) => Some(Handle::as_ptr(list) as *const ()),
+            ObjectValue::Map(map) => Some(Handle::as_ptr(map) as *const ()),
+            _ => None,
+        }
+    }
+
+    /// formats the value with strings quoted or not, a list or map already in `visiting` holds
+    /// itself and is written as `[...]` or `{...}`
+    fn format_nested(&self, quote: bool, visiting: &mut Vec<*const ()>) -> String {
+        let container = self.container();
+        if let Some(container) = container {
+            if visiting.contains(&container) {
+                return match self {
+                    ObjectValue::List(_) => "[...]".to_string(),
+                    _ => "{...}".to_string(),
+                };
+            }
+            visiting.push(container);
+        }
+
+        let formatted = match self {
+            ObjectValue::String(s) if quote => format!("{:?}", s),
+            // the elements are copied out so nothing stays locked while they are formatted
+            ObjectValue::List(list) => {
+                let elements = access(list).clone();
+                let elements = elements
+                    .iter()
+                    .map(|e| e.value.format_nested(quote, visiting))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!("[{}]", elements)
+            }
+            ObjectValue::Map(map) => {
+                // entries are sorted so the output doesn't depend on the hash order
+                let mut entries: Vec<(Object, Object)> = access(map)
+                    .iter()
+                    .map(|(k, v)| (k.clone(), v.clone()))
+                    .collect();
+                entries.sort_by(|(l, _), (r, _)| l.total_cmp(r));
+                let entries = entries
+                    .iter()
+                    .map(|(k, v)| {
+                        format!(
+                            "{}: {}",
+                            k.value.format_nested(quote, visiting),
+                            v.value.format_nested(quote, visiting)
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!("{{{}}}", entries)
+            }
+            value => value.to_string(),
+        };
+
+        if container.is_some() {
+            visiting.pop();
+        }
+        formatted
+    }
+
+    /// compares two values, a pair of containers already in `comparing` is being compared
+    /// further up and is taken as equal so containers holding themselves don't recurse forever
+    fn eq_nested(&self, other: &Self, comparing: &mut Vec<(*const (), *const ())>) -> bool {
+        match (self, other) {
+            (ObjectValue::Number(l), ObjectValue::Number(r)) => l == r,
+            (ObjectValue::String(l), ObjectValue::String(r)) => l == r,
+            (ObjectValue::Bool(l), ObjectValue::Bool(r)) => l == r,
+            (ObjectValue::Function(l), ObjectValue::Function(r)) => Handle::ptr_eq(l, r),
+            (ObjectValue::NativeFunction(l), ObjectValue::NativeFunction(r)) => {
+                Handle::ptr_eq(l, r)
+            }
+            (ObjectValue::List(l), ObjectValue::List(r)) if Handle::ptr_eq(l, r) => true,
+            (ObjectValue::Map(l), ObjectValue::Map(r)) if Handle::ptr_eq(l, r) => true,
+            (ObjectValue::List(_), ObjectValue::List(_))
+            | (ObjectValue::Map(_), ObjectValue::Map(_)) => {
+                let pair = match (self.container(), other.container()) {
+                    (Some(l), Some(r)) => (l, r),
+                    _ => return false,
+                };
+                if comparing.contains(&pair) {
+                    return true;
                 }
-                ObjectValue::Float(r) => {
-                    if l < r {
-                        return Some(Ordering::Less);
-                    }
-                    if l == r {
-                        return Some(Ordering::Equal);
+
+                comparing.push(pair);
+                let equal = match (self, other) {
+                    (ObjectValue::List(l), ObjectValue::List(r)) => {
+                        let (l, r) = (access(l).clone(), access(r).clone());
+                        l.len() == r.len()
+                            && l.iter()
+                                .zip(r.iter())
+                                .all(|(l, r)| l.value.eq_nested(&r.value, comparing))
                     }
-                    if l > r {
-                        return Some(Ordering::Greater);
+                    (ObjectValue::Map(l), ObjectValue::Map(r)) => {
+                        let (l, r) = (access(l).clone(), access(r).clone());
+                        l.len() == r.len()
+                            && l.iter().all(|(key, l)| {
+                                r.get(key)
+                                    .is_some_and(|r| l.value.eq_nested(&r.value, comparing))
+                            })
                     }
-                    None
-                }
-                _ => None,
+                    _ => false,
+                };
+                comparing.pop();
+                equal
+            }
+            (ObjectValue::Nil, ObjectValue::Nil) => true,
+            _ => match (self.as_f64(), other.as_f64()) {
+                (Some(l), Some(r)) => l == r,
+                _ => false,
             },
-            _ => None,
         }
     }
+}
 
-    fn lt(&self, other: &Self) -> bool {
-        match self {
-            ObjectValue::Number(l) => match other {
-                ObjectValue::Number(r) => l < r,
-                ObjectValue::Float(r) => l < &(*r as i32),
-                _ => {
-                    Reporter::arithmetic_error(&format!("{} > {}", l, other));
-                    false
-                }
-            },
-            ObjectValue::Float(l) => match other {
-                ObjectValue::Number(r) => l < &(*r as f32),
-                ObjectValue::Float(r) => l < r,
-                _ => {
-                    Reporter::arithmetic_error(&format!("{} > {}", l, other));
-                    false
-                }
-            },
-            _ => false,
+/// Only numbers and strings are ordered, an integer and a float are compared without truncating
+/// the float and strings are compared lexicographically.
+impl PartialOrd for ObjectValue {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match (self, other) {
+            (ObjectValue::Number(l), ObjectValue::Number(r)) => Some(l.cmp(r)),
+            (ObjectValue::String(l), ObjectValue::String(r)) => Some(l.cmp(r)),
+            _ => self.as_f64()?.partial_cmp(&other.as_f64()?),
         }
     }
 
+    fn lt(&self, other: &Self) -> bool {
+        self.compare(other, "<").is_some_and(Ordering::is_lt)
+    }
+
     fn le(&self, other: &Self) -> bool {
-        match self {
-            ObjectValue::Number(l) => match other {
-                ObjectValue::Number(r) => l <= r,
-                ObjectValue::Float(r) => l <= &(*r as i32),
-                _ => {
-                    Reporter::arithmetic_error(&format!("{} > {}", l, other));
-                    false
-                }
-            },
-            ObjectValue::Float(l) => match other {
-                ObjectValue::Number(r) => l <= &(*r as f32),
-                ObjectValue::Float(r) => l <= r,
-                _ => {
-                    Reporter::arithmetic_error(&format!("{} > {}", l, other));
-                    false
-                }
-            },
-            _ => false,
-        }
+        self.compare(other, "<=").is_some_and(Ordering::is_le)
     }
 
     fn gt(&self, other: &Self) -> bool {
-        match self {
-            ObjectValue::Number(l) => match other {
-                ObjectValue::Number(r) => l > r,
-                ObjectValue::Float(r) => l > &(*r as i32),
-                _ => {
-                    Reporter::arithmetic_error(&format!("{} > {}", l, other));
-                    false
-                }
-            },
-            ObjectValue::Float(l) => match other {
-                ObjectValue::Number(r) => l > &(*r as f32),
-                ObjectValue::Float(r) => l > r,
-                _ => {
-                    Reporter::arithmetic_error(&format!("{} > {}", l, other));
-                    false
-                }
-            },
-            _ => false,
-        }
+        self.compare(other, ">").is_some_and(Ordering::is_gt)
     }
 
     fn ge(&self, other: &Self) -> bool {
-        match self {
-            ObjectValue::Number(l) => match other {
-                ObjectValue::Number(r) => l >= r,
-                ObjectValue::Float(r) => l >= &(*r as i32),
-                _ => {
-                    Reporter::arithmetic_error(&format!("{} > {}", l, other));
-                    false
-                }
-            },
-            ObjectValue::Float(l) => match other {
-                ObjectValue::Number(r) => l >= &(*r as f32),
-                ObjectValue::Float(r) => l >= r,
-                _ => {
-                    Reporter::arithmetic_error(&format!("{} > {}", l, other));
-                    false
-                }
-            },
-            _ => false,
+        self.compare(other, ">=").is_some_and(Ordering::is_ge)
+    }
+}
+
+impl ObjectValue {
+    /// returns the value as a float wide enough to hold any integer exactly
+    fn as_f64(&self) -> Option<f64> {
+        match *self {
+            ObjectValue::Number(n) => Some(n as f64),
+            ObjectValue::Float(f) => Some(f as f64),
+            _ => None,
+        }
+    }
+
+    /// compares two values, reporting an arithmetic error when a number is compared with
+    /// anything else
+    fn compare(&self, other: &Self, operator: &str) -> Option<Ordering> {
+        if self.as_f64().is_some() && other.as_f64().is_none() {
+            Reporter::arithmetic_error(&format!("{} {} {}", self, operator, other));
         }
+        self.partial_cmp(other)
     }
 }
 
-#[derive(Clone, Debug, Default, PartialEq)]
+#[derive(Clone, Debug, Default)]
 pub struct Object {
     pub kind: ObjectKind,
     pub value: ObjectValue,
@@ -367,6 +508,107 @@ impl Object {
         self.kind == kind
     }
 
+    /// returns true unless the object is `nil` or `false`
+    ///
+    /// # Example
+    /// ```
+    /// use oxa::object::Object;
+    ///
+    /// assert!(Object::from(0).is_truthy());
+    /// assert!(!Object::default().is_truthy());
+    /// ```
+    pub fn is_truthy(&self) -> bool {
+        !matches!(self.value, ObjectValue::Nil | ObjectValue::Bool(false))
+    }
+
+    /// returns the host value of a number object, `None` for any other kind
+    ///
+    /// # Example
+    /// ```
+    /// use oxa::object::Object;
+    ///
+    /// assert_eq!(Object::from(10).as_number(), Some(10));
+    /// assert_eq!(Object::from(1.5).as_number(), None);
+    /// ```
+    pub fn as_number(&self) -> Option<i32> {
+        match self.value {
+            ObjectValue::Number(n) => Some(n),
+            _ => None,
+        }
+    }
+
+    /// returns the host value of a float object, `None` for any other kind
+    pub fn as_float(&self) -> Option<f32> {
+        match self.value {
+            ObjectValue::Float(f) => Some(f),
+            _ => None,
+        }
+    }
+
+    /// borrows the content of a string object, `None` for any other kind
+    pub fn as_string(&self) -> Option<&str> {
+        match &self.value {
+            ObjectValue::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// returns the host value of a boolean object, `None` for any other kind, use
+    /// `is_truthy` for the truthiness of any object
+    pub fn as_bool(&self) -> Option<bool> {
+        match self.value {
+            ObjectValue::Bool(b) => Some(b),
+            _ => None,
+        }
+    }
+
+    /// returns `true` if the object is falsey and `false` otherwise
+    ///
+    /// # Example
+    /// ```
+    /// use oxa::object::Object;
+    ///
+    /// assert_eq!(Object::from(false).logical_not(), Object::from(true));
+    /// assert_eq!(Object::from("").logical_not(), Object::from(false));
+    /// ```
+    pub fn logical_not(&self) -> Object {
+        Object::from(!self.is_truthy())
+    }
+
+    /// returns the object if it is falsey, otherwise returns `other`. Both values are already
+    /// evaluated so nothing is short-circuited.
+    ///
+    /// # Example
+    /// ```
+    /// use oxa::object::Object;
+    ///
+    /// assert_eq!(Object::default().logical_and(&Object::from(1)), Object::default());
+    /// assert_eq!(Object::from(1).logical_and(&Object::from(2)), Object::from(2));
+    /// ```
+    pub fn logical_and(&self, other: &Object) -> Object {
+        match self.is_truthy() {
+            true => other.clone(),
+            false => self.clone(),
+        }
+    }
+
+    /// returns the object if it is truthy, otherwise returns `other`. Both values are already
+    /// evaluated so nothing is short-circuited.
+    ///
+    /// # Example
+    /// ```
+    /// use oxa::object::Object;
+    ///
+    /// assert_eq!(Object::from(1).logical_or(&Object::from(2)), Object::from(1));
+    /// assert_eq!(Object::from(false).logical_or(&Object::from("a")), Object::from("a"));
+    /// ```
+    pub fn logical_or(&self, other: &Object) -> Object {
+        match self.is_truthy() {
+            true => self.clone(),
+            false => other.clone(),
+        }
+    }
+
     /// return true if the object is empty
     ///
     /// # Rule
@@ -396,16 +638,115 @@ impl Object {
             ObjectValue::Number(_) => false,
             ObjectValue::String(s) => s.is_empty(),
             ObjectValue::Bool(_) => false,
+            ObjectValue::Function(_) => false,
+            ObjectValue::NativeFunction(_) => false,
+            ObjectValue::List(list) => access(list).is_empty(),
+            ObjectValue::Map(map) => access(map).is_empty(),
             _ => true,
         }
     }
+
+    /// compares two objects of any kind, unlike `partial_cmp` every pair of objects is ordered
+    ///
+    /// # Rule
+    /// `nil < bool < number/float < string < list < map < function`
+    ///
+    /// numbers and floats are compared by value with each other, `NaN` sorts after every other
+    /// number. Lists are compared element by element, maps by size then entries and functions
+    /// are ordered by name.
+    ///
+    /// # Example
+    /// ```
+    /// use oxa::object::Object;
+    /// use std::cmp::Ordering;
+    ///
+    /// assert_eq!(Object::default().total_cmp(&Object::from(false)), Ordering::Less);
+    /// assert_eq!(Object::from(2).total_cmp(&Object::from(1.5)), Ordering::Greater);
+    /// ```
+    pub fn total_cmp(&self, other: &Self) -> Ordering {
+        self.total_cmp_nested(other, &mut Vec::new())
+    }
+
+    /// see `total_cmp`, a pair of lists already in `comparing` is being compared further up and
+    /// is taken as equal so lists holding themselves don't recurse forever
+    fn total_cmp_nested(
+        &self,
+        other: &Self,
+        comparing: &mut Vec<(*const (), *const ())>,
+    ) -> Ordering {
+        match (&self.value, &other.value) {
+            (ObjectValue::Bool(l), ObjectValue::Bool(r)) => l.cmp(r),
+            (ObjectValue::Number(l), ObjectValue::Number(r)) => l.cmp(r),
+            (ObjectValue::Number(l), ObjectValue::Float(r)) => (*l as f64).total_cmp(&(*r as f64)),
+            (ObjectValue::Float(l), ObjectValue::Number(r)) => (*l as f64).total_cmp(&(*r as f64)),
+            (ObjectValue::Float(l), ObjectValue::Float(r)) => l.total_cmp(r),
+            (ObjectValue::String(l), ObjectValue::String(r)) => l.cmp(r),
+            (ObjectValue::List(l), ObjectValue::List(r)) => {
+                let pair = (
+                    Handle::as_ptr(l) as *const (),
+                    Handle::as_ptr(r) as *const (),
+                );
+                if Handle::ptr_eq(l, r) || comparing.contains(&pair) {
+                    return Ordering::Equal;
+                }
+
+                comparing.push(pair);
+                let (l, r) = (access(l).clone(), access(r).clone());
+                let ordering = l
+                    .iter()
+                    .zip(r.iter())
+                    .map(|(l, r)| l.total_cmp_nested(r, comparing))
+                    .find(|ordering| ordering.is_ne())
+                    .unwrap_or_else(|| l.len().cmp(&r.len()));
+                comparing.pop();
+                ordering
+            }
+            (ObjectValue::Map(l), ObjectValue::Map(r)) => {
+                let l = access(l).len();
+                let r = access(r).len();
+                l.cmp(&r)
+                    .then_with(|| self.to_string().cmp(&other.to_string()))
+            }
+            (ObjectValue::Function(_) | ObjectValue::NativeFunction(_), _)
+                if self.rank() == other.rank() =>
+            {
+                self.to_string().cmp(&other.to_string())
+            }
+            _ => self.rank().cmp(&other.rank()),
+        }
+    }
+
+    /// position of the object kind in the total order
+    fn rank(&self) -> u8 {
+        match self.value {
+            ObjectValue::Nil => 0,
+            ObjectValue::Bool(_) => 1,
+            ObjectValue::Number(_) | ObjectValue::Float(_) => 2,
+            ObjectValue::String(_) => 3,
+            ObjectValue::List(_) => 4,
+            ObjectValue::Map(_) => 5,
+            ObjectValue::Function(_) | ObjectValue::NativeFunction(_) => 6,
+        }
+    }
 }
 
 impl From<Literal> for Object {
     fn from(value: Literal) -> Self {
-        Object {
-            kind: value.clone().into(),
-            value: value.into(),
+        Object::from(value.value)
+    }
+}
+
+impl From<LiteralKind> for Object {
+    fn from(value: LiteralKind) -> Self {
+        match value {
+            LiteralKind::Number(n) => Object::from(n),
+            LiteralKind::Float(f) => Object::from(f),
+            LiteralKind::String(s) => Object {
+                kind: ObjectKind::String,
+                value: ObjectValue::String(s),
+            },
+            LiteralKind::Bool(b) => Object::from(b),
+            LiteralKind::Nil => Object::default(),
         }
     }
 }
@@ -437,6 +778,42 @@ impl From<f32> for Object {
     }
 }
 
+impl From<Function> for Object {
+    fn from(value: Function) -> Self {
+        Object {
+            kind: ObjectKind::Function,
+            value: ObjectValue::Function(Handle::new(value)),
+        }
+    }
+}
+
+impl From<NativeFunction> for Object {
+    fn from(value: NativeFunction) -> Self {
+        Object {
+            kind: ObjectKind::NativeFunction,
+            value: ObjectValue::NativeFunction(Handle::new(value)),
+        }
+    }
+}
+
+impl From<Vec<Object>> for Object {
+    fn from(value: Vec<Object>) -> Self {
+        Object {
+            kind: ObjectKind::List,
+            value: ObjectValue::List(share(value)),
+        }
+    }
+}
+
+impl From<HashMap<Object, Object>> for Object {
+    fn from(value: HashMap<Object, Object>) -> Self {
+        Object {
+            kind: ObjectKind::Map,
+            value: ObjectValue::Map(share(value)),
+        }
+    }
+}
+
 impl From<&str> for Object {
     fn from(value: &str) -> Self {
         Object {
@@ -446,6 +823,86 @@ impl From<&str> for Object {
     }
 }
 
+impl TryFrom<Object> for i32 {
+    type Error = ErrorCode;
+
+    /// converts a number object into its host value
+    ///
+    /// # Example
+    /// ```
+    /// use oxa::object::Object;
+    /// let n: i32 = Object::from(10).try_into().unwrap();
+    /// assert_eq!(n, 10);
+    /// ```
+    fn try_from(value: Object) -> std::result::Result<Self, Self::Error> {
+        match value.value {
+            ObjectValue::Number(n) => Ok(n),
+            _ => Err(conversion_error(ObjectKind::Number, &value.kind)),
+        }
+    }
+}
+
+impl TryFrom<Object> for f32 {
+    type Error = ErrorCode;
+
+    /// converts a float object into its host value
+    ///
+    /// # Example
+    /// ```
+    /// use oxa::object::Object;
+    /// let f: f32 = Object::from(2.5).try_into().unwrap();
+    /// assert_eq!(f, 2.5);
+    /// ```
+    fn try_from(value: Object) -> std::result::Result<Self, Self::Error> {
+        match value.value {
+            ObjectValue::Float(f) => Ok(f),
+            _ => Err(conversion_error(ObjectKind::Float, &value.kind)),
+        }
+    }
+}
+
+impl TryFrom<Object> for String {
+    type Error = ErrorCode;
+
+    /// converts a string object into its host value
+    ///
+    /// # Example
+    /// ```
+    /// use oxa::object::Object;
+    /// let s: String = Object::from("oxa").try_into().unwrap();
+    /// assert_eq!(s, "oxa");
+    /// ```
+    fn try_from(value: Object) -> std::result::Result<Self, Self::Error> {
+        match value.value {
+            ObjectValue::String(s) => Ok(s),
+            _ => Err(conversion_error(ObjectKind::String, &value.kind)),
+        }
+    }
+}
+
+impl TryFrom<Object> for bool {
+    type Error = ErrorCode;
+
+    /// converts a boolean object into its host value
+    ///
+    /// # Example
+    /// ```
+    /// use oxa::object::Object;
+    /// let b: bool = Object::from(true).try_into().unwrap();
+    /// assert!(b);
+    /// ```
+    fn try_from(value: Object) -> std::result::Result<Self, Self::Error> {
+        match value.value {
+            ObjectValue::Bool(b) => Ok(b),
+            _ => Err(conversion_error(ObjectKind::Bool, &value.kind)),
+        }
+    }
+}
+
+fn conversion_error(expected: ObjectKind, found: &ObjectKind) -> ErrorCode {
+    ErrorCode::ConversionError(format!("expected {}, found {}", expected, found))
+}
+
 impl Display for Object {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
         write!(f, "{}", self.value)
@@ -480,7 +937,7 @@ impl Add for Object {
     type Output = Self;
 
     /// add one object to another and returns object of the same type or `ObjectKind::Nil`
-    /// if the operation cannot succeed
+    /// if the operation cannot succeed, two lists are concatenated into a new list
     ///
     /// # Example
     /// ```
@@ -488,6 +945,9 @@ impl Add for Object {
     /// let obj_1 = Object::from(2);
     /// let obj_2 = Object::from(4);
     /// assert_eq!(obj_1 + obj_2, Object::from(6));
+    ///
+    /// let list = Object::from(vec![Object::from(1)]) + Object::from(vec![Object::from(2)]);
+    /// assert_eq!(list, Object::from(vec![Object::from(1), Object::from(2)]));
     /// ```
     fn add(self, rhs: Self) -> Self::Output {
         let value = self.value + rhs.value;
@@ -496,6 +956,10 @@ impl Add for Object {
             ObjectValue::Number(n) => Object::from(n),
             ObjectValue::Float(f) => Object::from(f),
             ObjectValue::String(s) => Object::from(s.as_ref()),
+            ObjectValue::List(l) => Object {
+                kind: ObjectKind::List,
+                value: ObjectValue::List(l),
+            },
             _ => Object::default(),
         }
     }
@@ -549,38 +1013,236 @@ impl Div for Object {
     }
 }
 
+impl Rem for Object {
+    type Output = Self;
+
+    /// returns the remainder of dividing one object by another, or `ObjectKind::Nil` if the
+    /// operation cannot succeed or the divisor is zero
+    ///
+    /// # Example
+    /// ```
+    /// use oxa::object::Object;
+    /// assert_eq!(Object::from(10) % Object::from(3), Object::from(1));
+    /// assert_eq!(Object::from(10) % Object::from(0), Object::default());
+    /// ```
+    fn rem(self, rhs: Self) -> Self::Output {
+        let value = self.value % rhs.value;
+
+        match value {
+            ObjectValue::Number(n) => Object::from(n),
+            ObjectValue::Float(f) => Object::from(f),
+            _ => Object::default(),
+        }
+    }
+}
+
+impl Object {
+    /// raises one object to the power of another and returns `ObjectKind::Nil` if the operation
+    /// cannot succeed
+    ///
+    /// # Example
+    /// ```
+    /// use oxa::object::Object;
+    /// assert_eq!(Object::from(3).pow(Object::from(2)), Object::from(9));
+    /// assert_eq!(Object::from(4).pow(Object::from(0.5)), Object::from(2.0));
+    /// ```
+    pub fn pow(self, rhs: Self) -> Self {
+        match self.value.pow(rhs.value) {
+            ObjectValue::Number(n) => Object::from(n),
+            ObjectValue::Float(f) => Object::from(f),
+            _ => Object::default(),
+        }
+    }
+}
+
+impl Object {
+    /// returns the structural form of the object, unlike `to_string` strings are quoted so
+    /// `"1"` and `1` print differently, inside lists and maps too
+    ///
+    /// # Example
+    /// ```
+    /// use oxa::object::Object;
+    /// assert_eq!(Object::from("x").repr(), "\"x\"");
+    /// assert_eq!(Object::from(vec![Object::from(1), Object::from("1")]).repr(), "[1, \"1\"]");
+    /// ```
+    pub fn repr(&self) -> String {
+        self.value.format_nested(true, &mut Vec::new())
+    }
+
+    /// writes a scalar object in a compact form which `Object::deserialize` reads back
+    ///
+    /// # Format
+    /// `n:5`, `f:2.5`, `s:"abc"`, `b:true`, `nil`
+    ///
+    /// quotes and backslashes inside strings are escaped with a backslash. Functions, lists and
+    /// maps have no persistent form and are written as `nil`.
+    ///
+    /// # Example
+    /// ```
+    /// use oxa::object::Object;
+    /// assert_eq!(Object::from(5).serialize(), "n:5");
+    /// assert_eq!(Object::from("a\"b").serialize(), r#"s:"a\"b""#);
+    /// ```
+    pub fn serialize(&self) -> String {
+        match &self.value {
+            ObjectValue::Number(n) => format!("n:{}", n),
+            ObjectValue::Float(f) => format!("f:{:?}", f),
+            ObjectValue::String(s) => {
+                format!("s:\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+            }
+            ObjectValue::Bool(b) => format!("b:{}", b),
+            _ => "nil".to_string(),
+        }
+    }
+
+    /// reads an object written by `Object::serialize`
+    ///
+    /// # Example
+    /// ```
+    /// use oxa::object::Object;
+    /// assert_eq!(Object::deserialize("f:2.5").unwrap(), Object::from(2.5));
+    /// assert!(Object::deserialize("n:five").is_err());
+    /// ```
+    pub fn deserialize(source: &str) -> std::result::Result<Object, ErrorCode> {
+        let malformed = || ErrorCode::ConversionError(format!("malformed object '{}'", source));
+
+        if source == "nil" {
+            return Ok(Object::default());
+        }
+
+        let (tag, value) = source.split_once(':').ok_or_else(malformed)?;
+        match tag {
+            "n" => value
+                .parse::<i32>()
+                .map(Object::from)
+                .map_err(|_| malformed()),
+            "f" => value
+                .parse::<f32>()
+                .map(Object::from)
+                .map_err(|_| malformed()),
+            "b" => value
+                .parse::<bool>()
+                .map(Object::from)
+                .map_err(|_| malformed()),
+            "s" => {
+                let inner = value
+                    .strip_prefix('"')
+                    .and_then(|value| value.strip_suffix('"'))
+                    .ok_or_else(malformed)?;
+
+                let mut string = String::with_capacity(inner.len());
+                let mut chars = inner.chars();
+                while let Some(c) = chars.next() {
+                    match c {
+                        '\\' => match chars.next() {
+                            Some(escaped @ ('\\' | '"')) => string.push(escaped),
+                            _ => return Err(malformed()),
+                        },
+                        '"' => return Err(malformed()),
+                        c => string.push(c),
+                    }
+                }
+                Ok(Object::from(string.as_str()))
+            }
+            _ => Err(malformed()),
+        }
+    }
+}
+
+/// Objects are compared by value, see `ObjectValue::eq`
+impl PartialEq for Object {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+/// Objects are compared by value, a `NaN` float is never equal to itself so the interpreter
+/// rejects it as a map key.
+impl Eq for Object {}
+
+/// Hashes the value of the object consistently with its equality, so any object can be used as a
+/// map key. Mutating a list used as a key changes its hash and the entry can't be found anymore.
+impl Hash for Object {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.hash_nested(state, true)
+    }
+}
+
+impl Object {
+    /// hashes the object, the elements of a list are hashed with `deep` unset so a nested list
+    /// only adds its size and a list holding itself is hashed in one step
+    fn hash_nested<H: Hasher>(&self, state: &mut H, deep: bool) {
+        // integers and floats share a hash since `1 == 1.0`
+        match &self.value {
+            ObjectValue::Number(_) | ObjectValue::Float(_) => {
+                std::mem::discriminant(&ObjectValue::Number(0)).hash(state)
+            }
+            value => std::mem::discriminant(value).hash(state),
+        }
+
+        match &self.value {
+            ObjectValue::Number(n) => (*n as f64).to_bits().hash(state),
+            // 0.0 and -0.0 are equal so they need the same hash
+            ObjectValue::Float(f) if *f == 0.0 => 0.0f64.to_bits().hash(state),
+            ObjectValue::Float(f) => (*f as f64).to_bits().hash(state),
+            ObjectValue::String(s) => s.hash(state),
+            ObjectValue::Bool(b) => b.hash(state),
+            ObjectValue::Function(fun) => Handle::as_ptr(fun).hash(state),
+            ObjectValue::NativeFunction(fun) => Handle::as_ptr(fun).hash(state),
+            ObjectValue::List(list) if deep => {
+                let list = access(list).clone();
+                list.len().hash(state);
+                for element in list.iter() {
+                    element.hash_nested(state, false);
+                }
+            }
+            ObjectValue::List(list) => access(list).len().hash(state),
+            // maps only hash their kind, reading the entries would borrow a map being set with
+            // itself as the key and its size changes once it holds itself
+            ObjectValue::Map(_) => {}
+            ObjectValue::Nil => {}
+        }
+    }
+}
+
 impl PartialOrd for Object {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         match self.kind {
-            ObjectKind::Number | ObjectKind::Float => self.value.partial_cmp(&other.value),
+            ObjectKind::Number | ObjectKind::Float | ObjectKind::String => {
+                self.value.partial_cmp(&other.value)
+            }
             _ => None,
         }
     }
 
     fn lt(&self, other: &Self) -> bool {
         match self.kind {
-            ObjectKind::Number | ObjectKind::Float => self.value < other.value,
+            ObjectKind::Number | ObjectKind::Float | ObjectKind::String => self.value < other.value,
             _ => false,
         }
     }
 
     fn le(&self, other: &Self) -> bool {
         match self.kind {
-            ObjectKind::Number | ObjectKind::Float => self.value <= other.value,
+            ObjectKind::Number | ObjectKind::Float | ObjectKind::String => {
+                self.value <= other.value
+            }
             _ => false,
         }
     }
 
     fn gt(&self, other: &Self) -> bool {
         match self.kind {
-            ObjectKind::Number | ObjectKind::Float => self.value > other.value,
+            ObjectKind::Number | ObjectKind::Float | ObjectKind::String => self.value > other.value,
             _ => false,
         }
     }
 
     fn ge(&self, other: &Self) -> bool {
         match self.kind {
-            ObjectKind::Number | ObjectKind::Float => self.value >= other.value,
+            ObjectKind::Number | ObjectKind::Float | ObjectKind::String => {
+                self.value >= other.value
+            }
             _ => false,
         }
     }
@@ -590,6 +1252,297 @@ impl PartialOrd for Object {
 mod object_tests {
     use super::*;
 
+    fn list(elements: &[Object]) -> Object {
+        Object::from(elements.to_vec())
+    }
+
+    fn map(entries: &[(Object, Object)]) -> Object {
+        Object::from(entries.iter().cloned().collect::<HashMap<_, _>>())
+    }
+
+    type Operator = fn(Object, Object) -> Object;
+
+    #[test]
+    fn numeric_promotion_table() {
+        let operators: [(&str, Operator); 6] = [
+            ("+", |l, r| l + r),
+            ("-", |l, r| l - r),
+            ("*", |l, r| l * r),
+            ("/", |l, r| l / r),
+            ("%", |l, r| l % r),
+            ("**", |l, r| l.pow(r)),
+        ];
+        let operands = [
+            (Object::from(6), Object::from(2), ObjectKind::Number),
+            (Object::from(6), Object::from(2.0), ObjectKind::Float),
+            (Object::from(6.0), Object::from(2), ObjectKind::Float),
+            (Object::from(6.0), Object::from(2.0), ObjectKind::Float),
+        ];
+
+        for (name, operator) in operators {
+            for (lhs, rhs, kind) in &operands {
+                let result = operator(lhs.clone(), rhs.clone());
+                assert_eq!(&result.kind, kind, "{} {} {}", lhs, name, rhs);
+            }
+        }
+
+        let promoted = [
+            (Object::from(7) / Object::from(2), Object::from(3.5)),
+            (
+                Object::from(i32::MAX) + Object::from(1),
+                Object::from(2147483648.0),
+            ),
+            (
+                Object::from(i32::MIN) - Object::from(1),
+                Object::from(-2147483649.0),
+            ),
+            (
+                Object::from(i32::MAX) * Object::from(2),
+                Object::from(4294967294.0),
+            ),
+            (
+                Object::from(i32::MIN) / Object::from(-1),
+                Object::from(2147483648.0),
+            ),
+            (Object::from(2).pow(Object::from(-1)), Object::from(0.5)),
+        ];
+
+        for (result, expected) in promoted {
+            assert_eq!(result.kind, ObjectKind::Float);
+            assert_eq!(result, expected);
+        }
+    }
+
+    #[test]
+    fn logical_operations() {
+        let falsey = [Object::default(), Object::from(false)];
+        let truthy = [
+            Object::from(true),
+            Object::from(0),
+            Object::from(0.0),
+            Object::from(""),
+            Object::from(Vec::new()),
+        ];
+
+        for value in &falsey {
+            assert_eq!(value.logical_not(), Object::from(true));
+            for other in truthy.iter().chain(falsey.iter()) {
+                assert_eq!(value.logical_and(other), *value);
+                assert_eq!(value.logical_or(other), *other);
+            }
+        }
+
+        for value in &truthy {
+            assert_eq!(value.logical_not(), Object::from(false));
+            for other in truthy.iter().chain(falsey.iter()) {
+                assert_eq!(value.logical_and(other), *other);
+                assert_eq!(value.logical_or(other), *value);
+            }
+        }
+    }
+
+    #[test]
+    fn equality_matrix() {
+        let native = Object::from(NativeFunction::new(
+            "f",
+            crate::callable::Arity::Exact(0),
+            |_, _, _| Ok(Object::default()),
+        ));
+
+        let data = vec![
+            // numbers, across integers and floats
+            (Object::from(1), Object::from(1), true),
+            (Object::from(1), Object::from(2), false),
+            (Object::from(1), Object::from(1.0), true),
+            (Object::from(1.0), Object::from(1), true),
+            (Object::from(1), Object::from(1.5), false),
+            (Object::from(1.5), Object::from(1.5), true),
+            (Object::from(0), Object::from(-0.0), true),
+            (Object::from(0.0), Object::from(-0.0), true),
+            (Object::from(f32::NAN), Object::from(f32::NAN), false),
+            (Object::from(16_777_217), Object::from(16_777_216.0), false),
+            // strings
+            (Object::from("a"), Object::from("a"), true),
+            (Object::from("a"), Object::from("b"), false),
+            (Object::from("1"), Object::from(1), false),
+            (Object::from(""), Object::default(), false),
+            // bools
+            (Object::from(true), Object::from(true), true),
+            (Object::from(true), Object::from(false), false),
+            (Object::from(true), Object::from(1), false),
+            (Object::from(false), Object::from(0), false),
+            (Object::from(false), Object::default(), false),
+            // nil
+            (Object::default(), Object::default(), true),
+            (Object::default(), Object::from(0), false),
+            // lists are compared element by element
+            (list(&[]), list(&[]), true),
+            (list(&[Object::from(1)]), list(&[Object::from(1.0)]), true),
+            (
+                list(&[Object::from(1)]),
+                list(&[Object::from(1), Object::from(2)]),
+                false,
+            ),
+            (
+                list(&[list(&[Object::from("a")])]),
+                list(&[list(&[Object::from("a")])]),
+                true,
+            ),
+            (list(&[]), Object::default(), false),
+            (list(&[]), map(&[]), false),
+            // maps are compared by their entries
+            (map(&[]), map(&[]), true),
+            (
+                map(&[(Object::from("a"), Object::from(1))]),
+                map(&[(Object::from("a"), Object::from(1.0))]),
+                true,
+            ),
+            (
+                map(&[(Object::from(1), Object::from("a"))]),
+                map(&[(Object::from(1.0), Object::from("a"))]),
+                true,
+            ),
+            (
+                map(&[(Object::from("a"), Object::from(1))]),
+                map(&[(Object::from("b"), Object::from(1))]),
+                false,
+            ),
+            // functions are only equal to themselves
+            (native.clone(), native.clone(), true),
+            (native.clone(), Object::default(), false),
+        ];
+
+        for (lhs, rhs, expected) in data {
+            assert_eq!(lhs == rhs, expected, "{:?} == {:?}", lhs, rhs);
+            assert_eq!(rhs == lhs, expected, "{:?} == {:?}", rhs, lhs);
+        }
+    }
+
+    #[test]
+    #[allow(clippy::mutable_key_type)]
+    fn equal_numbers_hash_the_same() {
+        let mut numbers = HashMap::new();
+        numbers.insert(Object::from(1), Object::from("one"));
+        numbers.insert(Object::from(0.0), Object::from("zero"));
+
+        assert_eq!(numbers.get(&Object::from(1.0)), Some(&Object::from("one")));
+        assert_eq!(numbers.get(&Object::from(-0)), Some(&Object::from("zero")));
+    }
+
+    #[test]
+    #[allow(clippy::mutable_key_type)]
+    fn self_containing_containers_are_walked_once() {
+        let self_list = || {
+            let object = list(&[Object::from(1)]);
+            if let ObjectValue::List(elements) = &object.value {
+                access(elements)[0] = object.clone();
+            }
+            object
+        };
+        let (a, b) = (self_list(), self_list());
+
+        let m = map(&[]);
+        if let ObjectValue::Map(entries) = &m.value {
+            access(entries).insert(Object::from("self"), m.clone());
+        }
+
+        assert_eq!(a.to_string(), "[[...]]");
+        assert_eq!(a.repr(), "[[...]]");
+        assert_eq!(m.to_string(), "{self: {...}}");
+        assert_eq!(m.repr(), "{\"self\": {...}}");
+        assert_eq!(a, b);
+        assert_ne!(a, list(&[list(&[])]));
+        assert_eq!(a.total_cmp(&b), Ordering::Equal);
+        assert_eq!(m, m.clone());
+
+        let mut keys = HashMap::new();
+        keys.insert(a, Object::from(true));
+        assert_eq!(keys.get(&b), Some(&Object::from(true)));
+
+        // the containers hold themselves, break the cycles so they are freed
+        for container in [b, m] {
+            match &container.value {
+                ObjectValue::List(elements) => access(elements).clear(),
+                ObjectValue::Map(entries) => access(entries).clear(),
+                _ => {}
+            }
+        }
+    }
+
+    #[test]
+    fn compare_strings() {
+        assert!(Object::from("apple") < Object::from("banana"));
+        assert!(Object::from("b") > Object::from("abc"));
+        assert!(Object::from("a") <= Object::from("a"));
+        assert_eq!(Object::from("a").partial_cmp(&Object::from(1)), None);
+        assert!(!Object::from("a").lt(&Object::from(1)));
+    }
+
+    #[test]
+    fn compare_integer_with_float() {
+        assert!(Object::from(1) < Object::from(1.5));
+        assert!(Object::from(2) > Object::from(1.5));
+        assert!(Object::from(1) <= Object::from(1.0));
+        assert!(Object::from(1.5) >= Object::from(1));
+    }
+
+    #[test]
+    fn total_order_across_kinds() {
+        assert_eq!(
+            Object::default().total_cmp(&Object::from(false)),
+            Ordering::Less
+        );
+        assert_eq!(
+            Object::from(false).total_cmp(&Object::from(0)),
+            Ordering::Less
+        );
+        assert_eq!(
+            Object::from(1).total_cmp(&Object::from("a")),
+            Ordering::Less
+        );
+        assert_eq!(
+            Object::from(1).total_cmp(&Object::from(1.0)),
+            Ordering::Equal
+        );
+        assert_eq!(
+            Object::from(f32::NAN).total_cmp(&Object::from(i32::MAX)),
+            Ordering::Greater
+        );
+    }
+
+    #[test]
+    fn convert_literal_kind() {
+        let data = vec![
+            (
+                LiteralKind::Number(10),
+                Object::from(10),
+                ObjectKind::Number,
+            ),
+            (
+                LiteralKind::Float(1.5),
+                Object::from(1.5),
+                ObjectKind::Float,
+            ),
+            (
+                LiteralKind::String("text".to_string()),
+                Object::from("text"),
+                ObjectKind::String,
+            ),
+            (
+                LiteralKind::Bool(true),
+                Object::from(true),
+                ObjectKind::Bool,
+            ),
+            (LiteralKind::Nil, Object::default(), ObjectKind::Nil),
+        ];
+
+        for (literal, expected, kind) in data {
+            let object = Object::from(literal);
+            assert_eq!(object.kind, kind);
+            assert_eq!(object, expected);
+        }
+    }
+
     #[test]
     fn has_correct_object_kind() {
         let data = vec![
@@ -705,6 +1658,31 @@ mod object_tests {
         assert_ne!(obj_3, obj_4);
     }
 
+    #[test]
+    fn convert_matching_object_to_host_value() {
+        assert_eq!(i32::try_from(Object::from(10)).unwrap(), 10);
+        assert_eq!(f32::try_from(Object::from(10.5)).unwrap(), 10.5);
+        assert_eq!(String::try_from(Object::from("string")).unwrap(), "string");
+        assert!(bool::try_from(Object::from(true)).unwrap());
+    }
+
+    #[test]
+    fn error_converting_mismatched_object() {
+        assert!(i32::try_from(Object::from("10")).is_err());
+        assert!(i32::try_from(Object::from(10.0)).is_err());
+        assert!(f32::try_from(Object::from(10)).is_err());
+        assert!(String::try_from(Object::from(false)).is_err());
+        assert!(bool::try_from(Object::default()).is_err());
+
+        let result: std::result::Result<i32, ErrorCode> = Object::from("10").try_into();
+        match result {
+            Err(ErrorCode::ConversionError(message)) => {
+                assert_eq!(message, "expected number, found string")
+            }
+            _ => panic!("expected a conversion error"),
+        }
+    }
+
     #[test]
     fn check_numeric_type_points() {
         let obj_1 = Object::from(1);
@@ -713,4 +1691,148 @@ mod object_tests {
         let obj_2 = Object::from(-1);
         assert_eq!(obj_2.to_string(), "-1");
     }
+
+    #[test]
+    fn serialize_round_trips_scalars() {
+        let objects = [
+            Object::from(5),
+            Object::from(-12),
+            Object::from(2.5),
+            Object::from(1e-7),
+            Object::from("abc"),
+            Object::from("say \"hi\" \\ bye\n"),
+            Object::from(""),
+            Object::from(true),
+            Object::from(false),
+            Object::default(),
+        ];
+
+        for object in objects {
+            let serialized = object.serialize();
+            let deserialized = Object::deserialize(&serialized).unwrap();
+            assert_eq!(deserialized, object, "{}", serialized);
+            assert_eq!(deserialized.kind, object.kind, "{}", serialized);
+        }
+
+        assert_eq!(Object::from(5).serialize(), "n:5");
+        assert_eq!(Object::from(2.5).serialize(), "f:2.5");
+        assert_eq!(Object::from("abc").serialize(), "s:\"abc\"");
+        assert_eq!(Object::from(true).serialize(), "b:true");
+        assert_eq!(Object::default().serialize(), "nil");
+    }
+
+    #[test]
+    fn error_deserializing_malformed_input() {
+        let sources = [
+            "",
+            "5",
+            "n:",
+            "n:5.5",
+            "n:99999999999",
+            "f:abc",
+            "b:yes",
+            "s:abc",
+            "s:\"abc",
+            "s:\"a\"b\"",
+            "s:\"a\\\"",
+            "s:\"\\x\"",
+            "x:1",
+            "nil ",
+            "Nil",
+        ];
+
+        for source in sources {
+            assert!(
+                matches!(
+                    Object::deserialize(source),
+                    Err(ErrorCode::ConversionError(_))
+                ),
+                "{}",
+                source
+            );
+        }
+    }
+
+    #[test]
+    fn clone_every_value_variant() {
+        let native = NativeFunction::new("f", crate::callable::Arity::Exact(0), |_, _, _| {
+            Ok(Object::default())
+        });
+        let values = [
+            ObjectValue::Number(1),
+            ObjectValue::Float(1.5),
+            ObjectValue::String("text".to_string()),
+            ObjectValue::Bool(true),
+            ObjectValue::NativeFunction(Handle::new(native)),
+            list(&[Object::from(1), Object::from("a")]).value,
+            map(&[(Object::from("k"), Object::from(2))]).value,
+            ObjectValue::Nil,
+        ];
+
+        for value in values {
+            assert_eq!(value.clone(), value);
+        }
+    }
+
+    #[test]
+    fn cloned_list_aliases_the_elements() {
+        let original = list(&[Object::from(1)]);
+        let cloned = original.clone();
+
+        if let ObjectValue::List(elements) = &cloned.value {
+            access(elements).push(Object::from(2));
+        }
+        assert_eq!(original, list(&[Object::from(1), Object::from(2)]));
+    }
+
+    #[test]
+    fn divide_numbers() {
+        let half = Object::from(7) / Object::from(2);
+        assert!(half.is_kind(ObjectKind::Float));
+        assert_eq!(half, Object::from(3.5));
+
+        let exact = Object::from(8) / Object::from(2);
+        assert!(exact.is_kind(ObjectKind::Number));
+        assert_eq!(exact, Object::from(4));
+
+        assert_eq!(
+            Object::from(i32::MIN) / Object::from(-1),
+            Object::from(2147483648.0)
+        );
+    }
+
+    #[test]
+    fn error_dividing_by_zero() {
+        assert!((Object::from(1) / Object::from(0)).is_kind(ObjectKind::Nil));
+        assert!((Object::from(1.5) / Object::from(0)).is_kind(ObjectKind::Nil));
+        assert!((Object::from(1) / Object::from(-0.0)).is_kind(ObjectKind::Nil));
+    }
+
+    #[test]
+    fn accessors_match_only_their_kind() {
+        let number = Object::from(3);
+        let float = Object::from(2.5);
+        let string = Object::from("oxa");
+        let boolean = Object::from(false);
+        let nil = Object::default();
+
+        assert_eq!(number.as_number(), Some(3));
+        assert_eq!(float.as_number(), None);
+        assert_eq!(float.as_float(), Some(2.5));
+        assert_eq!(number.as_float(), None);
+        assert_eq!(string.as_string(), Some("oxa"));
+        assert_eq!(number.as_string(), None);
+        assert_eq!(boolean.as_bool(), Some(false));
+        assert_eq!(nil.as_bool(), None);
+        assert_eq!(string.as_bool(), None);
+    }
+
+    #[test]
+    fn truthiness() {
+        assert!(Object::from(0).is_truthy());
+        assert!(Object::from("").is_truthy());
+        assert!(Object::from(true).is_truthy());
+        assert!(!Object::from(false).is_truthy());
+        assert!(!Object::default().is_truthy());
+    }
 }
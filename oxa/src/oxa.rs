@@ -1,20 +1,44 @@
+use crate::ast::printer::AstPrinter;
+use crate::ast::stmt::StmtKind;
+use crate::errors::reporter::Reporter;
 use crate::errors::ErrorCode;
-use crate::interpreter::{Interpreter, InterpreterBuilder};
-use crate::parser::Parser;
+use crate::interpreter::{Interpreter, InterpreterBuilder, ResultObject};
+use crate::object::{Object, ObjectKind};
+use crate::parser::{InnerStmtType, Parser};
 use crate::scanner::ScannerBuilder;
+use crate::token::{Token, TokenKind};
 use std::cell::RefCell;
 
 use std::fs;
+use std::io::{BufRead, Write};
 use std::rc::Rc;
 
+/// Stack size to run the interpreter with. Every nested call takes around 10 KiB of stack in a
+/// debug build, an 8 MiB main thread overflows before the default call depth is reached
+pub const STACK_SIZE: usize = 64 * 1024 * 1024;
+
+type Statement = InnerStmtType<ResultObject, Interpreter, Interpreter>;
+
 pub struct OxaBuilder {
     interpreter: Rc<RefCell<Interpreter>>,
+    auto_semicolon: bool,
+    single_quote_strings: bool,
+    intern_identifiers: bool,
+    keep_comments: bool,
+    dump_tokens: bool,
+    dump_ast: bool,
 }
 
 impl Default for OxaBuilder {
     fn default() -> Self {
         OxaBuilder {
             interpreter: Rc::new(RefCell::new(InterpreterBuilder::new().build())),
+            auto_semicolon: false,
+            single_quote_strings: true,
+            intern_identifiers: false,
+            keep_comments: false,
+            dump_tokens: false,
+            dump_ast: false,
         }
     }
 }
@@ -25,8 +49,51 @@ impl OxaBuilder {
         self
     }
 
+    /// Treat line breaks as statement terminators, see `ScannerBuilder::auto_semicolon`
+    pub fn auto_semicolon(mut self, enabled: bool) -> Self {
+        self.auto_semicolon = enabled;
+        self
+    }
+
+    /// Accept `'...'` string literals, see `ScannerBuilder::single_quote_strings`
+    pub fn single_quote_strings(mut self, enabled: bool) -> Self {
+        self.single_quote_strings = enabled;
+        self
+    }
+
+    /// Share the lexemes of repeated identifiers, see `ScannerBuilder::intern_identifiers`
+    pub fn intern_identifiers(mut self, enabled: bool) -> Self {
+        self.intern_identifiers = enabled;
+        self
+    }
+
+    /// Attach comments to the declarations below them, see `ScannerBuilder::keep_comments`
+    pub fn keep_comments(mut self, enabled: bool) -> Self {
+        self.keep_comments = enabled;
+        self
+    }
+
+    /// Print the tokens of a source instead of running it
+    pub fn dump_tokens(mut self, enabled: bool) -> Self {
+        self.dump_tokens = enabled;
+        self
+    }
+
+    /// Print the parenthesized AST of a source instead of running it
+    pub fn dump_ast(mut self, enabled: bool) -> Self {
+        self.dump_ast = enabled;
+        self
+    }
+
     pub fn build(self) -> Oxa {
-        Oxa::new(self.interpreter)
+        let mut oxa = Oxa::new(self.interpreter);
+        oxa.auto_semicolon = self.auto_semicolon;
+        oxa.single_quote_strings = self.single_quote_strings;
+        oxa.intern_identifiers = self.intern_identifiers;
+        oxa.keep_comments = self.keep_comments;
+        oxa.dump_tokens = self.dump_tokens;
+        oxa.dump_ast = self.dump_ast;
+        oxa
     }
 }
 
@@ -35,6 +102,12 @@ pub struct Oxa {
     pub error: bool,
     pub runtime_error: bool,
     interpreter: Rc<RefCell<Interpreter>>,
+    auto_semicolon: bool,
+    single_quote_strings: bool,
+    intern_identifiers: bool,
+    keep_comments: bool,
+    dump_tokens: bool,
+    dump_ast: bool,
 }
 
 impl Oxa {
@@ -43,6 +116,12 @@ impl Oxa {
             error: false,
             runtime_error: false,
             interpreter,
+            auto_semicolon: false,
+            single_quote_strings: true,
+            intern_identifiers: false,
+            keep_comments: false,
+            dump_tokens: false,
+            dump_ast: false,
         }
     }
 
@@ -58,8 +137,18 @@ impl Oxa {
         let file = fs::read_to_string(file_path);
         match file {
             Ok(result) => {
-                self.run(&result)?;
-                Ok(())
+                Reporter::set_source(Some(file_path));
+                Reporter::reset_counts();
+                let result = self.run(&result);
+                Reporter::flush();
+                Reporter::set_source(None);
+                // a runtime error doesn't stop the file but still fails the run, warnings don't
+                match result {
+                    Ok(()) if Reporter::had_error() => {
+                        Err(ErrorCode::RuntimeErrors(Reporter::error_count()))
+                    }
+                    result => result,
+                }
             }
             Err(e) => {
                 log::error!("Unable to read file");
@@ -70,34 +159,321 @@ impl Oxa {
 
     pub fn run_prompt(&mut self) -> Result<(), ErrorCode> {
         log::info!("Reading input from prompt");
-        let mut input = String::new();
-        match std::io::stdin().read_line(&mut input) {
-            Ok(_) => {
-                self.run(&input)?;
-                Ok(())
+        self.run_lines(std::io::stdin().lock())
+    }
+
+    /// Runs every line read from `input` until the end of the input or a `quit`/`exit` line.
+    /// All lines share the same interpreter so a variable declared on a line is visible on the
+    /// following ones, an error only aborts the line it was found on.
+    pub fn run_lines<R: BufRead>(&mut self, mut input: R) -> Result<(), ErrorCode> {
+        loop {
+            print!("> ");
+            std::io::stdout().flush().map_err(ErrorCode::IO)?;
+
+            let mut line = String::new();
+            match input.read_line(&mut line) {
+                Ok(0) => return Ok(()),
+                Ok(_) => {}
+                Err(e) => {
+                    log::error!("Unable to get user input from the cli");
+                    return Err(ErrorCode::IO(e));
+                }
             }
-            Err(e) => {
-                log::error!("Unable to get user input from the cli");
-                Err(ErrorCode::IO(e))
+
+            match line.trim() {
+                "" => continue,
+                "quit" | "exit" => return Ok(()),
+                _ => {}
+            }
+
+            // errors are reported already, the prompt carries on with the next line. The interpreter
+            // reports a runtime error in a statement before the last one and goes on so the
+            // reported errors are counted as well
+            let errors = Reporter::error_count();
+            let result = self.execute(&line);
+            if Reporter::error_count() > errors {
+                self.error = true;
+            }
+            Reporter::flush();
+            match result {
+                Ok(Some(value)) if !value.is_kind(ObjectKind::Nil) => {
+                    let mut interpreter = self.interpreter.borrow_mut();
+                    writeln!(interpreter.output(), "{}", value).map_err(ErrorCode::IO)?;
+                }
+                Ok(_) => {}
+                Err(_) => self.error = true,
             }
         }
     }
+
+    /// Scans, parses and interprets the source and returns the value of every statement instead
+    /// of printing anything besides the program's own output. Diagnostics stay collected by the
+    /// `Reporter` until flushed.
+    ///
+    /// ```
+    /// use oxa::{object::Object, oxa::Oxa};
+    ///
+    /// let mut oxa = Oxa::builder().build();
+    /// let values = oxa.eval("1 + 2;").unwrap();
+    ///
+    /// assert_eq!(values, vec![Object::from(3)]);
+    /// ```
+    pub fn eval(&mut self, source: &str) -> Result<Vec<Object>, ErrorCode> {
+        let statements = self.parse(source)?;
+        self.interpreter.borrow_mut().interpret(&statements)
+    }
+
+    /// Runs the source and returns everything the program printed instead of writing it out
+    pub fn run_capture(&mut self, s: &str) -> Result<String, ErrorCode> {
+        let captured = Capture::default();
+        let previous = self
+            .interpreter
+            .borrow_mut()
+            .replace_output(Box::new(captured.clone()));
+
+        let result = self.eval(s);
+        Reporter::flush();
+        self.interpreter.borrow_mut().replace_output(previous);
+        result?;
+
+        let output = captured.0.borrow();
+        Ok(String::from_utf8_lossy(&output).into_owned())
+    }
 }
 
 /// private methods
 impl Oxa {
-    fn run(&self, s: &str) -> Result<(), ErrorCode> {
-        let mut scanner = ScannerBuilder::default().source(s).build();
+    /// runs the source, only `print` statements and natives writing out produce any output
+    fn run(&mut self, s: &str) -> Result<(), ErrorCode> {
+        if self.dump_tokens {
+            return self.print_tokens(s);
+        }
+        if self.dump_ast {
+            return self.print_ast(s);
+        }
 
-        let tokens = scanner.scan_tokens()?;
+        self.eval(s)?;
+        Ok(())
+    }
+
+    /// prints one token per line prefixed with its line, identifiers are followed by their name
+    fn print_tokens(&self, s: &str) -> Result<(), ErrorCode> {
+        let tokens = self.scan(s)?;
+        let mut interpreter = self.interpreter.borrow_mut();
+        let output = interpreter.output();
+
+        for token in tokens {
+            let result = match token.kind {
+                TokenKind::Identifier => {
+                    writeln!(output, "{} {} {}", token.line, token, token.lexeme)
+                }
+                _ => writeln!(output, "{} {}", token.line, token),
+            };
+            result.map_err(ErrorCode::IO)?;
+        }
+        Ok(())
+    }
+
+    /// scans, parses and interprets the source, returns the value of the last statement when it
+    /// is an expression statement so the prompt can echo it
+    fn execute(&self, s: &str) -> Result<Option<Object>, ErrorCode> {
+        let statements = self.parse(s)?;
+        let mut interpreter = self.interpreter.borrow_mut();
+        match statements.split_last() {
+            Some((last, rest)) if matches!(last.kind(), StmtKind::Expression(_)) => {
+                interpreter.interpret(rest)?;
+                match interpreter.execute(last.as_ref()) {
+                    Ok(value) => Ok(Some(value)),
+                    Err(e) => {
+                        Reporter::runtime_error(&e);
+                        Err(e)
+                    }
+                }
+            }
+            _ => {
+                interpreter.interpret(&statements)?;
+                Ok(None)
+            }
+        }
+    }
+
+    /// prints every statement with the `AstPrinter`, one per line
+    fn print_ast(&self, s: &str) -> Result<(), ErrorCode> {
+        let tokens = self.scan(s)?;
+        let statements = Parser::from_tokens(&tokens).parse::<String, AstPrinter, AstPrinter>()?;
+        let mut printer = AstPrinter {};
+        let mut interpreter = self.interpreter.borrow_mut();
+
+        for statement in statements {
+            writeln!(
+                interpreter.output(),
+                "{}",
+                printer.print_stmt(statement.as_ref())
+            )
+            .map_err(ErrorCode::IO)?;
+        }
+        Ok(())
+    }
+
+    /// scans and parses the source, scanner errors stop before the parser runs
+    fn parse(&self, s: &str) -> Result<Vec<Statement>, ErrorCode> {
+        let tokens = self.scan(s)?;
         let mut parser = Parser::from_tokens(&tokens);
-        let expression = parser.parse()?;
-        let result = self
-            .interpreter
-            .borrow_mut()
-            .interpret(expression.as_ref())?;
+        parser.parse()
+    }
+
+    fn scan(&self, s: &str) -> Result<Vec<Token>, ErrorCode> {
+        Reporter::set_code(Some(s));
+        let mut scanner = ScannerBuilder::default()
+            .source(s)
+            .auto_semicolon(self.auto_semicolon)
+            .single_quote_strings(self.single_quote_strings)
+            .intern_identifiers(self.intern_identifiers)
+            .keep_comments(self.keep_comments)
+            .build();
+
+        let tokens = scanner.scan_tokens()?;
+        // every scan error is reported already, don't run a partially scanned source
+        if let Some(ErrorCode::ScannerError(line, message)) = scanner.errors().first() {
+            return Err(ErrorCode::ScannerError(*line, message.clone()));
+        }
+        Ok(tokens)
+    }
+}
+
+/// In memory sink shared with the interpreter while capturing its output
+#[derive(Clone, Default)]
+struct Capture(Rc<RefCell<Vec<u8>>>);
+
+impl Write for Capture {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.borrow_mut().write(buf)
+    }
 
-        println!("{:?}", result);
+    fn flush(&mut self) -> std::io::Result<()> {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod oxa_tests {
+    use super::*;
+
+    #[test]
+    fn run_capture_returns_printed_output() {
+        let mut oxa = Oxa::builder().build();
+
+        assert_eq!(oxa.run_capture("print 1; print 2;").unwrap(), "1\n2\n");
+        assert_eq!(oxa.run_capture("let a = 1;").unwrap(), "");
+    }
+
+    #[test]
+    fn error_running_capture_restores_output() {
+        let mut oxa = Oxa::builder().build();
+
+        assert!(oxa.run_capture("print \"unterminated;").is_err());
+        assert_eq!(oxa.run_capture("print \"after\";").unwrap(), "after\n");
+    }
+
+    #[test]
+    fn run_lines_keeps_state_between_lines() {
+        let mut oxa = Oxa::builder().build();
+        let captured = Capture::default();
+        oxa.interpreter
+            .borrow_mut()
+            .replace_output(Box::new(captured.clone()));
+
+        let input = "let a = 1;\n\nprint a;\nprint \"unterminated;\nprint a + 1;\nquit\nprint 3;\n";
+        oxa.run_lines(input.as_bytes()).unwrap();
+
+        assert_eq!(String::from_utf8_lossy(&captured.0.borrow()), "1\n2\n");
+        assert!(oxa.error);
+    }
+
+    #[test]
+    fn run_lines_stops_at_end_of_input() {
+        let mut oxa = Oxa::builder().build();
+        let captured = Capture::default();
+        oxa.interpreter
+            .borrow_mut()
+            .replace_output(Box::new(captured.clone()));
+
+        oxa.run_lines("print 1;\nprint 2;".as_bytes()).unwrap();
+
+        assert_eq!(String::from_utf8_lossy(&captured.0.borrow()), "1\n2\n");
+        assert!(!oxa.error);
+    }
+
+    #[test]
+    fn run_lines_echoes_last_expression() {
+        let mut oxa = Oxa::builder().build();
+        let captured = Capture::default();
+        oxa.interpreter
+            .borrow_mut()
+            .replace_output(Box::new(captured.clone()));
+
+        let input = "let a = [1, 2];\na;\nlet b = 1; b + 1;\nnil;\n\"x\";\n";
+        oxa.run_lines(input.as_bytes()).unwrap();
+
+        assert_eq!(
+            String::from_utf8_lossy(&captured.0.borrow()),
+            "[1, 2]\n2\nx\n"
+        );
+    }
+
+    #[test]
+    fn eval_returns_statement_values() {
+        let mut oxa = Oxa::builder().build();
+
+        let values = oxa.eval("let a = 2; a * 3;").unwrap();
+        assert_eq!(values.last(), Some(&Object::from(6)));
+        assert!(oxa.eval("a +;").is_err());
+    }
+
+    #[test]
+    fn dump_tokens_prints_tokens_instead_of_running() {
+        let mut oxa = Oxa::builder().dump_tokens(true).build();
+        let captured = Capture::default();
+        oxa.interpreter
+            .borrow_mut()
+            .replace_output(Box::new(captured.clone()));
+
+        oxa.run("let a = 1;\nprint a;").unwrap();
+
+        assert_eq!(
+            String::from_utf8_lossy(&captured.0.borrow()),
+            "1 let\n1 identifier a\n1 =\n1 number 1\n1 ;\n2 print\n2 identifier a\n2 ;\n2 Eof\n"
+        );
+    }
+
+    #[test]
+    fn dump_ast_prints_statements_instead_of_running() {
+        let mut oxa = Oxa::builder().dump_ast(true).build();
+        let captured = Capture::default();
+        oxa.interpreter
+            .borrow_mut()
+            .replace_output(Box::new(captured.clone()));
+
+        oxa.run("print 1 + 2;\n-3;").unwrap();
+
+        assert_eq!(
+            String::from_utf8_lossy(&captured.0.borrow()),
+            "print (+ 1 2)\nexpression (- 3)\n"
+        );
+    }
+
+    #[test]
+    fn run_lines_flags_runtime_errors_of_any_statement() {
+        for input in ["1 + nil;\n", "1 + nil; 2;\n", "let a = 1; a + nil;\n"] {
+            let mut oxa = Oxa::builder().build();
+            let captured = Capture::default();
+            oxa.interpreter
+                .borrow_mut()
+                .replace_output(Box::new(captured.clone()));
+
+            oxa.run_lines(input.as_bytes()).unwrap();
+
+            assert!(oxa.error, "{}", input);
+        }
+    }
+}
@@ -1,14 +1,34 @@
-use crate::ast::expr::{Assign, Binary, Expr, ExprKind, Grouping, Literal, Unary, Variable};
-use crate::ast::stmt::{Const, Expression, Let, Print, Stmt};
+use crate::ast::expr::{
+    Assign, Binary, Call, Expr, ExprKind, Grouping, Index, IndexSet, Lambda, List, Literal,
+    Logical, Map, Unary, Variable,
+};
+use crate::ast::stmt::{
+    Block, Const, Expression, ForEach, Function, If, Let, Print, Return, Stmt, StmtKind, While,
+};
 use crate::ast::{expr, stmt};
 use crate::errors::reporter::Reporter;
 use crate::errors::ErrorCode;
+use crate::shared::Handle;
 use crate::token;
-use crate::token::{Token, TokenKind};
+use crate::token::{Token, TokenKind, KEYWORDS};
+use std::collections::HashMap;
 
 pub struct Parser {
     tokens: Vec<Token>,
     current: usize,
+    /// every error found so far, parsing synchronizes and carries on after an error
+    errors: Vec<ErrorCode>,
+    /// bodies enclosing the statement being parsed, empty at the top level
+    contexts: Vec<Context>,
+    /// text of the comments found on their own lines, by the index of the token following them
+    comments: HashMap<usize, String>,
+}
+
+/// Kind of body a statement is parsed in, some statements are only allowed inside of one
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Context {
+    Function,
+    Loop,
 }
 
 pub type InnerExprType<T, V> = Box<dyn Expr<T, V>>;
@@ -17,9 +37,33 @@ pub type InnerStmtType<T, U, V> = Box<dyn Stmt<T, U, V>>;
 // Constructor
 impl Parser {
     pub fn from_tokens(tokens: &[Token]) -> Self {
+        let mut parsed = Vec::with_capacity(tokens.len());
+        let mut comments = HashMap::new();
+        let mut leading: Vec<&str> = Vec::new();
+
+        for token in tokens {
+            if token.kind != TokenKind::Comment {
+                if !leading.is_empty() {
+                    comments.insert(parsed.len(), leading.join("\n"));
+                    leading.clear();
+                }
+                parsed.push(token.clone());
+                continue;
+            }
+
+            // a comment trailing a line of code doesn't belong to the next statement
+            match parsed.last() {
+                Some(previous) if previous.line == token.line => {}
+                _ => leading.push(comment_text(&token.lexeme)),
+            }
+        }
+
         Parser {
-            tokens: Vec::from(tokens),
+            tokens: parsed,
             current: 0,
+            errors: Vec::new(),
+            contexts: Vec::new(),
+            comments,
         }
     }
 }
@@ -27,68 +71,400 @@ impl Parser {
 impl Parser {
     /// Parses tokens in a top down approach to find the appropriate expression, some expression take
     /// more priority then other and eventually every expression boil down to primitives
-    pub fn parse<T: 'static, U: 'static, V: 'static>(
+    ///
+    /// Parsing recovers after a syntax error and carries on to find the following ones, fails
+    /// with `ErrorCode::ParserErrors` holding all of them if there was any.
+    pub fn parse<T, U, V>(&mut self) -> Result<Vec<InnerStmtType<T, U, V>>, ErrorCode>
+    where
+        T: 'static,
+        U: 'static + stmt::Visitor<T, V>,
+        V: 'static + expr::Visitor<T> + stmt::Visitor<T, V>,
+    {
+        let (statements, errors) = self.parse_with_diagnostics();
+        if !errors.is_empty() {
+            return Err(ErrorCode::ParserErrors(errors));
+        }
+
+        Ok(statements)
+    }
+
+    /// Best effort parse which never stops at an error, returns every statement that could be
+    /// built along with the errors found in between them.
+    pub fn parse_with_diagnostics<T, U, V>(
         &mut self,
-    ) -> Result<Vec<InnerStmtType<T, U, V>>, ErrorCode>
+    ) -> (Vec<InnerStmtType<T, U, V>>, Vec<ErrorCode>)
     where
-        U: stmt::Visitor<T, V>,
-        V: expr::Visitor<T>,
+        T: 'static,
+        U: 'static + stmt::Visitor<T, V>,
+        V: 'static + expr::Visitor<T> + stmt::Visitor<T, V>,
     {
         let mut statements: Vec<InnerStmtType<T, U, V>> = Vec::new();
 
         while !self.is_at_end() {
+            let errors = self.errors.len();
+
             if let Some(stmt) = self.declaration::<T, U, V>() {
                 statements.push(stmt);
-            } else {
-                self.synchronize();
-                Reporter::line_error(self.current, "Parser error");
+                continue;
+            }
+
+            // make sure every failed declaration leaves a diagnostic behind
+            if self.errors.len() == errors {
+                if let Some(token) = self.peek() {
+                    self.error(&token, "Parser error");
+                }
             }
+            self.synchronize();
         }
 
-        Ok(statements)
+        (statements, std::mem::take(&mut self.errors))
     }
 }
 
 /// Statement parser methods
 impl Parser {
-    fn declaration<T: 'static, U: 'static, V: 'static>(&mut self) -> Option<InnerStmtType<T, U, V>>
+    fn declaration<T, U, V>(&mut self) -> Option<InnerStmtType<T, U, V>>
     where
-        U: stmt::Visitor<T, V>,
-        V: expr::Visitor<T>,
+        T: 'static,
+        U: 'static + stmt::Visitor<T, V>,
+        V: 'static + expr::Visitor<T> + stmt::Visitor<T, V>,
     {
+        let leading_comment = self.comments.get(&self.current).cloned();
+
+        // `fun` without a name starts a lambda expression rather than a declaration
+        if self.check_token(&TokenKind::Fun) && self.is_named_function() {
+            self.advance();
+            return self.function_declaration(leading_comment);
+        }
+
         if self.match_token(&[TokenKind::Const]) {
-            return self.var_declaration(true);
+            return self.var_declaration(true, None);
         }
 
         if self.match_token(&[TokenKind::Let]) {
-            return self.var_declaration(false);
+            return self.var_declaration(false, leading_comment);
         }
 
         self.statement()
     }
 
-    fn statement<T: 'static, U: 'static, V: 'static>(&mut self) -> Option<InnerStmtType<T, U, V>>
+    fn statement<T, U, V>(&mut self) -> Option<InnerStmtType<T, U, V>>
     where
-        U: stmt::Visitor<T, V>,
-        V: expr::Visitor<T>,
+        T: 'static,
+        U: 'static + stmt::Visitor<T, V>,
+        V: 'static + expr::Visitor<T> + stmt::Visitor<T, V>,
     {
+        if self.match_token(&[TokenKind::For]) {
+            return self.for_statement::<T, U, V>();
+        }
+
+        if self.match_token(&[TokenKind::If]) {
+            return self.if_statement::<T, U, V>();
+        }
+
         if self.match_token(&[TokenKind::Print]) {
             return self.print_statement::<T, U, V>();
         }
 
+        if self.match_token(&[TokenKind::Return]) {
+            return self.return_statement::<T, U, V>();
+        }
+
+        if self.match_token(&[TokenKind::While]) {
+            return self.while_statement::<T, U, V>();
+        }
+
+        // A brace starts a block unless it is followed by a map key, `{ "a": 1 };`
+        if self.check_token(&TokenKind::LeftBrace) && !self.is_map_literal() {
+            self.advance();
+            return match self.block::<T, U, V>() {
+                Ok(statements) => Some(Box::new(Block::new(statements))),
+                Err(_) => None,
+            };
+        }
+
         self.expression_statement::<T, U, V>()
     }
 
-    /// print statement parser.
+    /// block statement parser, expects the opening brace to be consumed already.
     ///
     /// # Rule
-    /// `print_stmt      → "print" expression ";" ;`
-    fn print_statement<T: 'static, U: 'static, V: 'static>(
+    /// `block           → "{" declaration* "}" ;`
+    ///
+    /// # Errors
+    /// A missing closing brace is reported at the opening brace so the user can find the
+    /// unterminated block rather than the end of the file.
+    fn block<T, U, V>(&mut self) -> Result<Vec<InnerStmtType<T, U, V>>, ErrorCode>
+    where
+        T: 'static,
+        U: 'static + stmt::Visitor<T, V>,
+        V: 'static + expr::Visitor<T> + stmt::Visitor<T, V>,
+    {
+        let opener = self.previous().ok_or(ErrorCode::ProcessError)?;
+        let mut statements: Vec<InnerStmtType<T, U, V>> = Vec::new();
+        let mut returned = false;
+        let mut warned = false;
+
+        while !self.check_token(&TokenKind::RightBrace) && !self.is_at_end() {
+            // only the first unreachable statement of the block is reported
+            if returned && !warned {
+                warned = true;
+                if let Some(token) = self.peek() {
+                    Reporter::warn(token.line, "unreachable code after 'return'");
+                }
+            }
+
+            match self.declaration::<T, U, V>() {
+                Some(stmt) => {
+                    returned |= matches!(stmt.kind(), StmtKind::Return(_));
+                    statements.push(stmt);
+                }
+                None => self.synchronize(),
+            }
+        }
+
+        if self.consume(&TokenKind::RightBrace).is_none() {
+            return Err(self.error(&opener, "Expect '}' after block."));
+        }
+
+        Ok(statements)
+    }
+
+    /// function declaration parser.
+    ///
+    /// # Rule
+    /// `fun_decl        → "fun" IDENTIFIER "(" parameters? ")" block ;`
+    /// `parameters      → IDENTIFIER ( "," IDENTIFIER )* ;`
+    fn function_declaration<T, U, V>(
         &mut self,
+        leading_comment: Option<String>,
     ) -> Option<InnerStmtType<T, U, V>>
     where
-        U: stmt::Visitor<T, V>,
-        V: expr::Visitor<T>,
+        T: 'static,
+        U: 'static + stmt::Visitor<T, V>,
+        V: 'static + expr::Visitor<T> + stmt::Visitor<T, V>,
+    {
+        let name = self.expect(&TokenKind::Identifier, "Expect function name.")?;
+        self.expect(&TokenKind::LeftParen, "Expect '(' after function name.")?;
+        let params = self.parameters()?;
+        self.expect(&TokenKind::LeftBrace, "Expect '{' before function body.")?;
+        let body = self.within(Context::Function, |parser| parser.block::<T, U, V>().ok())?;
+
+        Some(Box::new(Function::new(
+            name,
+            params,
+            Handle::new(body),
+            leading_comment,
+        )))
+    }
+
+    /// for statement parser, the loop is desugared into a `while` statement.
+    ///
+    /// # Rule
+    /// `for_stmt        → "for" "(" ( var_decl | expr_stmt | ";" )
+    ///                    expression? ";" expression? ")" statement ;`
+    ///
+    /// # Scope
+    /// A loop variable declared in the initializer gets a fresh binding on every iteration, so
+    /// closures created in the body capture the value of their own iteration. The body runs in a
+    /// child environment holding a copy of the variable, which is written back through a hidden
+    /// variable before the increment:
+    ///
+    /// ```text
+    /// { let i = 0; let <i>; while (i < 3) { { let i = i; body; <i> = i; } i = <i>; i = i + 1; } }
+    /// ```
+    fn for_statement<T, U, V>(&mut self) -> Option<InnerStmtType<T, U, V>>
+    where
+        T: 'static,
+        U: 'static + stmt::Visitor<T, V>,
+        V: 'static + expr::Visitor<T> + stmt::Visitor<T, V>,
+    {
+        self.expect(&TokenKind::LeftParen, "Expect '(' after 'for'.")?;
+
+        if self.check_token(&TokenKind::Identifier)
+            && self.peek_index(self.current + 1).map(|t| t.kind) == Some(TokenKind::In)
+        {
+            return self.for_each_statement::<T, U, V>();
+        }
+
+        let initializer = if self.match_token(&[TokenKind::SemiColon]) {
+            None
+        } else if self.match_token(&[TokenKind::Let]) {
+            Some(self.var_declaration::<T, U, V>(false, None)?)
+        } else {
+            Some(self.expression_statement::<T, U, V>()?)
+        };
+
+        let condition = if self.check_token(&TokenKind::SemiColon) {
+            Box::new(Literal::new(token::Literal::from(true)))
+        } else {
+            self.expression::<T, V>()?
+        };
+        self.expect(&TokenKind::SemiColon, "Expect ';' after loop condition.")?;
+
+        let increment = if self.check_token(&TokenKind::RightParen) {
+            None
+        } else {
+            Some(self.expression::<T, V>()?)
+        };
+        self.expect(&TokenKind::RightParen, "Expect ')' after for clauses.")?;
+
+        let body = self.within(Context::Loop, |parser| parser.statement::<T, U, V>())?;
+
+        let loop_variable = match initializer.as_ref().map(|stmt| stmt.kind()) {
+            Some(StmtKind::Let(declaration)) => Some(declaration.name.clone()),
+            _ => None,
+        };
+
+        let mut declarations: Vec<InnerStmtType<T, U, V>> = initializer.into_iter().collect();
+        let mut statements: Vec<InnerStmtType<T, U, V>> = Vec::new();
+        match loop_variable {
+            Some(name) => {
+                // The hidden name can't be written in source so it never clashes with user code
+                let hidden = Token::new(
+                    TokenKind::Identifier,
+                    &format!("<{}>", name.lexeme),
+                    None,
+                    name.line,
+                    name.column,
+                );
+                declarations.push(Box::new(Let::new(
+                    hidden.clone(),
+                    Box::new(Literal::new(token::Literal::default())),
+                    None,
+                )));
+
+                let iteration: Vec<InnerStmtType<T, U, V>> = vec![
+                    Box::new(Let::new(
+                        name.clone(),
+                        Box::new(Variable::new(name.clone())),
+                        None,
+                    )),
+                    body,
+                    Box::new(Expression::new(Box::new(Assign::new(
+                        hidden.clone(),
+                        Box::new(Variable::new(name.clone())),
+                    )))),
+                ];
+                statements.push(Box::new(Block::new(iteration)));
+                statements.push(Box::new(Expression::new(Box::new(Assign::new(
+                    name,
+                    Box::new(Variable::new(hidden)),
+                )))));
+            }
+            None => statements.push(body),
+        }
+
+        if let Some(increment) = increment {
+            statements.push(Box::new(Expression::new(increment)));
+        }
+
+        declarations.push(Box::new(While::new(
+            condition,
+            Box::new(Block::new(statements)),
+        )));
+
+        Some(Box::new(Block::new(declarations)))
+    }
+
+    /// for-each statement parser, expects `for (` to be consumed already.
+    ///
+    /// # Rule
+    /// `for_each_stmt   → "for" "(" IDENTIFIER "in" expression ")" statement ;`
+    fn for_each_statement<T, U, V>(&mut self) -> Option<InnerStmtType<T, U, V>>
+    where
+        T: 'static,
+        U: 'static + stmt::Visitor<T, V>,
+        V: 'static + expr::Visitor<T> + stmt::Visitor<T, V>,
+    {
+        let variable = self.expect(&TokenKind::Identifier, "Expect loop variable name.")?;
+        self.expect(&TokenKind::In, "Expect 'in' after loop variable.")?;
+        let iterable = self.expression::<T, V>()?;
+        self.expect(&TokenKind::RightParen, "Expect ')' after for-each clause.")?;
+        let body = self.within(Context::Loop, |parser| parser.statement::<T, U, V>())?;
+
+        Some(Box::new(ForEach::new(variable, iterable, body)))
+    }
+
+    /// if statement parser, the `else` is bound to the nearest `if` preceding it.
+    ///
+    /// # Rule
+    /// `if_stmt         → "if" "(" expression ")" statement ( "else" statement )? ;`
+    fn if_statement<T, U, V>(&mut self) -> Option<InnerStmtType<T, U, V>>
+    where
+        T: 'static,
+        U: 'static + stmt::Visitor<T, V>,
+        V: 'static + expr::Visitor<T> + stmt::Visitor<T, V>,
+    {
+        self.expect(&TokenKind::LeftParen, "Expect '(' after 'if'.")?;
+        let condition = self.expression::<T, V>()?;
+        self.expect(&TokenKind::RightParen, "Expect ')' after if condition.")?;
+
+        let then_branch = self.statement::<T, U, V>()?;
+        let else_branch = if self.match_token(&[TokenKind::Else]) {
+            Some(self.statement::<T, U, V>()?)
+        } else {
+            None
+        };
+
+        Some(Box::new(If::new(condition, then_branch, else_branch)))
+    }
+
+    /// while statement parser.
+    ///
+    /// # Rule
+    /// `while_stmt      → "while" "(" expression ")" statement ;`
+    fn while_statement<T, U, V>(&mut self) -> Option<InnerStmtType<T, U, V>>
+    where
+        T: 'static,
+        U: 'static + stmt::Visitor<T, V>,
+        V: 'static + expr::Visitor<T> + stmt::Visitor<T, V>,
+    {
+        self.expect(&TokenKind::LeftParen, "Expect '(' after 'while'.")?;
+        let condition = self.expression::<T, V>()?;
+        self.expect(&TokenKind::RightParen, "Expect ')' after condition.")?;
+        let body = self.within(Context::Loop, |parser| parser.statement::<T, U, V>())?;
+
+        Some(Box::new(While::new(condition, body)))
+    }
+
+    /// return statement parser, a bare `return;` returns `nil`.
+    ///
+    /// # Rule
+    /// `return_stmt     → "return" expression? ";" ;`
+    fn return_statement<T, U, V>(&mut self) -> Option<InnerStmtType<T, U, V>>
+    where
+        T: 'static,
+        U: 'static + stmt::Visitor<T, V>,
+        V: 'static + expr::Visitor<T> + stmt::Visitor<T, V>,
+    {
+        let keyword = self.previous()?;
+
+        let value = if self.check_token(&TokenKind::SemiColon) {
+            Box::new(Literal::new(token::Literal::default()))
+        } else {
+            self.expression::<T, V>()?
+        };
+
+        self.check_stmt_terminal();
+
+        if !self.contexts.contains(&Context::Function) {
+            self.error(&keyword, "'return' outside of a function");
+            return None;
+        }
+
+        Some(Box::new(Return::new(keyword, value)))
+    }
+
+    /// print statement parser.
+    ///
+    /// # Rule
+    /// `print_stmt      → "print" expression ";" ;`
+    fn print_statement<T, U, V>(&mut self) -> Option<InnerStmtType<T, U, V>>
+    where
+        T: 'static,
+        U: 'static + stmt::Visitor<T, V>,
+        V: 'static + expr::Visitor<T> + stmt::Visitor<T, V>,
     {
         if let Some(expr) = self.expression::<T, V>() {
             self.check_stmt_terminal();
@@ -104,15 +480,27 @@ impl Parser {
     /// # Rule
     /// `var_decl        → "let" IDENTIFIER ( "=" expression )? ";"
     ///                  | "const" IDENTIFIER ( "=" expression )? ";" ;`
-    fn var_declaration<T: 'static, U: 'static, V: 'static>(
+    fn var_declaration<T, U, V>(
         &mut self,
         is_const: bool,
+        leading_comment: Option<String>,
     ) -> Option<InnerStmtType<T, U, V>>
     where
-        U: stmt::Visitor<T, V>,
-        V: expr::Visitor<T>,
+        T: 'static,
+        U: 'static + stmt::Visitor<T, V>,
+        V: 'static + expr::Visitor<T> + stmt::Visitor<T, V>,
     {
-        let name = self.consume(&TokenKind::Identifier)?;
+        if let Some(token) = self.peek().filter(|t| KEYWORDS.contains_key(&*t.lexeme)) {
+            self.error(
+                &token,
+                &format!(
+                    "'{}' is a reserved keyword and cannot be used as a variable name",
+                    token.lexeme
+                ),
+            );
+            return None;
+        }
+        let name = self.expect(&TokenKind::Identifier, "Expect variable name.")?;
 
         let initializer = if self.match_token(&[TokenKind::Equal]) {
             self.expression::<T, V>()?
@@ -127,19 +515,18 @@ impl Parser {
             return Some(Box::new(Const::new(name, initializer)));
         }
 
-        Some(Box::new(Let::new(name, initializer)))
+        Some(Box::new(Let::new(name, initializer, leading_comment)))
     }
 
     /// Expression statement parser
     ///
     /// # Rule
     /// `expr_stmt       → expression ";" ;`
-    fn expression_statement<T: 'static, U: 'static, V: 'static>(
-        &mut self,
-    ) -> Option<InnerStmtType<T, U, V>>
+    fn expression_statement<T, U, V>(&mut self) -> Option<InnerStmtType<T, U, V>>
     where
-        U: stmt::Visitor<T, V>,
-        V: expr::Visitor<T>,
+        T: 'static,
+        U: 'static + stmt::Visitor<T, V>,
+        V: 'static + expr::Visitor<T> + stmt::Visitor<T, V>,
     {
         if let Some(expr) = self.expression::<T, V>() {
             self.check_stmt_terminal();
@@ -157,9 +544,10 @@ impl Parser {
     ///
     /// # Rule
     /// `equality → comparison(("!=" | "==") comparison)*;`
-    pub fn expression<T: 'static, V: 'static>(&mut self) -> Option<InnerExprType<T, V>>
+    pub fn expression<T, V>(&mut self) -> Option<InnerExprType<T, V>>
     where
-        V: expr::Visitor<T>,
+        T: 'static,
+        V: 'static + expr::Visitor<T> + stmt::Visitor<T, V>,
     {
         self.assignment()
     }
@@ -168,12 +556,42 @@ impl Parser {
     ///
     /// # Rule
     /// `expression    → assignment ;`
-    /// `assignment    → IDENTIFIER "=" assignment | equality ;`
-    pub fn assignment<T: 'static, V: 'static>(&mut self) -> Option<InnerExprType<T, V>>
+    /// `assignment    → ( IDENTIFIER | call "[" expression "]" ) "=" assignment
+    ///                | IDENTIFIER ( "+=" | "-=" | "*=" | "/=" | "%=" | "**=" ) assignment
+    ///                | pipeline ;`
+    pub fn assignment<T, V>(&mut self) -> Option<InnerExprType<T, V>>
     where
-        V: expr::Visitor<T>,
+        T: 'static,
+        V: 'static + expr::Visitor<T> + stmt::Visitor<T, V>,
     {
-        let expr = self.equality()?;
+        let expr = self.pipeline()?;
+
+        if self.match_token(&[
+            TokenKind::PlusEqual,
+            TokenKind::MinusEqual,
+            TokenKind::StarEqual,
+            TokenKind::SlashEqual,
+            TokenKind::PercentEqual,
+            TokenKind::StarStarEqual,
+        ]) {
+            let operator = self.previous()?;
+            let value = self.assignment()?;
+            if let ExprKind::Variable(v) = expr.kind() {
+                // `a %= b` is desugared to `a = a % b`
+                let binary_operator = Token::new(
+                    compound_operator(&operator.kind)?,
+                    operator.lexeme.trim_end_matches('='),
+                    None,
+                    operator.line,
+                    operator.column,
+                );
+                let current = Box::new(Variable::new(v.name.clone()));
+                let binary = Box::new(Binary::new(current, binary_operator, value));
+                return Some(Box::new(Assign::new(v.name.clone(), binary)));
+            }
+            self.error(&operator, "Invalid assignment target.");
+            return Some(expr);
+        }
 
         if self.match_token(&[TokenKind::Equal]) {
             let equals = self.previous()?;
@@ -182,19 +600,80 @@ impl Parser {
                 let name = &v.name;
                 return Some(Box::new(Assign::new(name.clone(), value)));
             }
+            if let ExprKind::Index(_) = expr.kind() {
+                return Some(Box::new(IndexSet::new(expr, value)));
+            }
             // The grammar is incorrect
-            Reporter::token_error(&equals, "Invalid assignment target.");
+            self.error(&equals, "Invalid assignment target.");
+        }
+
+        Some(expr)
+    }
+
+    /// pipeline parser, `x |> f` is desugared to the call `f(x)` so `x |> f |> g` is `g(f(x))`.
+    ///
+    /// # Rule
+    /// `pipeline      → logic_or ( "|>" logic_or )* ;`
+    fn pipeline<T, V>(&mut self) -> Option<InnerExprType<T, V>>
+    where
+        T: 'static,
+        V: 'static + expr::Visitor<T> + stmt::Visitor<T, V>,
+    {
+        let mut expr = self.or()?;
+        while self.match_token(&[TokenKind::PipeGreater]) {
+            let pipe = self.previous()?;
+            let callee = self.or()?;
+            expr = Box::new(Call::new(callee, pipe, vec![expr]));
+        }
+        Some(expr)
+    }
+
+    /// logical or parser, binds looser than `and`.
+    ///
+    /// # Rule
+    /// `logic_or      → logic_and ( "or" logic_and )* ;`
+    fn or<T, V>(&mut self) -> Option<InnerExprType<T, V>>
+    where
+        T: 'static,
+        V: 'static + expr::Visitor<T> + stmt::Visitor<T, V>,
+    {
+        let mut expr = self.and()?;
+        while self.match_token(&[TokenKind::Or]) {
+            let operator = self.previous()?;
+            let right = self.and()?;
+            expr = Box::new(Logical::new(expr, operator, right));
         }
+        Some(expr)
+    }
 
+    /// logical and parser.
+    ///
+    /// # Rule
+    /// `logic_and     → equality ( "and" equality )* ;`
+    fn and<T, V>(&mut self) -> Option<InnerExprType<T, V>>
+    where
+        T: 'static,
+        V: 'static + expr::Visitor<T> + stmt::Visitor<T, V>,
+    {
+        let mut expr = self.equality()?;
+        while self.match_token(&[TokenKind::And]) {
+            let operator = self.previous()?;
+            let right = self.equality()?;
+            expr = Box::new(Logical::new(expr, operator, right));
+        }
         Some(expr)
     }
 
-    fn equality<T: 'static, V: 'static>(&mut self) -> Option<InnerExprType<T, V>>
+    fn equality<T, V>(&mut self) -> Option<InnerExprType<T, V>>
     where
-        V: expr::Visitor<T>,
+        T: 'static,
+        V: 'static + expr::Visitor<T> + stmt::Visitor<T, V>,
     {
         let mut expr = self.comparison();
-        while self.match_token(&[TokenKind::EqualEqual, TokenKind::EqualEqual]) {
+
+        expr.as_ref()?;
+
+        while self.match_token(&[TokenKind::BangEqual, TokenKind::EqualEqual]) {
             let operator = self.previous();
             let right = self.comparison();
             if right.is_none() || operator.is_none() {
@@ -213,9 +692,10 @@ impl Parser {
     ///
     /// # Rule
     /// `comparison → term ((">" | ">=" | "<" | "<=") term)* ;`
-    fn comparison<T: 'static, V: 'static>(&mut self) -> Option<InnerExprType<T, V>>
+    fn comparison<T, V>(&mut self) -> Option<InnerExprType<T, V>>
     where
-        V: expr::Visitor<T>,
+        T: 'static,
+        V: 'static + expr::Visitor<T> + stmt::Visitor<T, V>,
     {
         let mut expr = self.term();
 
@@ -246,9 +726,10 @@ impl Parser {
     ///
     /// # Rule
     /// `term -> primary ("+" | "-") primary;`
-    fn term<T: 'static, V: 'static>(&mut self) -> Option<InnerExprType<T, V>>
+    fn term<T, V>(&mut self) -> Option<InnerExprType<T, V>>
     where
-        V: expr::Visitor<T>,
+        T: 'static,
+        V: 'static + expr::Visitor<T> + stmt::Visitor<T, V>,
     {
         let mut expr = self.factor();
 
@@ -270,47 +751,37 @@ impl Parser {
         expr
     }
 
-    /// match multiplication and division expression.
+    /// match multiplication, division and remainder expression.
     ///
     /// # Rule
-    /// `factor -> primary ("*" | "/") primary
-    ///            | primary;`
-    fn factor<T: 'static, V: 'static>(&mut self) -> Option<InnerExprType<T, V>>
+    /// `factor -> unary (("*" | "/" | "%") unary)*;`
+    fn factor<T, V>(&mut self) -> Option<InnerExprType<T, V>>
     where
-        V: expr::Visitor<T>,
+        T: 'static,
+        V: 'static + expr::Visitor<T> + stmt::Visitor<T, V>,
     {
-        let expr = self.unary();
+        let mut expr = self.unary()?;
 
-        expr.as_ref()?;
-
-        match self.match_token(&[TokenKind::Slash, TokenKind::Star]) {
-            true => {
-                let operator = self.previous();
-                let right = self.unary();
-
-                if right.is_none() || operator.is_none() {
-                    return None;
-                }
-                Some(Box::new(Binary::new(
-                    expr.unwrap(),
-                    operator.unwrap(),
-                    right.unwrap(),
-                )))
-            }
-            false => expr,
+        while self.match_token(&[TokenKind::Slash, TokenKind::Star, TokenKind::Percent]) {
+            let operator = self.previous()?;
+            let right = self.unary()?;
+            expr = Box::new(Binary::new(expr, operator, right));
         }
+
+        Some(expr)
     }
 
     /// matches unary expression.
     ///
     /// # Rule
-    /// `unary → ("!" | "-") unary
-    ///          | primary;`
-    fn unary<T: 'static, V: 'static>(&mut self) -> Option<InnerExprType<T, V>>
+    /// `unary → ("!" | "-" | "+") unary
+    ///          | power;`
+    fn unary<T, V>(&mut self) -> Option<InnerExprType<T, V>>
     where
-        V: expr::Visitor<T>,
+        T: 'static,
+        V: 'static + expr::Visitor<T> + stmt::Visitor<T, V>,
     {
-        if self.match_token(&[TokenKind::Bang, TokenKind::Minus]) {
+        if self.match_token(&[TokenKind::Bang, TokenKind::Minus, TokenKind::Plus]) {
             let operator = self.previous();
             let right = self.unary();
 
@@ -321,7 +792,66 @@ impl Parser {
             return Some(Box::new(Unary::new(operator.unwrap(), right.unwrap())));
         }
 
-        self.primary()
+        self.power()
+    }
+
+    /// matches exponentiation, it is right associative and binds tighter than a unary operator on
+    /// its left so `-2 ** 2` is `-(2 ** 2)`.
+    ///
+    /// # Rule
+    /// `power → call ("**" unary)?;`
+    fn power<T, V>(&mut self) -> Option<InnerExprType<T, V>>
+    where
+        T: 'static,
+        V: 'static + expr::Visitor<T> + stmt::Visitor<T, V>,
+    {
+        let base = self.call()?;
+
+        if self.match_token(&[TokenKind::StarStar]) {
+            let operator = self.previous()?;
+            let exponent = self.unary()?;
+            return Some(Box::new(Binary::new(base, operator, exponent)));
+        }
+
+        Some(base)
+    }
+
+    /// matches function calls and indexing, both can be chained as long as they are followed by
+    /// parenthesis or brackets.
+    ///
+    /// # Rule
+    /// `call      → primary ( "(" arguments? ")" | "[" expression "]" )* ;`
+    /// `arguments → expression ( "," expression )* ;`
+    fn call<T, V>(&mut self) -> Option<InnerExprType<T, V>>
+    where
+        T: 'static,
+        V: 'static + expr::Visitor<T> + stmt::Visitor<T, V>,
+    {
+        let mut expr = self.primary()?;
+
+        while self.match_token(&[TokenKind::LeftParen, TokenKind::LeftBracket]) {
+            if self.previous()?.kind == TokenKind::LeftBracket {
+                let index = self.expression::<T, V>()?;
+                let bracket = self.expect(&TokenKind::RightBracket, "Expect ']' after index.")?;
+                expr = Box::new(Index::new(expr, bracket, index));
+                continue;
+            }
+
+            let mut arguments = Vec::new();
+            if !self.check_token(&TokenKind::RightParen) {
+                loop {
+                    arguments.push(self.expression::<T, V>()?);
+                    if !self.match_token(&[TokenKind::Comma]) {
+                        break;
+                    }
+                }
+            }
+
+            let paren = self.expect(&TokenKind::RightParen, "Expect ')' after arguments.")?;
+            expr = Box::new(Call::new(expr, paren, arguments));
+        }
+
+        Some(expr)
     }
 
     /// matches primitive types or parenthesis matching.
@@ -330,10 +860,15 @@ impl Parser {
     /// `primary → NUMBER | STRING
     ///            | "true" | "false" | "nil"
     ///            | "("expression")"
+    ///            | "[" ( expression ( "," expression )* )? "]"
+    ///            | "{" ( expression ":" expression ( "," expression ":" expression )* )? "}"
+    ///            | "fun" "(" parameters? ")" block
+    ///            | "(" parameters? ")" "=>" expression
     ///            | IDENTIFIER;`
-    fn primary<T: 'static, V: 'static>(&mut self) -> Option<InnerExprType<T, V>>
+    fn primary<T, V>(&mut self) -> Option<InnerExprType<T, V>>
     where
-        V: expr::Visitor<T>,
+        T: 'static,
+        V: 'static + expr::Visitor<T> + stmt::Visitor<T, V>,
     {
         if self.match_token(&[TokenKind::Identifier]) {
             return match self.previous() {
@@ -343,16 +878,61 @@ impl Parser {
         }
 
         if self.match_token(&[TokenKind::Number, TokenKind::String]) {
-            return match self.previous() {
-                Some(t) => {
-                    if let Some(l) = &t.literal {
-                        return Some(Box::new(Literal::new(l.clone())));
+            let literal = self.tokens.get(self.current - 1)?.literal_value()?;
+            return Some(Box::new(Literal::new(literal.clone())));
+        }
+
+        if self.match_token(&[TokenKind::LeftBrace]) {
+            let mut entries = Vec::new();
+            if !self.check_token(&TokenKind::RightBrace) {
+                loop {
+                    let key = self.expression::<T, V>()?;
+                    self.expect(&TokenKind::Colon, "Expect ':' after map key.")?;
+                    entries.push((key, self.expression::<T, V>()?));
+                    if !self.match_token(&[TokenKind::Comma]) {
+                        break;
                     }
+                }
+            }
+
+            let brace = self.expect(&TokenKind::RightBrace, "Expect '}' after map entries.")?;
+            return Some(Box::new(Map::new(brace, entries)));
+        }
 
-                    None
+        if self.match_token(&[TokenKind::LeftBracket]) {
+            let mut elements = Vec::new();
+            if !self.check_token(&TokenKind::RightBracket) {
+                loop {
+                    elements.push(self.expression::<T, V>()?);
+                    if !self.match_token(&[TokenKind::Comma]) {
+                        break;
+                    }
                 }
-                None => None,
-            };
+            }
+
+            let bracket =
+                self.expect(&TokenKind::RightBracket, "Expect ']' after list elements.")?;
+            return Some(Box::new(List::new(bracket, elements)));
+        }
+
+        if self.match_token(&[TokenKind::Fun]) {
+            let keyword = self.previous()?;
+            self.expect(&TokenKind::LeftParen, "Expect '(' after 'fun'.")?;
+            let params = self.parameters()?;
+            self.expect(&TokenKind::LeftBrace, "Expect '{' before function body.")?;
+            let body = self.within(Context::Function, |parser| parser.block::<T, V, V>().ok())?;
+            return Some(Box::new(Lambda::new(keyword, params, Handle::new(body))));
+        }
+
+        if self.check_token(&TokenKind::LeftParen) && self.is_arrow_lambda() {
+            self.advance();
+            let params = self.parameters()?;
+            let arrow = self.expect(&TokenKind::Arrow, "Expect '=>' after parameters.")?;
+            // `(a) => a + 1` is a shorthand for `fun(a) { return a + 1; }`
+            let value = self.expression::<T, V>()?;
+            let body: Vec<InnerStmtType<T, V, V>> =
+                vec![Box::new(Return::new(arrow.clone(), value))];
+            return Some(Box::new(Lambda::new(arrow, params, Handle::new(body))));
         }
 
         if self.match_token(&[TokenKind::LeftParen]) {
@@ -384,7 +964,19 @@ impl Parser {
         }
 
         if let Some(t) = self.peek() {
-            self.error(&t, "Expect expression.");
+            match t.kind {
+                // already reported by the scanner
+                TokenKind::Error => self.errors.push(ErrorCode::ParserError(
+                    t.clone(),
+                    format!("Unexpected character: {}.", t.lexeme),
+                )),
+                TokenKind::RightParen | TokenKind::RightBrace | TokenKind::RightBracket => {
+                    self.error(&t, &format!("Unexpected closing '{}'", t.lexeme));
+                }
+                _ => {
+                    self.error(&t, "Expect expression.");
+                }
+            }
         }
         None
     }
@@ -417,6 +1009,20 @@ impl Parser {
         }
     }
 
+    /// Consumes the current token if it is of the given kind, otherwise reports the `message`
+    /// as an error at the current token and returns `None`.
+    fn expect(&mut self, kind: &TokenKind, message: &str) -> Option<Token> {
+        match self.consume(kind) {
+            Some(token) => Some(token),
+            None => {
+                if let Some(token) = self.peek() {
+                    self.error(&token, message);
+                }
+                None
+            }
+        }
+    }
+
     /// returns true if the current token is of the given type.
     fn check_token(&self, kind: &TokenKind) -> bool {
         match self.is_at_end() {
@@ -459,6 +1065,71 @@ impl Parser {
         self.peek_index(self.current - 1)
     }
 
+    /// parses a comma separated list of parameter names up to the closing parenthesis, the
+    /// opening one is already consumed
+    fn parameters(&mut self) -> Option<Vec<Token>> {
+        let mut params = Vec::new();
+        if !self.check_token(&TokenKind::RightParen) {
+            loop {
+                params.push(self.expect(&TokenKind::Identifier, "Expect parameter name.")?);
+                if !self.match_token(&[TokenKind::Comma]) {
+                    break;
+                }
+            }
+        }
+
+        self.expect(&TokenKind::RightParen, "Expect ')' after parameters.")?;
+        Some(params)
+    }
+
+    /// returns true if the current `fun` keyword is followed by a name and so declares a
+    /// function rather than starting a lambda
+    fn is_named_function(&self) -> bool {
+        self.peek_index(self.current + 1)
+            .is_some_and(|t| t.kind == TokenKind::Identifier)
+    }
+
+    /// returns true if the current parenthesis opens the parameters of an arrow lambda, that is
+    /// it only holds comma separated names and the closing parenthesis is followed by `=>`
+    fn is_arrow_lambda(&self) -> bool {
+        let mut index = self.current + 1;
+        let kind_at = |index: usize| self.tokens.get(index).map(|t| t.kind.clone());
+        if kind_at(index) != Some(TokenKind::RightParen) {
+            loop {
+                if kind_at(index) != Some(TokenKind::Identifier) {
+                    return false;
+                }
+                index += 1;
+                match kind_at(index) {
+                    Some(TokenKind::Comma) => index += 1,
+                    Some(TokenKind::RightParen) => break,
+                    _ => return false,
+                }
+            }
+        }
+
+        kind_at(index + 1) == Some(TokenKind::Arrow)
+    }
+
+    /// returns true if the current brace opens a map literal rather than a block, that is the
+    /// brace is followed by a single token key and a colon.
+    fn is_map_literal(&self) -> bool {
+        let key = self.peek_index(self.current + 1).map(|t| t.kind);
+        let colon = self.peek_index(self.current + 2).map(|t| t.kind);
+
+        matches!(
+            key,
+            Some(
+                TokenKind::String
+                    | TokenKind::Number
+                    | TokenKind::Identifier
+                    | TokenKind::True
+                    | TokenKind::False
+                    | TokenKind::Nil
+            )
+        ) && colon == Some(TokenKind::Colon)
+    }
+
     /// returns true if there is still some token to parse
     fn is_at_end(&self) -> bool {
         match self.peek() {
@@ -467,6 +1138,14 @@ impl Parser {
         }
     }
 
+    /// parses a body with `context` pushed on the context stack
+    fn within<R>(&mut self, context: Context, parse: impl FnOnce(&mut Self) -> R) -> R {
+        self.contexts.push(context);
+        let result = parse(self);
+        self.contexts.pop();
+        result
+    }
+
     /// discards tokens until we find a statement boundary
     fn synchronize(&mut self) {
         self.advance();
@@ -503,17 +1182,44 @@ impl Parser {
         }
     }
 
-    fn error(&self, token: &Token, message: &str) -> ErrorCode {
+    /// reports the error at the token and keeps it for `parse_with_diagnostics`
+    fn error(&mut self, token: &Token, message: &str) -> ErrorCode {
         Reporter::token_error(token, message);
+        self.errors
+            .push(ErrorCode::ParserError(token.clone(), message.to_string()));
         ErrorCode::ParserError(token.clone(), message.to_string())
     }
 }
 
+/// text of a `// ...` or `/* ... */` comment without its delimiters
+fn comment_text(comment: &str) -> &str {
+    match comment.strip_prefix("/*") {
+        Some(block) => block.strip_suffix("*/").unwrap_or(block).trim(),
+        None => comment.trim_start_matches('/').trim(),
+    }
+}
+
+/// returns the binary operator applied by a compound assignment operator
+fn compound_operator(kind: &TokenKind) -> Option<TokenKind> {
+    match kind {
+        TokenKind::PlusEqual => Some(TokenKind::Plus),
+        TokenKind::MinusEqual => Some(TokenKind::Minus),
+        TokenKind::StarEqual => Some(TokenKind::Star),
+        TokenKind::SlashEqual => Some(TokenKind::Slash),
+        TokenKind::PercentEqual => Some(TokenKind::Percent),
+        TokenKind::StarStarEqual => Some(TokenKind::StarStar),
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod parser_tests {
-    use crate::ast::expr::{Binary, Unary};
+    use crate::ast::expr::{Assign, Binary, Unary, Variable};
     use crate::ast::printer::AstPrinter;
+    use crate::errors::reporter::Reporter;
+    use crate::errors::ErrorCode;
     use crate::parser::{Literal, Parser};
+    use crate::scanner::ScannerBuilder;
     use crate::token;
     use crate::token::{Token, TokenKind};
 
@@ -527,10 +1233,10 @@ mod parser_tests {
     #[test]
     fn confirms_existence_of_token() {
         let tokens = [
-            Token::new(TokenKind::Minus, "-", None, 1),
-            Token::new(TokenKind::Plus, "+", None, 1),
-            Token::new(TokenKind::Slash, "/", None, 1),
-            Token::new(TokenKind::Star, "*", None, 1),
+            Token::new(TokenKind::Minus, "-", None, 1, 1),
+            Token::new(TokenKind::Plus, "+", None, 1, 1),
+            Token::new(TokenKind::Slash, "/", None, 1, 1),
+            Token::new(TokenKind::Star, "*", None, 1, 1),
         ];
 
         let mut parser = Parser::from_tokens(&tokens);
@@ -546,12 +1252,13 @@ mod parser_tests {
     fn parse_simple_expression() {
         // !false
         let tokens = [
-            Token::new(TokenKind::Bang, "!", None, 1),
+            Token::new(TokenKind::Bang, "!", None, 1, 1),
             Token::new(
                 TokenKind::False,
                 "false",
                 Some(token::Literal::from(false)),
                 1,
+                1,
             ),
         ];
 
@@ -561,7 +1268,7 @@ mod parser_tests {
         assert!(expr.is_some());
 
         let expected = Unary::new(
-            Token::new(TokenKind::Bang, "!", None, 1),
+            Token::new(TokenKind::Bang, "!", None, 1, 1),
             Box::new(Literal::<String, AstPrinter>::new(token::Literal::from(
                 false,
             ))),
@@ -573,8 +1280,8 @@ mod parser_tests {
     #[test]
     fn parse_unary_expression() {
         let tokens = [
-            Token::new(TokenKind::Minus, "-", None, 1),
-            Token::new(TokenKind::Number, "2", Some(token::Literal::from(2)), 1),
+            Token::new(TokenKind::Minus, "-", None, 1, 1),
+            Token::new(TokenKind::Number, "2", Some(token::Literal::from(2)), 1, 1),
         ];
 
         let mut parser = Parser::from_tokens(&tokens);
@@ -583,20 +1290,173 @@ mod parser_tests {
         assert!(expr.is_some());
 
         let expected = Unary::new(
-            Token::new(TokenKind::Minus, "-", None, 1),
+            Token::new(TokenKind::Minus, "-", None, 1, 1),
             Box::new(Literal::<String, AstPrinter>::new(token::Literal::from(2))),
         );
 
         assert_eq!(expr.unwrap().to_string(), expected.to_string());
     }
 
+    #[test]
+    fn parse_unary_plus() {
+        let tokens = [
+            Token::new(TokenKind::Plus, "+", None, 1, 1),
+            Token::new(TokenKind::Minus, "-", None, 1, 2),
+            Token::new(TokenKind::Number, "2", Some(token::Literal::from(2)), 1, 3),
+        ];
+
+        let mut parser = Parser::from_tokens(&tokens);
+        let expr = parser.unary::<String, AstPrinter>().unwrap();
+
+        assert_eq!(AstPrinter {}.print_expr(expr.as_ref()), "(+ (- 2))");
+    }
+
+    fn assert_compound_assignment(compound: Token, operator: Token) {
+        // x <compound> 3
+        let x = Token::new(TokenKind::Identifier, "x", None, 1, 1);
+        let three = Token::new(TokenKind::Number, "3", Some(token::Literal::from(3)), 1, 1);
+        let tokens = [x.clone(), compound, three];
+
+        let mut parser = Parser::from_tokens(&tokens);
+        let expr = parser.expression::<String, AstPrinter>();
+
+        assert!(expr.is_some());
+
+        let expected = Assign::new(
+            x.clone(),
+            Box::new(Binary::new(
+                Box::new(Variable::<String, AstPrinter>::new(x)),
+                operator,
+                Box::new(Literal::<String, AstPrinter>::new(token::Literal::from(3))),
+            )),
+        );
+
+        let mut printer = AstPrinter {};
+        let expr = expr.unwrap();
+        assert_eq!(expr.to_string(), expected.to_string());
+        assert_eq!(
+            printer.print_expr(expr.as_ref()),
+            printer.print_expr(&expected)
+        );
+    }
+
+    #[test]
+    fn parse_bang_equal() {
+        use crate::ast::expr::ExprKind;
+
+        // 10 != 20
+        let tokens = [
+            Token::new(
+                TokenKind::Number,
+                "10",
+                Some(token::Literal::from(10)),
+                1,
+                1,
+            ),
+            Token::new(TokenKind::BangEqual, "!=", None, 1, 1),
+            Token::new(
+                TokenKind::Number,
+                "20",
+                Some(token::Literal::from(20)),
+                1,
+                1,
+            ),
+        ];
+
+        let mut parser = Parser::from_tokens(&tokens);
+        let expr = parser.expression::<String, AstPrinter>().unwrap();
+
+        match expr.kind() {
+            ExprKind::Binary(binary) => assert_eq!(binary.operator.kind, TokenKind::BangEqual),
+            _ => panic!("expected a binary expression"),
+        }
+        assert!(parser.is_at_end());
+    }
+
+    #[test]
+    fn parse_assignment_right_associative() {
+        use crate::ast::expr::ExprKind;
+
+        // a = b = c
+        let a = Token::new(TokenKind::Identifier, "a", None, 1, 1);
+        let b = Token::new(TokenKind::Identifier, "b", None, 1, 1);
+        let c = Token::new(TokenKind::Identifier, "c", None, 1, 1);
+        let equal = Token::new(TokenKind::Equal, "=", None, 1, 1);
+        let tokens = [a, equal.clone(), b, equal, c];
+
+        let mut parser = Parser::from_tokens(&tokens);
+        let expr = parser.expression::<String, AstPrinter>().unwrap();
+
+        let outer = match expr.kind() {
+            ExprKind::Assign(assign) => assign,
+            _ => panic!("expected an assignment"),
+        };
+        assert_eq!(&*outer.name.lexeme, "a");
+
+        let inner = match outer.value.kind() {
+            ExprKind::Assign(assign) => assign,
+            _ => panic!("expected `b = c` to be the assigned value"),
+        };
+        assert_eq!(&*inner.name.lexeme, "b");
+        assert!(matches!(inner.value.kind(), ExprKind::Variable(v) if &*v.name.lexeme == "c"));
+    }
+
+    #[test]
+    fn parse_plus_minus_star_slash_equal() {
+        for (compound, operator, kind) in [
+            ("+=", "+", TokenKind::PlusEqual),
+            ("-=", "-", TokenKind::MinusEqual),
+            ("*=", "*", TokenKind::StarEqual),
+            ("/=", "/", TokenKind::SlashEqual),
+        ] {
+            assert_compound_assignment(
+                Token::new(kind.clone(), compound, None, 1, 1),
+                Token::new(
+                    super::compound_operator(&kind).unwrap(),
+                    operator,
+                    None,
+                    1,
+                    1,
+                ),
+            );
+        }
+    }
+
+    #[test]
+    fn parse_percent_equal() {
+        assert_compound_assignment(
+            Token::new(TokenKind::PercentEqual, "%=", None, 1, 1),
+            Token::new(TokenKind::Percent, "%", None, 1, 1),
+        );
+    }
+
+    #[test]
+    fn parse_star_star_equal() {
+        assert_compound_assignment(
+            Token::new(TokenKind::StarStarEqual, "**=", None, 1, 1),
+            Token::new(TokenKind::StarStar, "**", None, 1, 1),
+        );
+    }
+
     #[test]
     fn parse_complex_expression() {
         // 10 == 10
         let tokens = [
-            Token::new(TokenKind::Number, "10", Some(token::Literal::from(10)), 1),
-            Token::new(TokenKind::EqualEqual, "==", None, 1),
-            Token::new(TokenKind::Number, "10", Some(token::Literal::from(10)), 1),
+            Token::new(
+                TokenKind::Number,
+                "10",
+                Some(token::Literal::from(10)),
+                1,
+                1,
+            ),
+            Token::new(TokenKind::EqualEqual, "==", None, 1, 1),
+            Token::new(
+                TokenKind::Number,
+                "10",
+                Some(token::Literal::from(10)),
+                1,
+                1,
+            ),
         ];
 
         let mut parser = Parser::from_tokens(&tokens);
@@ -606,7 +1466,7 @@ mod parser_tests {
 
         let expected = Binary::new(
             Box::new(Literal::<String, AstPrinter>::new(token::Literal::from(10))),
-            Token::new(TokenKind::EqualEqual, "==", None, 1),
+            Token::new(TokenKind::EqualEqual, "==", None, 1, 1),
             Box::new(Literal::<String, AstPrinter>::new(token::Literal::from(10))),
         );
 
@@ -617,15 +1477,45 @@ mod parser_tests {
     fn parse_advance_expression() {
         // a == b == c == d == e
         let tokens = [
-            Token::new(TokenKind::String, "a", Some(token::Literal::from("a")), 1),
-            Token::new(TokenKind::EqualEqual, "==", None, 1),
-            Token::new(TokenKind::String, "b", Some(token::Literal::from("b")), 1),
-            Token::new(TokenKind::EqualEqual, "==", None, 1),
-            Token::new(TokenKind::String, "c", Some(token::Literal::from("c")), 1),
-            Token::new(TokenKind::EqualEqual, "==", None, 1),
-            Token::new(TokenKind::String, "d", Some(token::Literal::from("d")), 1),
-            Token::new(TokenKind::EqualEqual, "==", None, 1),
-            Token::new(TokenKind::String, "e", Some(token::Literal::from("e")), 1),
+            Token::new(
+                TokenKind::String,
+                "a",
+                Some(token::Literal::from("a")),
+                1,
+                1,
+            ),
+            Token::new(TokenKind::EqualEqual, "==", None, 1, 1),
+            Token::new(
+                TokenKind::String,
+                "b",
+                Some(token::Literal::from("b")),
+                1,
+                1,
+            ),
+            Token::new(TokenKind::EqualEqual, "==", None, 1, 1),
+            Token::new(
+                TokenKind::String,
+                "c",
+                Some(token::Literal::from("c")),
+                1,
+                1,
+            ),
+            Token::new(TokenKind::EqualEqual, "==", None, 1, 1),
+            Token::new(
+                TokenKind::String,
+                "d",
+                Some(token::Literal::from("d")),
+                1,
+                1,
+            ),
+            Token::new(TokenKind::EqualEqual, "==", None, 1, 1),
+            Token::new(
+                TokenKind::String,
+                "e",
+                Some(token::Literal::from("e")),
+                1,
+                1,
+            ),
         ];
 
         let mut parser = Parser::from_tokens(&tokens);
@@ -640,22 +1530,22 @@ mod parser_tests {
                         Box::new(Literal::<String, AstPrinter>::new(token::Literal::from(
                             "a",
                         ))),
-                        Token::new(TokenKind::EqualEqual, "==", None, 1),
+                        Token::new(TokenKind::EqualEqual, "==", None, 1, 1),
                         Box::new(Literal::<String, AstPrinter>::new(token::Literal::from(
                             "b",
                         ))),
                     )),
-                    Token::new(TokenKind::EqualEqual, "==", None, 1),
+                    Token::new(TokenKind::EqualEqual, "==", None, 1, 1),
                     Box::new(Literal::<String, AstPrinter>::new(token::Literal::from(
                         "c",
                     ))),
                 )),
-                Token::new(TokenKind::EqualEqual, "==", None, 1),
+                Token::new(TokenKind::EqualEqual, "==", None, 1, 1),
                 Box::new(Literal::<String, AstPrinter>::new(token::Literal::from(
                     "d",
                 ))),
             )),
-            Token::new(TokenKind::EqualEqual, "==", None, 1),
+            Token::new(TokenKind::EqualEqual, "==", None, 1, 1),
             Box::new(Literal::<String, AstPrinter>::new(token::Literal::from(
                 "e",
             ))),
@@ -668,17 +1558,35 @@ mod parser_tests {
     fn parse_extreme_expression() {
         // (a + b) * (10 / 2)
         let tokens = [
-            Token::new(TokenKind::LeftParen, "(", None, 1),
-            Token::new(TokenKind::String, "a", Some(token::Literal::from("a")), 1),
-            Token::new(TokenKind::Plus, "+", None, 1),
-            Token::new(TokenKind::String, "b", Some(token::Literal::from("b")), 1),
-            Token::new(TokenKind::RightParen, ")", None, 1),
-            Token::new(TokenKind::Star, "*", None, 1),
-            Token::new(TokenKind::LeftParen, "(", None, 1),
-            Token::new(TokenKind::Number, "10", Some(token::Literal::from(10)), 1),
-            Token::new(TokenKind::Slash, "/", None, 1),
-            Token::new(TokenKind::Number, "2", Some(token::Literal::from(2)), 1),
-            Token::new(TokenKind::RightParen, ")", None, 1),
+            Token::new(TokenKind::LeftParen, "(", None, 1, 1),
+            Token::new(
+                TokenKind::String,
+                "a",
+                Some(token::Literal::from("a")),
+                1,
+                1,
+            ),
+            Token::new(TokenKind::Plus, "+", None, 1, 1),
+            Token::new(
+                TokenKind::String,
+                "b",
+                Some(token::Literal::from("b")),
+                1,
+                1,
+            ),
+            Token::new(TokenKind::RightParen, ")", None, 1, 1),
+            Token::new(TokenKind::Star, "*", None, 1, 1),
+            Token::new(TokenKind::LeftParen, "(", None, 1, 1),
+            Token::new(
+                TokenKind::Number,
+                "10",
+                Some(token::Literal::from(10)),
+                1,
+                1,
+            ),
+            Token::new(TokenKind::Slash, "/", None, 1, 1),
+            Token::new(TokenKind::Number, "2", Some(token::Literal::from(2)), 1, 1),
+            Token::new(TokenKind::RightParen, ")", None, 1, 1),
         ];
 
         let mut parser = Parser::from_tokens(&tokens);
@@ -691,15 +1599,15 @@ mod parser_tests {
                 Box::new(Literal::<String, AstPrinter>::new(token::Literal::from(
                     "a",
                 ))),
-                Token::new(TokenKind::Plus, "+", None, 1),
+                Token::new(TokenKind::Plus, "+", None, 1, 1),
                 Box::new(Literal::<String, AstPrinter>::new(token::Literal::from(
                     "b",
                 ))),
             )),
-            Token::new(TokenKind::Star, "*", None, 1),
+            Token::new(TokenKind::Star, "*", None, 1, 1),
             Box::new(Binary::<String, AstPrinter>::new(
                 Box::new(Literal::<String, AstPrinter>::new(token::Literal::from(10))),
-                Token::new(TokenKind::Slash, "/", None, 1),
+                Token::new(TokenKind::Slash, "/", None, 1, 1),
                 Box::new(Literal::<String, AstPrinter>::new(token::Literal::from(2))),
             )),
         );
@@ -711,8 +1619,8 @@ mod parser_tests {
     fn error_parsing_incomplete_expression() {
         // 1 +
         let tokens = [
-            Token::new(TokenKind::Number, "1", Some(token::Literal::from(1)), 1),
-            Token::new(TokenKind::Plus, "+", None, 1),
+            Token::new(TokenKind::Number, "1", Some(token::Literal::from(1)), 1, 1),
+            Token::new(TokenKind::Plus, "+", None, 1, 1),
         ];
 
         let mut parser = Parser::from_tokens(&tokens);
@@ -725,15 +1633,16 @@ mod parser_tests {
     fn parse_print_unary_statement() {
         // print "one";
         let tokens = [
-            Token::new(TokenKind::Print, "print", None, 1),
+            Token::new(TokenKind::Print, "print", None, 1, 1),
             Token::new(
                 TokenKind::String,
                 "one",
                 Some(token::Literal::from("one")),
                 1,
+                1,
             ),
-            Token::new(TokenKind::SemiColon, ";", None, 1),
-            Token::new(TokenKind::Eof, "", None, 1),
+            Token::new(TokenKind::SemiColon, ";", None, 1, 1),
+            Token::new(TokenKind::Eof, "", None, 1, 1),
         ];
 
         let mut parser = Parser::from_tokens(&tokens);
@@ -745,20 +1654,38 @@ mod parser_tests {
     fn parse_expr_statement() {
         // print (a + b) * (10 / 2);
         let tokens = [
-            Token::new(TokenKind::Print, "print", None, 1),
-            Token::new(TokenKind::LeftParen, "(", None, 1),
-            Token::new(TokenKind::String, "a", Some(token::Literal::from("a")), 1),
-            Token::new(TokenKind::Plus, "+", None, 1),
-            Token::new(TokenKind::String, "b", Some(token::Literal::from("b")), 1),
-            Token::new(TokenKind::RightParen, ")", None, 1),
-            Token::new(TokenKind::Star, "*", None, 1),
-            Token::new(TokenKind::LeftParen, "(", None, 1),
-            Token::new(TokenKind::Number, "10", Some(token::Literal::from(10)), 1),
-            Token::new(TokenKind::Slash, "/", None, 1),
-            Token::new(TokenKind::Number, "2", Some(token::Literal::from(2)), 1),
-            Token::new(TokenKind::RightParen, ")", None, 1),
-            Token::new(TokenKind::SemiColon, ";", None, 1),
-            Token::new(TokenKind::Eof, "", None, 1),
+            Token::new(TokenKind::Print, "print", None, 1, 1),
+            Token::new(TokenKind::LeftParen, "(", None, 1, 1),
+            Token::new(
+                TokenKind::String,
+                "a",
+                Some(token::Literal::from("a")),
+                1,
+                1,
+            ),
+            Token::new(TokenKind::Plus, "+", None, 1, 1),
+            Token::new(
+                TokenKind::String,
+                "b",
+                Some(token::Literal::from("b")),
+                1,
+                1,
+            ),
+            Token::new(TokenKind::RightParen, ")", None, 1, 1),
+            Token::new(TokenKind::Star, "*", None, 1, 1),
+            Token::new(TokenKind::LeftParen, "(", None, 1, 1),
+            Token::new(
+                TokenKind::Number,
+                "10",
+                Some(token::Literal::from(10)),
+                1,
+                1,
+            ),
+            Token::new(TokenKind::Slash, "/", None, 1, 1),
+            Token::new(TokenKind::Number, "2", Some(token::Literal::from(2)), 1, 1),
+            Token::new(TokenKind::RightParen, ")", None, 1, 1),
+            Token::new(TokenKind::SemiColon, ";", None, 1, 1),
+            Token::new(TokenKind::Eof, "", None, 1, 1),
         ];
 
         let mut parser = Parser::from_tokens(&tokens);
@@ -771,17 +1698,314 @@ mod parser_tests {
     fn parse_expr_statements() {
         // print 1 + 2;
         let tokens = [
-            Token::new(TokenKind::Print, "print", None, 1),
-            Token::new(TokenKind::Number, "1", Some(token::Literal::from(1)), 1),
-            Token::new(TokenKind::Plus, "+", None, 1),
-            Token::new(TokenKind::Number, "2", Some(token::Literal::from(2)), 1),
-            Token::new(TokenKind::SemiColon, ";", None, 1),
-            Token::new(TokenKind::Eof, "", None, 1),
+            Token::new(TokenKind::Print, "print", None, 1, 1),
+            Token::new(TokenKind::Number, "1", Some(token::Literal::from(1)), 1, 1),
+            Token::new(TokenKind::Plus, "+", None, 1, 1),
+            Token::new(TokenKind::Number, "2", Some(token::Literal::from(2)), 1, 1),
+            Token::new(TokenKind::SemiColon, ";", None, 1, 1),
+            Token::new(TokenKind::Eof, "", None, 1, 1),
         ];
 
         let mut parser = Parser::from_tokens(&tokens);
         let statements = parser.parse::<String, AstPrinter, AstPrinter>().unwrap();
-        let print = statements.get(0);
+        let print = statements.first();
         assert_eq!(print.unwrap().to_string(), format!("1 + 2"))
     }
+
+    #[test]
+    fn error_parsing_unterminated_block() {
+        // { print 1;
+        let tokens = [
+            Token::new(TokenKind::LeftBrace, "{", None, 1, 1),
+            Token::new(TokenKind::Print, "print", None, 2, 1),
+            Token::new(TokenKind::Number, "1", Some(token::Literal::from(1)), 2, 1),
+            Token::new(TokenKind::SemiColon, ";", None, 2, 1),
+            Token::new(TokenKind::Eof, "", None, 3, 1),
+        ];
+
+        let mut parser = Parser::from_tokens(&tokens);
+        assert!(parser.match_token(&[TokenKind::LeftBrace]));
+
+        match parser.block::<String, AstPrinter, AstPrinter>() {
+            Err(ErrorCode::ParserError(token, message)) => {
+                assert_eq!(message, "Expect '}' after block.");
+                assert_eq!(token.kind, TokenKind::LeftBrace);
+                assert_eq!(token.line, 1);
+            }
+            _ => panic!("expected an unterminated block error"),
+        }
+    }
+
+    #[test]
+    fn parse_with_diagnostics_recovers_from_errors() {
+        let tokens = ScannerBuilder::default()
+            .source("let a = 1; @@@ print a;")
+            .build()
+            .scan_tokens()
+            .unwrap();
+
+        let mut parser = Parser::from_tokens(&tokens);
+        let (statements, diagnostics) =
+            parser.parse_with_diagnostics::<String, AstPrinter, AstPrinter>();
+
+        assert_eq!(statements.len(), 2);
+        assert_eq!(diagnostics.len(), 1);
+        match &diagnostics[0] {
+            ErrorCode::ParserError(token, message) => {
+                assert_eq!(&*token.lexeme, "@@@");
+                assert_eq!(message, "Unexpected character: @@@.");
+            }
+            _ => panic!("expected a parser error"),
+        }
+    }
+
+    #[test]
+    fn error_return_outside_of_function() {
+        let parse = |source: &str| {
+            let tokens = ScannerBuilder::default()
+                .source(source)
+                .build()
+                .scan_tokens()
+                .unwrap();
+            Parser::from_tokens(&tokens).parse_with_diagnostics::<String, AstPrinter, AstPrinter>()
+        };
+
+        let (statements, diagnostics) = parse("return 1;");
+        assert!(statements.is_empty());
+        match &diagnostics[..] {
+            [ErrorCode::ParserError(token, message)] => {
+                assert_eq!(token.kind, TokenKind::Return);
+                assert_eq!(message, "'return' outside of a function");
+            }
+            _ => panic!("expected a single parser error"),
+        }
+
+        let (statements, diagnostics) = parse("fun f() { while (true) { return 1; } }");
+        assert_eq!(statements.len(), 1);
+        assert!(diagnostics.is_empty());
+    }
+
+    #[test]
+    fn warn_unreachable_code_after_return() {
+        let tokens = ScannerBuilder::default()
+            .source("fun f() { return 1; print 2; print 3; }")
+            .build()
+            .scan_tokens()
+            .unwrap();
+
+        Reporter::reset_counts();
+        let (statements, diagnostics) =
+            Parser::from_tokens(&tokens).parse_with_diagnostics::<String, AstPrinter, AstPrinter>();
+
+        assert_eq!(statements.len(), 1);
+        assert!(diagnostics.is_empty());
+        assert_eq!(Reporter::warning_count(), 1);
+        assert_eq!(Reporter::error_count(), 0);
+    }
+
+    #[test]
+    fn attach_leading_comments_to_declarations() {
+        use crate::ast::stmt::StmtKind;
+
+        let source = "// doc\nlet a = 1;\n// adds\n// two numbers\nfun add(a, b) { return a + b; }\nlet b = 2; // trailing\nlet c = 3;";
+        let tokens = ScannerBuilder::default()
+            .source(source)
+            .keep_comments(true)
+            .build()
+            .scan_tokens()
+            .unwrap();
+        let statements = Parser::from_tokens(&tokens)
+            .parse::<String, AstPrinter, AstPrinter>()
+            .unwrap();
+
+        let comments: Vec<Option<String>> = statements
+            .iter()
+            .map(|statement| match statement.kind() {
+                StmtKind::Let(declaration) => declaration.leading_comment.clone(),
+                StmtKind::Function(function) => function.leading_comment.clone(),
+                _ => panic!("expected a declaration"),
+            })
+            .collect();
+
+        assert_eq!(
+            comments,
+            vec![
+                Some("doc".to_string()),
+                Some("adds\ntwo numbers".to_string()),
+                None,
+                None
+            ]
+        );
+    }
+
+    fn diagnostics(source: &str) -> Vec<ErrorCode> {
+        let tokens = ScannerBuilder::default()
+            .source(source)
+            .build()
+            .scan_tokens()
+            .unwrap();
+        let (_, diagnostics) =
+            Parser::from_tokens(&tokens).parse_with_diagnostics::<String, AstPrinter, AstPrinter>();
+        diagnostics
+    }
+
+    #[test]
+    fn error_stray_closing_paren() {
+        match diagnostics("print 1;\n);").as_slice() {
+            [ErrorCode::ParserError(token, message)] => {
+                assert_eq!(message, "Unexpected closing ')'");
+                assert_eq!(token.kind, TokenKind::RightParen);
+                assert_eq!(token.line, 2);
+            }
+            _ => panic!("expected a single stray ')' error"),
+        }
+    }
+
+    #[test]
+    fn error_compound_assignment_to_non_variable() {
+        match diagnostics("let a = [1];\na[0] += 1;").as_slice() {
+            [ErrorCode::ParserError(token, message)] => {
+                assert_eq!(message, "Invalid assignment target.");
+                assert_eq!(token.kind, TokenKind::PlusEqual);
+                assert_eq!(token.line, 2);
+            }
+            _ => panic!("expected a single invalid target error"),
+        }
+    }
+
+    #[test]
+    fn error_equality_without_left_operand() {
+        for source in ["print != 1;", "print == 1;"] {
+            match diagnostics(source).as_slice() {
+                [ErrorCode::ParserError(token, message), ..] => {
+                    assert_eq!(message, "Expect expression.");
+                    assert_eq!(token.line, 1);
+                }
+                _ => panic!("expected a missing expression error for {}", source),
+            }
+        }
+    }
+
+    #[test]
+    fn error_stray_closing_brace() {
+        match diagnostics("let a = 1; }\nprint a;").as_slice() {
+            [ErrorCode::ParserError(token, message)] => {
+                assert_eq!(message, "Unexpected closing '}'");
+                assert_eq!(token.kind, TokenKind::RightBrace);
+                assert_eq!(token.line, 1);
+            }
+            _ => panic!("expected a single stray brace error"),
+        }
+
+        match diagnostics("let a = [1];]").as_slice() {
+            [ErrorCode::ParserError(_, message)] => {
+                assert_eq!(message, "Unexpected closing ']'")
+            }
+            _ => panic!("expected a single stray ']' error"),
+        }
+    }
+
+    #[test]
+    fn parse_logical_precedence() {
+        // a or b and c == d
+        let tokens = ScannerBuilder::default()
+            .source("a or b and c == d")
+            .build()
+            .scan_tokens()
+            .unwrap();
+
+        let mut parser = Parser::from_tokens(&tokens);
+        let expr = parser.expression::<String, AstPrinter>().unwrap();
+        let mut printer = AstPrinter {};
+        assert_eq!(
+            printer.print_expr(expr.as_ref()),
+            "(or identifier (and identifier (== identifier identifier)))"
+        );
+    }
+
+    #[test]
+    fn parse_lambda_expressions() {
+        let tokens = ScannerBuilder::default()
+            .source("fun(a, b) { return a; }; (a) => a; () => 1; (a)")
+            .build()
+            .scan_tokens()
+            .unwrap();
+
+        let mut parser = Parser::from_tokens(&tokens);
+        let mut printer = AstPrinter {};
+        let mut next = |parser: &mut Parser| {
+            let expr = parser.expression::<String, AstPrinter>().unwrap();
+            parser.match_token(&[TokenKind::SemiColon]);
+            printer.print_expr(expr.as_ref())
+        };
+
+        assert_eq!(next(&mut parser), "(fun (a b) return identifier)");
+        assert_eq!(next(&mut parser), "(fun (a) return identifier)");
+        assert_eq!(next(&mut parser), "(fun () return 1)");
+        // a parenthesized name without an arrow stays a grouping
+        assert_eq!(next(&mut parser), "(group identifier)");
+    }
+
+    #[test]
+    fn error_named_lambda() {
+        match diagnostics("let f = fun g() {};").as_slice() {
+            [ErrorCode::ParserError(_, message), ..] => {
+                assert_eq!(message, "Expect '(' after 'fun'.")
+            }
+            _ => panic!("expected a missing parenthesis error"),
+        }
+    }
+
+    #[test]
+    fn error_parse_collects_every_syntax_error() {
+        let tokens = ScannerBuilder::default()
+            .source("let = 1;\nprint 1;\nprint (2;\nprint 3;")
+            .build()
+            .scan_tokens()
+            .unwrap();
+
+        let result = Parser::from_tokens(&tokens).parse::<String, AstPrinter, AstPrinter>();
+        match result {
+            Err(ErrorCode::ParserErrors(errors)) => {
+                let lines: Vec<_> = errors
+                    .iter()
+                    .map(|e| match e {
+                        ErrorCode::ParserError(token, _) => token.line,
+                        _ => panic!("expected parser errors only"),
+                    })
+                    .collect();
+                assert_eq!(lines, vec![1, 3]);
+            }
+            _ => panic!("expected the syntax errors"),
+        }
+    }
+
+    #[test]
+    fn error_keyword_as_variable_name() {
+        match diagnostics("let if = 1;").as_slice() {
+            [ErrorCode::ParserError(token, message), ..] => {
+                assert_eq!(&*token.lexeme, "if");
+                assert_eq!(
+                    message,
+                    "'if' is a reserved keyword and cannot be used as a variable name"
+                );
+            }
+            _ => panic!("expected a reserved keyword error"),
+        }
+
+        match diagnostics("const nil = 1;").as_slice() {
+            [ErrorCode::ParserError(_, message), ..] => assert_eq!(
+                message,
+                "'nil' is a reserved keyword and cannot be used as a variable name"
+            ),
+            _ => panic!("expected a reserved keyword error"),
+        }
+
+        match diagnostics("let 1 = 1;").as_slice() {
+            [ErrorCode::ParserError(_, message), ..] => {
+                assert_eq!(message, "Expect variable name.")
+            }
+            _ => panic!("expected a missing name error"),
+        }
+    }
 }
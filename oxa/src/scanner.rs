@@ -1,14 +1,20 @@
 use crate::errors::{reporter::Reporter, ErrorCode};
+use crate::shared::Handle;
 use crate::token::{Literal, Token, TokenKind, KEYWORDS};
 
+use std::collections::HashSet;
 use std::str::FromStr;
 
 #[derive(Default)]
 pub struct ScannerBuilder {
     source: String,
-    line: usize,
+    line: Option<usize>,
     current: usize,
     start: usize,
+    auto_semicolon: bool,
+    single_quote_strings: Option<bool>,
+    intern_identifiers: bool,
+    keep_comments: bool,
 }
 
 impl ScannerBuilder {
@@ -17,8 +23,9 @@ impl ScannerBuilder {
         self
     }
 
+    /// line of the first character of the source, lines start at 1 by default
     pub fn _line(mut self, line: usize) -> ScannerBuilder {
-        self.line = line;
+        self.line = Some(line);
         self
     }
 
@@ -32,8 +39,48 @@ impl ScannerBuilder {
         self
     }
 
+    /// Treat a line break as a statement terminator, a `;` is inserted at the end of any line
+    /// whose last token can end a statement. Disabled by default.
+    pub fn auto_semicolon(mut self, enabled: bool) -> ScannerBuilder {
+        self.auto_semicolon = enabled;
+        self
+    }
+
+    /// Accept `'...'` as a string literal, when disabled a single-quoted string is reported as
+    /// a scan error. Enabled by default.
+    pub fn single_quote_strings(mut self, enabled: bool) -> ScannerBuilder {
+        self.single_quote_strings = Some(enabled);
+        self
+    }
+
+    /// Share a single allocation between the tokens of equal identifiers and keywords, saves
+    /// memory in sources repeating the same names. Disabled by default.
+    pub fn intern_identifiers(mut self, enabled: bool) -> ScannerBuilder {
+        self.intern_identifiers = enabled;
+        self
+    }
+
+    /// Emit a `TokenKind::Comment` token for every `//` comment instead of skipping it, lets
+    /// the parser attach comments to the declaration below them. Disabled by default.
+    pub fn keep_comments(mut self, enabled: bool) -> ScannerBuilder {
+        self.keep_comments = enabled;
+        self
+    }
+
     pub fn build(self) -> Scanner {
-        Scanner::new(&self.source, self.start, self.current, self.line)
+        let mut scanner = Scanner::new(
+            &self.source,
+            self.start,
+            self.current,
+            self.line.unwrap_or(1),
+        );
+        scanner.auto_semicolon = self.auto_semicolon;
+        scanner.single_quote_strings = self.single_quote_strings.unwrap_or(true);
+        scanner.keep_comments = self.keep_comments;
+        if self.intern_identifiers {
+            scanner.interned = Some(HashSet::new());
+        }
+        scanner
     }
 }
 
@@ -45,6 +92,20 @@ pub struct Scanner {
     start: usize,
     current: usize,
     line: usize,
+    /// line of the `start` of the lexeme, tokens spanning several lines are located where they
+    /// begin
+    start_line: usize,
+    auto_semicolon: bool,
+    single_quote_strings: bool,
+    keep_comments: bool,
+    errors: Vec<ErrorCode>,
+    /// lexemes of identifiers and keywords already seen, `None` unless interning is enabled
+    interned: Option<HashSet<Handle<str>>>,
+    /// true if the source only holds ascii chars
+    ascii: bool,
+    /// byte offset and column of the last position asked to `column()`, the next token's column
+    /// is counted from there rather than from the start of its line which keeps long lines linear
+    last_column: Option<(usize, usize)>,
 }
 
 /// Constructor implementation
@@ -56,6 +117,8 @@ impl Scanner {
             start,
             current,
             line,
+            start_line: line,
+            ascii: source.is_ascii(),
             ..Self::default()
         }
     }
@@ -72,13 +135,31 @@ impl Scanner {
         while !self.is_at_end() {
             // Start from the beginning of the next lexeme
             self.start = self.current;
+            self.start_line = self.line;
             self.process_next_token()?;
         }
 
+        if self.should_insert_semicolon() {
+            let column = self.column(self.current);
+            self.tokens.push(Token::new(
+                TokenKind::SemiColon,
+                "",
+                None,
+                self.line,
+                column,
+            ));
+        }
+
+        let column = self.column(self.current);
         self.tokens
-            .push(Token::new(TokenKind::Eof, "", None, self.line));
+            .push(Token::new(TokenKind::Eof, "", None, self.line, column));
         Ok(self.tokens.clone())
     }
+
+    /// Errors found in the source, scanning carries on after an error so every error is kept
+    pub fn errors(&self) -> &[ErrorCode] {
+        &self.errors
+    }
 }
 
 /// Internal method implementation
@@ -88,6 +169,7 @@ impl Scanner {
             Some(c) => {
                 // Note: The match order is done with priority to avoid matching to the wrong token
                 if self.process_comparator_char_token(c)
+                    || self.process_arithmetic_char_token(c)
                     || self.process_comment_char_token(c)
                     || self.process_identifier_token(c)
                     || self.process_numeric_token(c)
@@ -95,6 +177,7 @@ impl Scanner {
                     || self.process_single_char_token(c)
                     || self.process_keyword_token(c)
                     || self.process_ignored_char(c)
+                    || self.process_unexpected_chars(c)
                 {
                     // Do nothing if the operation succeeds
                 }
@@ -108,6 +191,15 @@ impl Scanner {
     }
 
     fn process_comparator_char_token(&mut self, c: char) -> bool {
+        if !matches!(c, '!' | '=' | '<' | '>') {
+            return false;
+        }
+
+        if c == '=' && self.next_match_char('>') {
+            self.add_token(TokenKind::Arrow, None);
+            return true;
+        }
+
         let next_match_equal = self.next_match_char('=');
         match c {
             '!' => self.add_token(
@@ -150,82 +242,124 @@ impl Scanner {
         true
     }
 
+    /// matches the arithmetic operators made of more than one char, `*`, `**`, `**=`, `%`, `%=`
+    /// and the compound assignments `+=`, `-=`, `*=`, `/=`
+    fn process_arithmetic_char_token(&mut self, c: char) -> bool {
+        let kind = match c {
+            '+' if self.next_match_char('=') => TokenKind::PlusEqual,
+            '-' if self.next_match_char('=') => TokenKind::MinusEqual,
+            '/' if self.next_match_char('=') => TokenKind::SlashEqual,
+            '*' if self.next_match_char('*') => {
+                if self.next_match_char('=') {
+                    TokenKind::StarStarEqual
+                } else {
+                    TokenKind::StarStar
+                }
+            }
+            '*' if self.next_match_char('=') => TokenKind::StarEqual,
+            '*' => TokenKind::Star,
+            '%' if self.next_match_char('=') => TokenKind::PercentEqual,
+            '%' => TokenKind::Percent,
+            _ => return false,
+        };
+
+        self.add_token(kind, None);
+        true
+    }
+
     fn process_comment_char_token(&mut self, c: char) -> bool {
-        // TODO: Provide support for multi line comment /* .... */
         if c != '/' {
             return false;
         }
 
-        match self.next_match_char('/') {
-            true => {
-                // A comment goes until the end of the line.
-                while !self.is_at_end() && self.peek(0).is_some() {
-                    if let Some(v) = self.peek(0) {
-                        if v != '\n' {
-                            self.advance();
-                        }
+        if self.next_match_char('/') {
+            // A comment goes until the end of the line.
+            while !self.is_at_end() && self.peek(0).is_some_and(|v| v != '\n') {
+                self.advance();
+            }
+        } else if self.next_match_char('*') {
+            if !self.consume_block_comment() {
+                return true;
+            }
+        } else {
+            return false;
+        }
+
+        if self.keep_comments {
+            self.add_token(TokenKind::Comment, None);
+        }
+        true
+    }
+
+    /// consumes a `/* ... */` comment up to its closing `*/`, returns false and reports an error
+    /// if the source ends first. Block comments nest so commenting out code which already holds a
+    /// block comment works, `/* outer /* inner */ still comment */`.
+    fn consume_block_comment(&mut self) -> bool {
+        let line = self.line;
+        let mut depth = 1;
+        while !self.is_at_end() {
+            match self.advance() {
+                Some('/') if self.next_match_char('*') => depth += 1,
+                Some('*') if self.next_match_char('/') => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return true;
                     }
                 }
-                true
+                Some('\n') => self.line += 1,
+                _ => {}
             }
-            false => false,
         }
+
+        let end = self.line;
+        self.line = line;
+        self.error("Unterminated block comment.");
+        self.line = end;
+        false
     }
 
     fn process_string_token(&mut self, c: char) -> bool {
-        let string: &str = match c {
-            '"' => {
-                while !self.is_at_end() {
-                    if let Some(p) = self.peek(0) {
-                        match p {
-                            '"' => break,
-                            '\n' => self.line += 1,
-                            _ => {}
-                        }
-                    }
-                    self.advance();
-                }
-                if self.is_at_end() {
-                    log::warn!("Unexpected character: unterminated string.");
-                    Reporter::line_error(self.line, "Unexpected character: unterminated string.");
-                    return false;
-                }
+        let quote = match c {
+            '"' => '"',
+            '\'' => '\'',
+            _ => return false,
+        };
 
-                // The closing ".
+        let line = self.line;
+        while !self.is_at_end() && self.peek(0) != Some(quote) {
+            // an escaped quote doesn't close the string, a trailing `\` is left for the loop to
+            // stop at the end of the source
+            if self.peek(0) == Some('\\') && self.char_ahead(1).is_some() {
                 self.advance();
-
-                // Trim the surrounding quotes.
-                &self.source[self.start + 1..self.current - 1]
             }
-            '\'' => {
-                while !self.is_at_end() {
-                    if let Some(p) = self.peek(0) {
-                        match p {
-                            '\'' => break,
-                            '\n' => self.line += 1,
-                            _ => {}
-                        }
-                    }
-                    self.advance();
-                }
-                if self.is_at_end() {
-                    log::warn!("Unexpected character: unterminated string.");
-                    Reporter::line_error(self.line, "Unexpected character: unterminated string.");
-                    return false;
-                }
+            if self.peek(0) == Some('\n') {
+                self.line += 1;
+            }
+            self.advance();
+        }
 
-                // The closing ".
-                self.advance();
+        if self.is_at_end() {
+            self.recover_unterminated_string(line);
+            return true;
+        }
 
-                // Trim the surrounding quotes.
-                &self.source[self.start + 1..self.current - 1]
-            }
-            _ => {
-                return false;
+        // The closing quote.
+        self.advance();
+
+        if quote == '\'' && !self.single_quote_strings {
+            self.error("Single-quoted strings are disabled");
+            return true;
+        }
+
+        // Trim the surrounding quotes.
+        let string = match unescape(&self.source[self.start + 1..self.current - 1]) {
+            Ok(string) => string,
+            Err(message) => {
+                self.error(&message);
+                return true;
             }
         };
-
-        match Literal::from_str(string) {
+        match Literal::from_str(&string) {
             Ok(l) => {
                 self.add_token(TokenKind::String, Some(l));
                 true
@@ -237,68 +371,144 @@ impl Scanner {
         }
     }
 
+    /// reports an unterminated string opened on `line` and resumes scanning on the next line, so
+    /// the rest of the source isn't swallowed by the string
+    fn recover_unterminated_string(&mut self, line: usize) {
+        self.line = line;
+        log::warn!("Unexpected character: unterminated string.");
+        self.error("Unexpected character: unterminated string.");
+
+        if let Some(offset) = self.source[self.start..].find('\n') {
+            self.current = self.start + offset;
+        }
+    }
+
+    /// numeric literal, digits of both the integer and the fractional part can be grouped with
+    /// underscores placed between two digits, `1_000` and `3.141_592`. An exponent makes it a
+    /// float, `1e3` and `2.5e-3`
     fn process_numeric_token(&mut self, c: char) -> bool {
         match c {
+            '0' if self
+                .peek(0)
+                .is_some_and(|c| matches!(c, 'x' | 'X' | 'b' | 'B')) =>
+            {
+                self.process_radix_token()
+            }
             c if c.is_ascii_digit() => {
-                'first_number: loop {
-                    let current = self.peek(0);
-                    if current.is_some() && current.unwrap().is_numeric() {
-                        self.advance();
-                    } else {
-                        break 'first_number;
-                    }
-                }
+                self.consume_digits();
 
                 // Look for a fractional part.
                 if let Some(v) = self.peek(0) {
                     if v == '.' {
                         let next = self.peek(1);
-                        if next.is_some() && next.unwrap().is_numeric() {
+                        if next.is_some_and(|c| c.is_ascii_digit() || c == '_') {
                             // Consume the "."
                             self.advance();
-
-                            'fractional_number: loop {
-                                let current = self.peek(0);
-                                if current.is_some() && current.unwrap().is_numeric() {
-                                    self.advance();
-                                } else {
-                                    break 'fractional_number;
-                                }
-                            }
+                            self.consume_digits();
                         }
                     }
                 }
 
-                let string = self.get_string();
-                match self.get_string().contains('.') {
-                    false => match string.parse::<i32>() {
-                        Ok(n) => {
-                            self.add_token(TokenKind::Number, Some(Literal::from(n)));
-                            true
-                        }
-                        _ => false,
-                    },
+                // Look for an exponent, the sign is optional but a digit has to follow
+                if self.peek(0).is_some_and(|c| c == 'e' || c == 'E') {
+                    let sign = self.peek(1).is_some_and(|c| c == '+' || c == '-');
+                    self.advance();
+                    if sign {
+                        self.advance();
+                    }
+                    if !self.peek(0).is_some_and(|c| c.is_ascii_digit()) {
+                        let lexeme = self.get_string();
+                        self.error(&format!(
+                            "Invalid number '{}': expect digits after the exponent.",
+                            lexeme
+                        ));
+                        return true;
+                    }
+                    self.consume_digits();
+                }
 
-                    true => match string.parse::<f32>() {
-                        Ok(f) => {
-                            self.add_token(TokenKind::Number, Some(Literal::from(f)));
-                            true
-                        }
-                        _ => false,
-                    },
+                let lexeme = self.get_string();
+                let bytes = lexeme.as_bytes();
+                let misplaced_underscore = bytes.iter().enumerate().any(|(i, &b)| {
+                    b == b'_'
+                        && !(bytes[i - 1].is_ascii_digit()
+                            && bytes.get(i + 1).is_some_and(u8::is_ascii_digit))
+                });
+                if misplaced_underscore {
+                    self.error(&format!(
+                        "Invalid number '{}': '_' must be between digits.",
+                        lexeme
+                    ));
+                    return true;
+                }
+
+                let string = lexeme.replace('_', "");
+                let literal = match string.contains(['.', 'e', 'E']) {
+                    false => string.parse::<i32>().ok().map(Literal::from),
+                    // a float too large for an f32 parses as infinity rather than failing
+                    true => string
+                        .parse::<f32>()
+                        .ok()
+                        .filter(|f| f.is_finite())
+                        .map(Literal::from),
+                };
+                match literal {
+                    Some(literal) => self.add_token(TokenKind::Number, Some(literal)),
+                    // an integer too large for an i32 or a float too large for an f32
+                    None => self.error(&format!("Invalid number '{}'.", lexeme)),
                 }
+                true
             }
             _ => false,
         }
     }
 
+    /// hexadecimal `0xFF` or binary `0b1010` integer literal, the prefix is already peeked
+    fn process_radix_token(&mut self) -> bool {
+        // Consume the prefix and every alphanumeric after it so `0b12` is reported as a whole
+        self.advance();
+        while self
+            .peek(0)
+            .is_some_and(|c| c.is_ascii_alphanumeric() || c == '_')
+        {
+            self.advance();
+        }
+
+        let lexeme = self.get_string();
+        let (radix, name) = match &lexeme[1..2] {
+            "x" | "X" => (16, "hexadecimal"),
+            _ => (2, "binary"),
+        };
+        let digits = &lexeme[2..];
+        let value = match digits.split('_').any(str::is_empty) {
+            true => None,
+            false => i32::from_str_radix(&digits.replace('_', ""), radix).ok(),
+        };
+
+        match value {
+            Some(n) => self.add_token(TokenKind::Number, Some(Literal::from(n))),
+            None => self.error(&format!("Invalid {} number '{}'.", name, lexeme)),
+        }
+        true
+    }
+
+    /// consumes a run of digits and underscores
+    fn consume_digits(&mut self) {
+        while self.peek(0).is_some_and(|c| c.is_ascii_digit() || c == '_') {
+            self.advance();
+        }
+    }
+
+    /// identifier or keyword, an identifier starts with a letter or `_` and goes on with letters,
+    /// digits and `_`, `is_even`, `_private`. Underscores followed by nothing but digits, `_5`,
+    /// are a misplaced number underscore
     fn process_identifier_token(&mut self, c: char) -> bool {
         match c {
-            c if c.is_alphabetic() => {
+            c if c.is_alphabetic() || c == '_' => {
                 // Consume next character until we reach a non alpha-numeric character
                 loop {
                     let current = self.peek(0);
-                    if current.is_some() && current.unwrap().is_alphanumeric() {
+                    if current.is_some_and(|c| c.is_alphanumeric() || c == '_') {
                         self.advance();
                     } else {
                         break;
@@ -307,6 +517,17 @@ impl Scanner {
 
                 // check if there is a reserved keyword
                 let string = self.get_string();
+                // `_5` reads as a number with a leading underscore rather than a name
+                let digits = string.trim_start_matches('_');
+                if digits.starts_with(|c: char| c.is_ascii_digit())
+                    && digits.chars().all(|c| c.is_ascii_digit() || c == '_')
+                {
+                    self.error(&format!(
+                        "Invalid number '{}': '_' must be between digits.",
+                        string
+                    ));
+                    return true;
+                }
                 if KEYWORDS.get(&string).is_some() {
                     return false;
                 }
@@ -324,13 +545,17 @@ impl Scanner {
             ')' => self.add_token(TokenKind::RightParen, None),
             '{' => self.add_token(TokenKind::LeftBrace, None),
             '}' => self.add_token(TokenKind::RightBrace, None),
+            '[' => self.add_token(TokenKind::LeftBracket, None),
+            ']' => self.add_token(TokenKind::RightBracket, None),
+            ':' => self.add_token(TokenKind::Colon, None),
             ',' => self.add_token(TokenKind::Comma, None),
             '.' => self.add_token(TokenKind::Dot, None),
             '-' => self.add_token(TokenKind::Minus, None),
             '+' => self.add_token(TokenKind::Plus, None),
             '/' => self.add_token(TokenKind::Slash, None),
-            '*' => self.add_token(TokenKind::Star, None),
             ';' => self.add_token(TokenKind::SemiColon, None),
+            // a `|` on its own isn't an operator and is left for `process_unexpected_chars`
+            '|' if self.next_match_char('>') => self.add_token(TokenKind::PipeGreater, None),
             _ => {
                 return false;
             }
@@ -369,6 +594,9 @@ impl Scanner {
             // Ignore whitespace.
             ' ' | '\r' | '\t' => true,
             '\n' => {
+                if self.should_insert_semicolon() {
+                    self.add_token(TokenKind::SemiColon, None);
+                }
                 self.line += 1;
                 true
             }
@@ -376,34 +604,145 @@ impl Scanner {
         }
     }
 
+    /// groups a run of unrecognised characters into a single `TokenKind::Error` token
+    fn process_unexpected_chars(&mut self, c: char) -> bool {
+        if !is_unexpected_char(c) {
+            return false;
+        }
+
+        // `peek` yields '\0' at the end of the source which would count as unexpected forever
+        while !self.is_at_end() && self.peek(0).is_some_and(is_unexpected_char) {
+            self.advance();
+        }
+
+        self.add_token(TokenKind::Error, None);
+        let message = format!("Unexpected character: {}.", self.get_string());
+        self.error(&message);
+        true
+    }
+
+    /// returns true if automatic semicolon insertion is enabled and the last scanned token can
+    /// end a statement, a line ending with an operator continues on the next line.
+    fn should_insert_semicolon(&self) -> bool {
+        if !self.auto_semicolon {
+            return false;
+        }
+
+        // a comment never ends a statement, look at the token before it
+        let last = self
+            .tokens
+            .iter()
+            .rev()
+            .find(|token| token.kind != TokenKind::Comment);
+        match last {
+            Some(token) => matches!(
+                token.kind,
+                TokenKind::Identifier
+                    | TokenKind::String
+                    | TokenKind::Number
+                    | TokenKind::True
+                    | TokenKind::False
+                    | TokenKind::Nil
+                    | TokenKind::This
+                    | TokenKind::Super
+                    | TokenKind::Return
+                    | TokenKind::RightParen
+                    | TokenKind::RightBracket
+            ),
+            None => false,
+        }
+    }
+
     fn add_token(&mut self, kind: TokenKind, literal: Option<Literal>) {
         // TODO: Try a less error prone approach to select the string slice
         // e.g collection with error validation for range
         let lexeme = self.get_string();
 
-        let token = Token::new(kind, &lexeme, literal, self.line);
+        let lexeme = match self.interned.as_mut() {
+            Some(interned) if kind == TokenKind::Identifier || KEYWORDS.contains_key(&lexeme) => {
+                match interned.get(lexeme.as_str()) {
+                    Some(shared) => Handle::clone(shared),
+                    None => {
+                        let shared: Handle<str> = Handle::from(lexeme);
+                        interned.insert(Handle::clone(&shared));
+                        shared
+                    }
+                }
+            }
+            _ => Handle::from(lexeme),
+        };
+
+        let column = self.column(self.start);
+        let token = Token::with_shared_lexeme(kind, lexeme, literal, self.start_line, column);
 
         self.tokens.push(token);
     }
 
+    /// reports the error where the current lexeme starts and keeps it for `errors()`
+    fn error(&mut self, message: &str) {
+        let column = self.column(self.start);
+        Reporter::line_error(self.start_line, column, message);
+        self.errors.push(ErrorCode::ScannerError(
+            self.start_line,
+            message.to_string(),
+        ));
+    }
+
+    /// 1-based column of the byte `offset` of the source, counted in chars from the last line
+    /// break before it
+    fn column(&mut self, offset: usize) -> usize {
+        let (from, column) = match self.last_column {
+            Some((from, column)) if from <= offset => (from, column),
+            _ => (0, 1),
+        };
+
+        let count = |s: &str| {
+            if self.ascii {
+                s.len()
+            } else {
+                s.chars().count()
+            }
+        };
+        let skipped = &self.source[from..offset];
+        let column = match skipped.rfind('\n') {
+            Some(i) => count(&skipped[i + 1..]) + 1,
+            None => column + count(skipped),
+        };
+        self.last_column = Some((offset, column));
+        column
+    }
+
     fn is_at_end(&self) -> bool {
         self.current >= self.source.len()
     }
 
     fn advance(&mut self) -> Option<char> {
-        self.increment_current();
-
-        // nth is zero-index based
-        return self.source.chars().nth(self.current - 1);
+        let c = self.char_ahead(0);
+        self.increment_current(c);
+        c
     }
 
     fn peek(&self, to: usize) -> Option<char> {
-        let to_index = self.current + to;
-
-        if self.is_at_end() || to_index >= self.source.len() {
+        if self.is_at_end() {
             return Some('\0');
         }
-        return self.source.chars().nth(to_index);
+
+        self.char_ahead(to).or(Some('\0'))
+    }
+
+    /// returns the char `to` chars after the current one, every char of an ascii source is a
+    /// single byte so it is indexed directly rather than decoded.
+    fn char_ahead(&self, to: usize) -> Option<char> {
+        if self.ascii {
+            return self
+                .source
+                .as_bytes()
+                .get(self.current + to)
+                .map(|&b| b as char);
+        }
+
+        // `current` is a byte offset, so look ahead by chars from there
+        self.source.get(self.current..)?.chars().nth(to)
     }
 
     fn next_match_char(&mut self, expected: char) -> bool {
@@ -411,21 +750,23 @@ impl Scanner {
             return false;
         }
 
-        match self.source.chars().nth(self.current) {
+        match self.char_ahead(0) {
             None => false,
             Some(c) => {
                 if c != expected {
                     return false;
                 }
 
-                self.increment_current();
+                self.increment_current(Some(c));
                 true
             }
         }
     }
 
-    fn increment_current(&mut self) {
-        self.current += 1;
+    /// moves past the consumed char, `start` and `current` are byte offsets so multi-byte chars
+    /// advance by their utf-8 length and lexemes can be sliced from the source
+    fn increment_current(&mut self, consumed: Option<char>) {
+        self.current += consumed.map_or(1, char::len_utf8);
     }
 
     fn get_string(&self) -> String {
@@ -434,9 +775,66 @@ impl Scanner {
     }
 }
 
+/// replaces the escape sequences of a string literal by the chars they stand for
+fn unescape(raw: &str) -> Result<String, String> {
+    let mut string = String::with_capacity(raw.len());
+    let mut chars = raw.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            string.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('n') => string.push('\n'),
+            Some('t') => string.push('\t'),
+            Some('r') => string.push('\r'),
+            Some(escaped @ ('\\' | '"' | '\'')) => string.push(escaped),
+            Some(escaped) => return Err(format!("Unknown escape sequence '\\{}'.", escaped)),
+            None => return Err("Unknown escape sequence '\\'.".to_string()),
+        }
+    }
+    Ok(string)
+}
+
+/// returns true if the char can't start or continue any token
+fn is_unexpected_char(c: char) -> bool {
+    // digits other than ascii ones can't start a number
+    !(c.is_alphabetic()
+        || c.is_ascii_digit()
+        || c.is_whitespace()
+        || matches!(
+            c,
+            '_' | '"'
+                | '\''
+                | '('
+                | ')'
+                | '{'
+                | '}'
+                | '['
+                | ']'
+                | ':'
+                | ','
+                | '.'
+                | '-'
+                | '+'
+                | '/'
+                | ';'
+                | '*'
+                | '%'
+                | '!'
+                | '='
+                | '<'
+                | '>'
+        ))
+}
+
 #[cfg(test)]
 mod scanner_tests {
     use super::*;
+    use crate::ast::printer::AstPrinter;
+    use crate::parser::Parser;
+    use crate::token::LiteralKind;
 
     #[test]
     fn test_no_token_with_initial_creation() {
@@ -464,7 +862,7 @@ mod scanner_tests {
         let mut scanner = ScannerBuilder::default().source("1").build();
         scanner.scan_tokens().unwrap();
         assert_eq!(scanner.tokens.len(), 2);
-        assert_eq!(scanner.tokens.get(0).unwrap().kind, TokenKind::Number);
+        assert_eq!(scanner.tokens.first().unwrap().kind, TokenKind::Number);
     }
 
     #[test]
@@ -472,7 +870,7 @@ mod scanner_tests {
         let mut scanner = ScannerBuilder::default().source("-1").build();
         scanner.scan_tokens().unwrap();
         assert_eq!(scanner.tokens.len(), 3);
-        assert_eq!(scanner.tokens.get(0).unwrap().kind, TokenKind::Minus);
+        assert_eq!(scanner.tokens.first().unwrap().kind, TokenKind::Minus);
         assert_eq!(scanner.tokens.get(1).unwrap().kind, TokenKind::Number);
     }
 
@@ -481,14 +879,33 @@ mod scanner_tests {
         let mut scanner = ScannerBuilder::default().source("1 + 2").build();
         scanner.scan_tokens().unwrap();
         assert_eq!(scanner.tokens.len(), 4);
-        assert_eq!(scanner.tokens.get(0).unwrap().kind, TokenKind::Number);
+        assert_eq!(scanner.tokens.first().unwrap().kind, TokenKind::Number);
         assert_eq!(scanner.tokens.get(1).unwrap().kind, TokenKind::Plus);
         assert_eq!(scanner.tokens.get(2).unwrap().kind, TokenKind::Number);
     }
 
+    #[test]
+    fn test_generates_token_for_pipe_greater() {
+        let mut scanner = ScannerBuilder::default().source("x |> f | y").build();
+        let tokens = scanner.scan_tokens().unwrap();
+        let kinds: Vec<_> = tokens.iter().map(|t| t.kind.clone()).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                TokenKind::Identifier,
+                TokenKind::PipeGreater,
+                TokenKind::Identifier,
+                TokenKind::Error,
+                TokenKind::Identifier,
+                TokenKind::Eof
+            ]
+        );
+        assert_eq!(scanner.errors().len(), 1);
+    }
+
     #[test]
     fn test_generates_token_for_multiple_single_char() {
-        let mut scanner = ScannerBuilder::default().source("(){},.-+/*;").build();
+        let mut scanner = ScannerBuilder::default().source("(){},.-+*/;").build();
         scanner.scan_tokens().unwrap();
         assert_eq!(scanner.tokens.len(), 12);
     }
@@ -507,6 +924,22 @@ mod scanner_tests {
         assert_eq!(scanner.tokens.len(), 5);
     }
 
+    #[test]
+    fn test_generates_token_for_arrow() {
+        let mut scanner = ScannerBuilder::default().source("=> = >").build();
+        let tokens = scanner.scan_tokens().unwrap();
+        let kinds: Vec<_> = tokens.iter().map(|t| t.kind.clone()).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                TokenKind::Arrow,
+                TokenKind::Equal,
+                TokenKind::Greater,
+                TokenKind::Eof
+            ]
+        );
+    }
+
     #[test]
     fn test_ignore_comment_characters() {
         let mut scanner = ScannerBuilder::default()
@@ -523,9 +956,80 @@ mod scanner_tests {
             .source("!*+-/=<> <= == // operators")
             .build();
         scanner.scan_tokens().unwrap();
+        // `/=` is a single compound assignment token
         assert_eq!(scanner.tokens.len(), 10);
     }
 
+    #[test]
+    fn test_ignore_block_comment() {
+        let mut scanner = ScannerBuilder::default().source("1 /* x */ + 2").build();
+        let tokens = scanner.scan_tokens().unwrap();
+        assert!(scanner.errors().is_empty());
+        assert_eq!(tokens.len(), 4);
+        assert_eq!(tokens[1].kind, TokenKind::Plus);
+    }
+
+    #[test]
+    fn test_ignore_multi_line_block_comment() {
+        let mut scanner = ScannerBuilder::default()
+            .source("/* first\n * second\n*/ a")
+            .build();
+        let tokens = scanner.scan_tokens().unwrap();
+        assert!(scanner.errors().is_empty());
+        assert_eq!(tokens.len(), 2);
+        assert_eq!(&*tokens[0].lexeme, "a");
+        assert_eq!(tokens[0].line, 3);
+    }
+
+    #[test]
+    fn test_ignore_nested_block_comments() {
+        let mut scanner = ScannerBuilder::default()
+            .source("/* outer /* inner /* innermost */ */\nstill comment */")
+            .build();
+        let tokens = scanner.scan_tokens().unwrap();
+        assert!(scanner.errors().is_empty());
+        assert_eq!(tokens.len(), 1);
+        assert_eq!(tokens[0].kind, TokenKind::Eof);
+        assert_eq!(tokens[0].line, 2);
+
+        let mut scanner = ScannerBuilder::default()
+            .source("/* outer /* inner */ still open")
+            .build();
+        scanner.scan_tokens().unwrap();
+        assert_eq!(scanner.errors().len(), 1);
+    }
+
+    #[test]
+    fn test_error_on_unterminated_block_comment() {
+        let mut scanner = ScannerBuilder::default()
+            .source("a\n/* never\nclosed *")
+            .build();
+        let tokens = scanner.scan_tokens().unwrap();
+        assert_eq!(tokens.len(), 2);
+        match scanner.errors() {
+            [ErrorCode::ScannerError(line, message)] => {
+                assert_eq!(*line, 2);
+                assert_eq!(message, "Unterminated block comment.");
+            }
+            _ => panic!("expected an unterminated block comment error"),
+        }
+    }
+
+    #[test]
+    fn test_keep_comments() {
+        let mut scanner = ScannerBuilder::default()
+            .source("// doc\nlet a = 1; // note")
+            .keep_comments(true)
+            .build();
+        let tokens = scanner.scan_tokens().unwrap();
+
+        assert_eq!(tokens[0].kind, TokenKind::Comment);
+        assert_eq!(&*tokens[0].lexeme, "// doc");
+        assert_eq!(tokens[6].kind, TokenKind::Comment);
+        assert_eq!(&*tokens[6].lexeme, "// note");
+        assert_eq!(tokens.len(), 8);
+    }
+
     #[test]
     fn test_generates_token_for_strings() {
         let mut scanner = ScannerBuilder::default()
@@ -535,6 +1039,145 @@ mod scanner_tests {
         assert_eq!(scanner.tokens.len(), 2);
     }
 
+    #[test]
+    fn test_translates_string_escapes() {
+        let mut scanner = ScannerBuilder::default()
+            .source(r#""a\nb" "\t\r\\" "say \"hi\"" 'it\'s'"#)
+            .build();
+        let tokens = scanner.scan_tokens().unwrap();
+        let strings: Vec<_> = tokens
+            .iter()
+            .filter_map(|t| match &t.literal {
+                Some(Literal {
+                    value: LiteralKind::String(s),
+                }) => Some(s.as_str()),
+                _ => None,
+            })
+            .collect();
+
+        assert!(scanner.errors().is_empty());
+        assert_eq!(strings, vec!["a\nb", "\t\r\\", "say \"hi\"", "it's"]);
+    }
+
+    #[test]
+    fn test_error_on_unterminated_string_ending_with_backslash() {
+        for source in ["\"abc\\", "print \"a\\"] {
+            let mut scanner = ScannerBuilder::default().source(source).build();
+            let tokens = scanner.scan_tokens().unwrap();
+
+            match scanner.errors() {
+                [ErrorCode::ScannerError(1, message)] => {
+                    assert_eq!(message, "Unexpected character: unterminated string.")
+                }
+                errors => panic!("{}: unexpected errors {:?}", source, errors),
+            }
+            assert_eq!(tokens.last().unwrap().kind, TokenKind::Eof);
+        }
+    }
+
+    #[test]
+    fn test_error_on_unknown_string_escape() {
+        let mut scanner = ScannerBuilder::default().source(r#""\q" 1"#).build();
+        let tokens = scanner.scan_tokens().unwrap();
+
+        assert_eq!(tokens.len(), 2);
+        match scanner.errors() {
+            [ErrorCode::ScannerError(_, message)] => {
+                assert_eq!(message, "Unknown escape sequence '\\q'.")
+            }
+            _ => panic!("expected an unknown escape error"),
+        }
+    }
+
+    /// scans the source keeping comments and checks the kind, lexeme, line and column of every
+    /// token against the expected ones
+    fn assert_positions(source: &str, expected: &[(TokenKind, &str, usize, usize)]) {
+        let mut scanner = ScannerBuilder::default()
+            .source(source)
+            .keep_comments(true)
+            .build();
+        let tokens = scanner.scan_tokens().unwrap();
+        let actual: Vec<_> = tokens
+            .iter()
+            .map(|t| (t.kind.clone(), &*t.lexeme, t.line, t.column))
+            .collect();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_golden_token_positions() {
+        assert_positions(
+            "// counts words\nlet text = \"one\n  two\";\n\tlet n = len(text) /* note */ >= 2;\nprint n |> string;",
+            &[
+                (TokenKind::Comment, "// counts words", 1, 1),
+                (TokenKind::Let, "let", 2, 1),
+                (TokenKind::Identifier, "text", 2, 5),
+                (TokenKind::Equal, "=", 2, 10),
+                // a string spanning lines is located where it starts
+                (TokenKind::String, "\"one\n  two\"", 2, 12),
+                (TokenKind::SemiColon, ";", 3, 7),
+                // a tab counts as a single column
+                (TokenKind::Let, "let", 4, 2),
+                (TokenKind::Identifier, "n", 4, 6),
+                (TokenKind::Equal, "=", 4, 8),
+                (TokenKind::Identifier, "len", 4, 10),
+                (TokenKind::LeftParen, "(", 4, 13),
+                (TokenKind::Identifier, "text", 4, 14),
+                (TokenKind::RightParen, ")", 4, 18),
+                (TokenKind::Comment, "/* note */", 4, 20),
+                (TokenKind::GreaterEqual, ">=", 4, 31),
+                (TokenKind::Number, "2", 4, 34),
+                (TokenKind::SemiColon, ";", 4, 35),
+                (TokenKind::Print, "print", 5, 1),
+                (TokenKind::Identifier, "n", 5, 7),
+                (TokenKind::PipeGreater, "|>", 5, 9),
+                (TokenKind::Identifier, "string", 5, 12),
+                (TokenKind::SemiColon, ";", 5, 18),
+                (TokenKind::Eof, "", 5, 19),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_tokens_track_their_column() {
+        let mut scanner = ScannerBuilder::default()
+            .source("let a = 1;\n  print \"é\" + a;")
+            .build();
+        let tokens = scanner.scan_tokens().unwrap();
+        let positions: Vec<_> = tokens.iter().map(|t| (t.line, t.column)).collect();
+
+        assert_eq!(
+            positions,
+            vec![
+                (1, 1),
+                (1, 5),
+                (1, 7),
+                (1, 9),
+                (1, 10),
+                (2, 3),
+                (2, 9),
+                (2, 13),
+                (2, 15),
+                (2, 16),
+                (2, 17)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_columns_of_a_long_line() {
+        let source = "'é' + ".repeat(5_000) + "1\n\"open\n2";
+        let mut scanner = ScannerBuilder::default().source(&source).build();
+        let tokens = scanner.scan_tokens().unwrap();
+
+        assert_eq!(tokens.len(), 10_003);
+        assert_eq!((tokens[9_998].line, tokens[9_998].column), (1, 29_995));
+        assert_eq!((tokens[10_000].line, tokens[10_000].column), (1, 30_001));
+        // the scanner steps back to the line break after an unterminated string
+        assert_eq!((tokens[10_001].line, tokens[10_001].column), (3, 1));
+    }
+
     #[test]
     fn test_generates_token_for_light_string() {
         let mut scanner = ScannerBuilder::default()
@@ -551,6 +1194,179 @@ mod scanner_tests {
         assert_eq!(scanner.tokens.len(), 2);
     }
 
+    #[test]
+    fn test_generates_token_for_numbers_with_underscores() {
+        let mut scanner = ScannerBuilder::default()
+            .source("12.345_678 1_000 1_0.2_5")
+            .build();
+        let tokens = scanner.scan_tokens().unwrap();
+
+        assert!(scanner.errors().is_empty());
+        assert_eq!(tokens.len(), 4);
+        assert_eq!(&*tokens[0].lexeme, "12.345_678");
+        assert_eq!(tokens[0].literal, Some(Literal::from(12.345_678)));
+        assert_eq!(tokens[1].literal, Some(Literal::from(1000)));
+        assert_eq!(tokens[2].literal, Some(Literal::from(10.25)));
+
+        let mut scanner = ScannerBuilder::default().source("3.141_592").build();
+        let tokens = scanner.scan_tokens().unwrap();
+        let expected: f32 = "3.141592".parse().unwrap();
+        assert_eq!(tokens[0].literal, Some(Literal::from(expected)));
+    }
+
+    #[test]
+    fn test_generates_token_for_scientific_notation() {
+        let mut scanner = ScannerBuilder::default()
+            .source("1e3 2.5e-1 1E+2 1_0e1_0")
+            .build();
+        let tokens = scanner.scan_tokens().unwrap();
+
+        assert!(scanner.errors().is_empty());
+        assert_eq!(tokens.len(), 5);
+        assert_eq!(&*tokens[1].lexeme, "2.5e-1");
+        assert_eq!(tokens[0].literal, Some(Literal::from(1000.0)));
+        assert_eq!(tokens[1].literal, Some(Literal::from(0.25)));
+        assert_eq!(tokens[2].literal, Some(Literal::from(100.0)));
+        assert_eq!(tokens[3].literal, Some(Literal::from(1e11)));
+    }
+
+    #[test]
+    fn test_error_on_missing_exponent_digits() {
+        for source in ["1e", "2.5e-", "1E+"] {
+            let mut scanner = ScannerBuilder::default().source(source).build();
+            let tokens = scanner.scan_tokens().unwrap();
+
+            assert_eq!(scanner.errors().len(), 1, "{}", source);
+            assert_eq!(tokens.len(), 1, "{}", source);
+        }
+    }
+
+    #[test]
+    fn test_generates_token_for_radix_numbers() {
+        let mut scanner = ScannerBuilder::default()
+            .source("0xFF 0b1010 0X7fff_FFFF 0B1_0")
+            .build();
+        let tokens = scanner.scan_tokens().unwrap();
+
+        assert!(scanner.errors().is_empty());
+        assert_eq!(tokens.len(), 5);
+        assert_eq!(&*tokens[0].lexeme, "0xFF");
+        assert_eq!(tokens[0].literal, Some(Literal::from(255)));
+        assert_eq!(tokens[1].literal, Some(Literal::from(10)));
+        assert_eq!(tokens[2].literal, Some(Literal::from(i32::MAX)));
+        assert_eq!(tokens[3].literal, Some(Literal::from(2)));
+    }
+
+    #[test]
+    fn test_error_on_malformed_radix_numbers() {
+        for source in ["0x", "0b2", "0xFG", "0b_1", "0x1__0", "0x1_0000_0000"] {
+            let mut scanner = ScannerBuilder::default().source(source).build();
+            let tokens = scanner.scan_tokens().unwrap();
+
+            assert_eq!(scanner.errors().len(), 1, "{}", source);
+            assert_eq!(tokens.len(), 1, "{}", source);
+        }
+    }
+
+    #[test]
+    fn test_underscores_between_digits() {
+        let mut scanner = ScannerBuilder::default().source("1_000").build();
+        let tokens = scanner.scan_tokens().unwrap();
+        assert!(scanner.errors().is_empty());
+        assert_eq!(tokens[0].literal, Some(Literal::from(1000)));
+
+        let mut scanner = ScannerBuilder::default().source("1__0").build();
+        scanner.scan_tokens().unwrap();
+        match scanner.errors() {
+            [ErrorCode::ScannerError(1, message)] => {
+                assert_eq!(
+                    message,
+                    "Invalid number '1__0': '_' must be between digits."
+                )
+            }
+            errors => panic!("unexpected errors {:?}", errors),
+        }
+    }
+
+    #[test]
+    fn test_error_on_float_out_of_range() {
+        let mut scanner = ScannerBuilder::default().source("print 1e39;").build();
+        let tokens = scanner.scan_tokens().unwrap();
+
+        match scanner.errors() {
+            [ErrorCode::ScannerError(1, message)] => {
+                assert_eq!(message, "Invalid number '1e39'.")
+            }
+            errors => panic!("unexpected errors {:?}", errors),
+        }
+        let kinds: Vec<TokenKind> = tokens.into_iter().map(|t| t.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![TokenKind::Print, TokenKind::SemiColon, TokenKind::Eof]
+        );
+    }
+
+    #[test]
+    fn test_error_on_integer_out_of_range() {
+        let mut scanner = ScannerBuilder::default()
+            .source("print 99999999999;")
+            .build();
+        let tokens = scanner.scan_tokens().unwrap();
+
+        match scanner.errors() {
+            [ErrorCode::ScannerError(1, message)] => {
+                assert_eq!(message, "Invalid number '99999999999'.")
+            }
+            errors => panic!("unexpected errors {:?}", errors),
+        }
+        let kinds: Vec<TokenKind> = tokens.into_iter().map(|t| t.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![TokenKind::Print, TokenKind::SemiColon, TokenKind::Eof]
+        );
+    }
+
+    #[test]
+    fn test_numbers_stop_at_non_ascii_digits() {
+        // `١` and `٣` are arabic-indic digits, they aren't part of the number
+        let mut scanner = ScannerBuilder::default().source("1١ 2.٣").build();
+        let tokens = scanner.scan_tokens().unwrap();
+
+        let lexemes: Vec<&str> = tokens.iter().map(|t| &*t.lexeme).collect();
+        assert_eq!(lexemes, vec!["1", "١", "2", ".", "٣", ""]);
+        assert_eq!(tokens[0].literal, Some(Literal::from(1)));
+        assert_eq!(tokens[1].kind, TokenKind::Error);
+        assert_eq!(scanner.errors().len(), 2);
+    }
+
+    #[test]
+    fn test_error_on_leading_number_underscore() {
+        for source in ["_5", "__1_000"] {
+            let mut scanner = ScannerBuilder::default().source(source).build();
+            let tokens = scanner.scan_tokens().unwrap();
+
+            assert_eq!(scanner.errors().len(), 1, "{}", source);
+            assert_eq!(tokens.len(), 1, "{}", source);
+        }
+
+        // a name merely starting with a digit after the underscore is still a name
+        let mut scanner = ScannerBuilder::default().source("_1st").build();
+        let tokens = scanner.scan_tokens().unwrap();
+        assert!(scanner.errors().is_empty());
+        assert_eq!(tokens[0].kind, TokenKind::Identifier);
+    }
+
+    #[test]
+    fn test_error_on_misplaced_number_underscores() {
+        for source in ["3._14", "3.14_", "1__0", "10_"] {
+            let mut scanner = ScannerBuilder::default().source(source).build();
+            let tokens = scanner.scan_tokens().unwrap();
+
+            assert_eq!(scanner.errors().len(), 1, "{}", source);
+            assert_eq!(tokens.len(), 1, "{}", source);
+        }
+    }
+
     #[test]
     fn test_generates_token_for_identifiers() {
         let mut scanner = ScannerBuilder::default().source("idFor1234").build();
@@ -558,6 +1374,33 @@ mod scanner_tests {
         assert_eq!(scanner.tokens.len(), 2);
     }
 
+    #[test]
+    fn test_generates_token_for_identifiers_with_underscores() {
+        let mut scanner = ScannerBuilder::default().source("is_even _private").build();
+        let tokens = scanner.scan_tokens().unwrap();
+        assert_eq!(tokens.len(), 3);
+        assert_eq!(&*tokens[0].lexeme, "is_even");
+        assert_eq!(&*tokens[1].lexeme, "_private");
+    }
+
+    #[test]
+    fn test_underscore_placement_in_identifiers() {
+        let mut scanner = ScannerBuilder::default()
+            .source("_ __init__ trailing_ a_1_b let_")
+            .build();
+        let tokens = scanner.scan_tokens().unwrap();
+
+        assert!(scanner.errors().is_empty());
+        let lexemes: Vec<&str> = tokens.iter().map(|t| &*t.lexeme).collect();
+        assert_eq!(
+            lexemes,
+            vec!["_", "__init__", "trailing_", "a_1_b", "let_", ""]
+        );
+        assert!(tokens[..5]
+            .iter()
+            .all(|token| token.kind == TokenKind::Identifier));
+    }
+
     #[test]
     fn test_ignore_keywords_token() {
         let mut scanner = ScannerBuilder::default()
@@ -573,4 +1416,236 @@ mod scanner_tests {
         scanner.scan_tokens().unwrap();
         assert_eq!(scanner.tokens.len(), 6);
     }
+
+    #[test]
+    fn test_lexemes_round_trip_source() {
+        let source = "let café = \"héllo wörld\"; // ünicode comment
+fun greet(name) {
+    if (name == 'ok' and 1.5 >= 1) return name + \"!\";
+    return !(2 <= 3) or 4 < 5 or 6 > 7 or nil;
+}
+print greet(café) * -3 / 1;";
+        let tokens = ScannerBuilder::default()
+            .source(source)
+            .build()
+            .scan_tokens()
+            .unwrap();
+
+        // Every lexeme is the next piece of source once whitespace and comments are skipped
+        let mut rest = source;
+        for token in &tokens {
+            loop {
+                rest = rest.trim_start();
+                match rest.strip_prefix("//") {
+                    Some(comment) => rest = comment.split_once('\n').map_or("", |(_, r)| r),
+                    None => break,
+                }
+            }
+            assert!(
+                rest.starts_with(&*token.lexeme),
+                "{:?} does not start with {:?}",
+                rest,
+                token.lexeme
+            );
+            rest = &rest[token.lexeme.len()..];
+        }
+
+        assert!(rest.is_empty());
+        let last = tokens.last().unwrap();
+        assert_eq!((&last.kind, &*last.lexeme), (&TokenKind::Eof, ""));
+    }
+
+    #[test]
+    fn test_single_quote_strings_enabled_by_default() {
+        let mut scanner = ScannerBuilder::default().source("'x'").build();
+        let tokens = scanner.scan_tokens().unwrap();
+
+        assert_eq!(tokens[0].kind, TokenKind::String);
+        assert!(scanner.errors().is_empty());
+    }
+
+    #[test]
+    fn test_error_on_disabled_single_quote_strings() {
+        let mut scanner = ScannerBuilder::default()
+            .source("'x' \"y\"")
+            .single_quote_strings(false)
+            .build();
+        let tokens = scanner.scan_tokens().unwrap();
+
+        let kinds: Vec<TokenKind> = tokens.into_iter().map(|t| t.kind).collect();
+        assert_eq!(kinds, vec![TokenKind::String, TokenKind::Eof]);
+        assert!(matches!(
+            scanner.errors(),
+            [ErrorCode::ScannerError(_, m)] if m == "Single-quoted strings are disabled"
+        ));
+    }
+
+    #[test]
+    fn test_ascii_and_utf8_sources_scan_alike() {
+        let scan = |source: &str| {
+            ScannerBuilder::default()
+                .source(source)
+                .build()
+                .scan_tokens()
+                .unwrap()
+        };
+
+        let ascii = scan("let greeting = \"hello\";\nprint greeting + \"!\"; // done");
+        let utf8 = scan("let greeting = \"héllo wörld ✓\";\nprint greeting + \"!\"; // done");
+
+        assert_eq!(ascii.len(), utf8.len());
+        for (a, u) in ascii.iter().zip(utf8.iter()) {
+            assert_eq!((&a.kind, a.line), (&u.kind, u.line));
+            if a.kind != TokenKind::String || &*a.lexeme == "\"!\"" {
+                assert_eq!(a.lexeme, u.lexeme);
+            }
+        }
+        assert_eq!(&*utf8[3].lexeme, "\"héllo wörld ✓\"");
+    }
+
+    #[test]
+    fn test_intern_identifiers() {
+        let source = "let a = a + b; let b = a;";
+
+        let tokens = ScannerBuilder::default()
+            .source(source)
+            .intern_identifiers(true)
+            .build()
+            .scan_tokens()
+            .unwrap();
+        assert!(Handle::ptr_eq(&tokens[1].lexeme, &tokens[3].lexeme));
+        assert!(Handle::ptr_eq(&tokens[0].lexeme, &tokens[7].lexeme));
+        assert!(!Handle::ptr_eq(&tokens[1].lexeme, &tokens[5].lexeme));
+
+        let tokens = ScannerBuilder::default()
+            .source(source)
+            .build()
+            .scan_tokens()
+            .unwrap();
+        assert_eq!(tokens[1].lexeme, tokens[3].lexeme);
+        assert!(!Handle::ptr_eq(&tokens[1].lexeme, &tokens[3].lexeme));
+    }
+
+    #[test]
+    fn test_recover_after_unterminated_string() {
+        let mut scanner = ScannerBuilder::default().source("\"oops\nprint 1;").build();
+        let tokens = scanner.scan_tokens().unwrap();
+
+        assert_eq!(scanner.errors().len(), 1);
+        assert!(matches!(scanner.errors()[0], ErrorCode::ScannerError(1, _)));
+
+        let kinds: Vec<&TokenKind> = tokens.iter().map(|t| &t.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                &TokenKind::Print,
+                &TokenKind::Number,
+                &TokenKind::SemiColon,
+                &TokenKind::Eof
+            ]
+        );
+        assert_eq!(tokens[0].line, 2);
+    }
+
+    #[test]
+    fn test_unexpected_characters() {
+        let mut scanner = ScannerBuilder::default().source("1 @@@ $ 2").build();
+        let tokens = scanner.scan_tokens().unwrap();
+
+        let lexemes: Vec<&str> = tokens.iter().map(|t| &*t.lexeme).collect();
+        assert_eq!(lexemes, vec!["1", "@@@", "$", "2", ""]);
+        assert_eq!(tokens[1].kind, TokenKind::Error);
+        assert_eq!(scanner.errors().len(), 2);
+
+        let mut scanner = ScannerBuilder::default().source("1 @@").build();
+        let tokens = scanner.scan_tokens().unwrap();
+        assert_eq!(&*tokens[1].lexeme, "@@");
+        assert_eq!(scanner.errors().len(), 1);
+    }
+
+    #[test]
+    fn test_compound_assignment_operators() {
+        let mut scanner = ScannerBuilder::default()
+            .source("+= -= *= /= + - / //= comment")
+            .build();
+        let tokens = scanner.scan_tokens().unwrap();
+
+        let kinds: Vec<TokenKind> = tokens.into_iter().map(|t| t.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                TokenKind::PlusEqual,
+                TokenKind::MinusEqual,
+                TokenKind::StarEqual,
+                TokenKind::SlashEqual,
+                TokenKind::Plus,
+                TokenKind::Minus,
+                TokenKind::Slash,
+                TokenKind::Eof
+            ]
+        );
+    }
+
+    #[test]
+    fn test_arithmetic_operators() {
+        let mut scanner = ScannerBuilder::default().source("* ** **= % %=").build();
+        let tokens = scanner.scan_tokens().unwrap();
+
+        let kinds: Vec<TokenKind> = tokens.into_iter().map(|t| t.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                TokenKind::Star,
+                TokenKind::StarStar,
+                TokenKind::StarStarEqual,
+                TokenKind::Percent,
+                TokenKind::PercentEqual,
+                TokenKind::Eof
+            ]
+        );
+    }
+
+    #[test]
+    fn test_auto_semicolon_disabled_by_default() {
+        let mut scanner = ScannerBuilder::default().source("print 1\nprint 2").build();
+        scanner.scan_tokens().unwrap();
+        assert!(!scanner
+            .tokens
+            .iter()
+            .any(|t| t.kind == TokenKind::SemiColon));
+    }
+
+    #[test]
+    fn test_auto_semicolon_terminates_lines() {
+        let mut scanner = ScannerBuilder::default()
+            .source("print 1\nprint 2")
+            .auto_semicolon(true)
+            .build();
+        let tokens = scanner.scan_tokens().unwrap();
+
+        let mut parser = Parser::from_tokens(&tokens);
+        let statements = parser.parse::<String, AstPrinter, AstPrinter>().unwrap();
+        assert_eq!(statements.len(), 2);
+    }
+
+    #[test]
+    fn test_auto_semicolon_skips_line_ending_with_operator() {
+        let mut scanner = ScannerBuilder::default()
+            .source("1 +\n2")
+            .auto_semicolon(true)
+            .build();
+        let tokens = scanner.scan_tokens().unwrap();
+
+        let kinds: Vec<TokenKind> = tokens.into_iter().map(|t| t.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                TokenKind::Number,
+                TokenKind::Plus,
+                TokenKind::Number,
+                TokenKind::SemiColon,
+                TokenKind::Eof
+            ]
+        );
+    }
 }
@@ -0,0 +1,63 @@
+//! Pointer and cell types the interpreter shares values through, single threaded by default and
+//! thread safe with the `sync` feature.
+
+/// Reference counted handle to an immutable value, `Rc` by default and `Arc` with the `sync`
+/// feature
+#[cfg(not(feature = "sync"))]
+pub type Handle<T> = std::rc::Rc<T>;
+#[cfg(feature = "sync")]
+pub type Handle<T> = std::sync::Arc<T>;
+
+/// Handle to a value shared between the environment and the interpreter, single threaded by
+/// default and thread safe with the `sync` feature.
+#[cfg(not(feature = "sync"))]
+pub type Shared<T> = Handle<std::cell::RefCell<T>>;
+#[cfg(feature = "sync")]
+pub type Shared<T> = Handle<std::sync::Mutex<T>>;
+
+/// Exclusive access to the value behind a `Shared` handle
+#[cfg(not(feature = "sync"))]
+pub type SharedGuard<'a, T> = std::cell::RefMut<'a, T>;
+#[cfg(feature = "sync")]
+pub type SharedGuard<'a, T> = std::sync::MutexGuard<'a, T>;
+
+/// Cell initialized on first use, the object cache of literal nodes
+#[cfg(not(feature = "sync"))]
+pub type OnceValue<T> = std::cell::OnceCell<T>;
+#[cfg(feature = "sync")]
+pub type OnceValue<T> = std::sync::OnceLock<T>;
+
+/// Bound of the syntax tree nodes and native functions, `Send + Sync` with the `sync` feature
+/// and met by every type otherwise
+#[cfg(not(feature = "sync"))]
+pub trait ThreadSafe {}
+#[cfg(not(feature = "sync"))]
+impl<T: ?Sized> ThreadSafe for T {}
+
+/// Bound of the syntax tree nodes and native functions, `Send + Sync` with the `sync` feature
+/// and met by every type otherwise
+#[cfg(feature = "sync")]
+pub trait ThreadSafe: Send + Sync {}
+#[cfg(feature = "sync")]
+impl<T: ?Sized + Send + Sync> ThreadSafe for T {}
+
+/// wraps the value in a new `Shared` handle
+pub fn share<T>(value: T) -> Shared<T> {
+    Shared::new(value.into())
+}
+
+/// gives exclusive access to a shared value. A `Mutex` isn't reentrant, the guard is dropped
+/// before handing nested values back to code that may access them again
+#[cfg(not(feature = "sync"))]
+pub fn access<T>(shared: &Shared<T>) -> SharedGuard<'_, T> {
+    shared.borrow_mut()
+}
+
+/// gives exclusive access to a shared value, a value poisoned by a panicking thread is still
+/// handed out since the interpreter never leaves an object half written
+#[cfg(feature = "sync")]
+pub fn access<T>(shared: &Shared<T>) -> SharedGuard<'_, T> {
+    shared
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
@@ -1,3 +1,4 @@
+use crate::shared::Handle;
 use phf::phf_map;
 
 use std::{fmt, str};
@@ -10,6 +11,7 @@ pub static KEYWORDS: phf::Map<&'static str, TokenKind> = phf_map! {
     "for" => TokenKind::For,
     "fun" => TokenKind::Fun,
     "if" => TokenKind::If,
+    "in" => TokenKind::In,
     "nil" => TokenKind::Nil,
     "or" => TokenKind::Or,
     "print" => TokenKind::Print,
@@ -29,6 +31,9 @@ pub enum TokenKind {
     RightParen,
     LeftBrace,
     RightBrace,
+    LeftBracket,
+    RightBracket,
+    Colon,
     Comma,
     Dot,
     SemiColon,
@@ -38,6 +43,14 @@ pub enum TokenKind {
     Star,
 
     // One or two character tokens.
+    PlusEqual,
+    MinusEqual,
+    StarEqual,
+    SlashEqual,
+    Percent,
+    PercentEqual,
+    StarStar,
+    StarStarEqual,
     Bang,
     BangEqual,
     Equal,
@@ -46,6 +59,8 @@ pub enum TokenKind {
     GreaterEqual,
     Less,
     LessEqual,
+    PipeGreater,
+    Arrow,
 
     // literals.
     Identifier,
@@ -60,6 +75,7 @@ pub enum TokenKind {
     Fun,
     For,
     If,
+    In,
     Nil,
     Or,
     Print,
@@ -71,9 +87,93 @@ pub enum TokenKind {
     Const,
     While,
 
+    /// A `//` comment, only produced when the scanner keeps comments
+    Comment,
+    /// A run of characters the scanner doesn't recognise, kept so the parser can recover
+    Error,
     Eof,
 }
 
+/// Group of a token kind for syntax highlighting
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SyntaxClass {
+    Keyword,
+    Operator,
+    /// strings, numbers and the `true`, `false` and `nil` keywords
+    Literal,
+    Punctuation,
+    Identifier,
+    /// error and end of file tokens which have no source to highlight
+    Other,
+}
+
+impl TokenKind {
+    /// returns the syntax class the kind is highlighted as
+    pub fn syntax_class(&self) -> SyntaxClass {
+        match self {
+            TokenKind::LeftParen
+            | TokenKind::RightParen
+            | TokenKind::LeftBrace
+            | TokenKind::RightBrace
+            | TokenKind::LeftBracket
+            | TokenKind::RightBracket
+            | TokenKind::Colon
+            | TokenKind::Comma
+            | TokenKind::Dot
+            | TokenKind::Arrow
+            | TokenKind::SemiColon => SyntaxClass::Punctuation,
+
+            TokenKind::Minus
+            | TokenKind::Plus
+            | TokenKind::Slash
+            | TokenKind::Star
+            | TokenKind::PlusEqual
+            | TokenKind::MinusEqual
+            | TokenKind::StarEqual
+            | TokenKind::SlashEqual
+            | TokenKind::Percent
+            | TokenKind::PercentEqual
+            | TokenKind::StarStar
+            | TokenKind::StarStarEqual
+            | TokenKind::Bang
+            | TokenKind::BangEqual
+            | TokenKind::Equal
+            | TokenKind::EqualEqual
+            | TokenKind::Greater
+            | TokenKind::GreaterEqual
+            | TokenKind::Less
+            | TokenKind::LessEqual
+            | TokenKind::PipeGreater => SyntaxClass::Operator,
+
+            TokenKind::String
+            | TokenKind::Number
+            | TokenKind::True
+            | TokenKind::False
+            | TokenKind::Nil => SyntaxClass::Literal,
+
+            TokenKind::Identifier => SyntaxClass::Identifier,
+
+            TokenKind::And
+            | TokenKind::Class
+            | TokenKind::Else
+            | TokenKind::Fun
+            | TokenKind::For
+            | TokenKind::If
+            | TokenKind::In
+            | TokenKind::Or
+            | TokenKind::Print
+            | TokenKind::Return
+            | TokenKind::Super
+            | TokenKind::This
+            | TokenKind::Let
+            | TokenKind::Const
+            | TokenKind::While => SyntaxClass::Keyword,
+
+            TokenKind::Comment | TokenKind::Error | TokenKind::Eof => SyntaxClass::Other,
+        }
+    }
+}
+
 impl fmt::Display for TokenKind {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -81,6 +181,9 @@ impl fmt::Display for TokenKind {
             TokenKind::RightParen => write!(f, ")"),
             TokenKind::LeftBrace => write!(f, "{{"),
             TokenKind::RightBrace => write!(f, "}}"),
+            TokenKind::LeftBracket => write!(f, "["),
+            TokenKind::RightBracket => write!(f, "]"),
+            TokenKind::Colon => write!(f, ":"),
             TokenKind::Comma => write!(f, ","),
             TokenKind::Dot => write!(f, "."),
             TokenKind::SemiColon => write!(f, ";"),
@@ -88,6 +191,14 @@ impl fmt::Display for TokenKind {
             TokenKind::Plus => write!(f, "+"),
             TokenKind::Slash => write!(f, "/"),
             TokenKind::Star => write!(f, "*"),
+            TokenKind::PlusEqual => write!(f, "+="),
+            TokenKind::MinusEqual => write!(f, "-="),
+            TokenKind::StarEqual => write!(f, "*="),
+            TokenKind::SlashEqual => write!(f, "/="),
+            TokenKind::Percent => write!(f, "%"),
+            TokenKind::PercentEqual => write!(f, "%="),
+            TokenKind::StarStar => write!(f, "**"),
+            TokenKind::StarStarEqual => write!(f, "**="),
             TokenKind::Bang => write!(f, "!"),
             TokenKind::BangEqual => write!(f, "!="),
             TokenKind::Equal => write!(f, "="),
@@ -96,6 +207,8 @@ impl fmt::Display for TokenKind {
             TokenKind::GreaterEqual => write!(f, ">="),
             TokenKind::Less => write!(f, "<"),
             TokenKind::LessEqual => write!(f, "<="),
+            TokenKind::PipeGreater => write!(f, "|>"),
+            TokenKind::Arrow => write!(f, "=>"),
             TokenKind::Identifier => write!(f, "identifier"),
             TokenKind::String => write!(f, "string"),
             TokenKind::Number => write!(f, "number"),
@@ -106,6 +219,7 @@ impl fmt::Display for TokenKind {
             TokenKind::Fun => write!(f, "fun"),
             TokenKind::For => write!(f, "for"),
             TokenKind::If => write!(f, "if"),
+            TokenKind::In => write!(f, "in"),
             TokenKind::Nil => write!(f, "nil"),
             TokenKind::Or => write!(f, "or"),
             TokenKind::Print => write!(f, "print"),
@@ -116,6 +230,8 @@ impl fmt::Display for TokenKind {
             TokenKind::Let => write!(f, "let"),
             TokenKind::Const => write!(f, "const"),
             TokenKind::While => write!(f, "while"),
+            TokenKind::Comment => write!(f, "comment"),
+            TokenKind::Error => write!(f, "error"),
             TokenKind::Eof => write!(f, "Eof"),
         }
     }
@@ -205,20 +321,51 @@ impl fmt::Display for Literal {
 #[derive(Debug)]
 pub struct Token {
     pub kind: TokenKind,
-    pub lexeme: String,
+    /// shared so cloning a token is cheap and equal lexemes can be interned by the scanner
+    pub lexeme: Handle<str>,
     pub literal: Option<Literal>,
     pub line: usize,
+    /// 1-based position of the first char of the lexeme on its line
+    pub column: usize,
 }
 
 impl Token {
-    pub fn new(kind: TokenKind, lexeme: &str, literal: Option<Literal>, line: usize) -> Self {
+    pub fn new(
+        kind: TokenKind,
+        lexeme: &str,
+        literal: Option<Literal>,
+        line: usize,
+        column: usize,
+    ) -> Self {
+        Self::with_shared_lexeme(kind, Handle::from(lexeme), literal, line, column)
+    }
+
+    /// Creates a token reusing an already allocated lexeme.
+    pub fn with_shared_lexeme(
+        kind: TokenKind,
+        lexeme: Handle<str>,
+        literal: Option<Literal>,
+        line: usize,
+        column: usize,
+    ) -> Self {
         Token {
             kind,
-            lexeme: lexeme.to_string(),
+            lexeme,
             literal,
             line,
+            column,
         }
     }
+
+    /// Returns whether the token carries a literal value, such as a number or a string.
+    pub fn is_literal(&self) -> bool {
+        self.literal.is_some()
+    }
+
+    /// Borrows the literal value carried by the token, if any.
+    pub fn literal_value(&self) -> Option<&Literal> {
+        self.literal.as_ref()
+    }
 }
 
 impl fmt::Display for Token {
@@ -234,9 +381,10 @@ impl Clone for Token {
     fn clone(&self) -> Self {
         Token {
             kind: self.kind.clone(),
-            lexeme: String::from(&self.lexeme),
+            lexeme: Handle::clone(&self.lexeme),
             literal: self.literal.as_ref().cloned(),
             line: self.line,
+            column: self.column,
         }
     }
 }
@@ -247,9 +395,48 @@ impl PartialEq for Token {
             || self.lexeme != other.lexeme
             || self.literal != other.literal
             || self.line != other.line
+            || self.column != other.column
         {
             return false;
         }
         true
     }
 }
+
+#[cfg(test)]
+mod token_tests {
+    use super::*;
+
+    #[test]
+    fn literal_value_of_number_token() {
+        let token = Token::new(TokenKind::Number, "12", Some(Literal::from(12)), 1, 1);
+
+        assert!(token.is_literal());
+        assert_eq!(token.literal_value(), Some(&Literal::from(12)));
+    }
+
+    #[test]
+    fn literal_value_of_operator_token() {
+        let token = Token::new(TokenKind::Plus, "+", None, 1, 1);
+
+        assert!(!token.is_literal());
+        assert_eq!(token.literal_value(), None);
+    }
+
+    #[test]
+    fn syntax_class_of_kinds() {
+        assert_eq!(TokenKind::Plus.syntax_class(), SyntaxClass::Operator);
+        assert_eq!(TokenKind::If.syntax_class(), SyntaxClass::Keyword);
+        assert_eq!(TokenKind::Number.syntax_class(), SyntaxClass::Literal);
+        assert_eq!(TokenKind::Nil.syntax_class(), SyntaxClass::Literal);
+        assert_eq!(
+            TokenKind::LeftParen.syntax_class(),
+            SyntaxClass::Punctuation
+        );
+        assert_eq!(
+            TokenKind::Identifier.syntax_class(),
+            SyntaxClass::Identifier
+        );
+        assert_eq!(TokenKind::Eof.syntax_class(), SyntaxClass::Other);
+    }
+}
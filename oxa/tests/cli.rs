@@ -0,0 +1,172 @@
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+/// runs the oxa binary on a script written to a temporary file
+fn run_script(name: &str, source: &str) -> Output {
+    run_script_with(name, source, &[])
+}
+
+/// runs the oxa binary with the flags followed by a script written to a temporary file
+fn run_script_with(name: &str, source: &str, flags: &[&str]) -> Output {
+    let path: PathBuf =
+        std::env::temp_dir().join(format!("oxa-{}-{}.oxa", name, std::process::id()));
+    std::fs::write(&path, source).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_oxa"))
+        .args(flags)
+        .arg(&path)
+        .env("RUST_LOG", "off")
+        .output()
+        .unwrap();
+
+    std::fs::remove_file(&path).unwrap();
+    output
+}
+
+#[test]
+fn print_output_goes_to_stdout() {
+    let output = run_script("print", "print 1;");
+
+    assert!(String::from_utf8_lossy(&output.stdout).starts_with("1\n"));
+    assert!(!String::from_utf8_lossy(&output.stderr).contains("Error"));
+}
+
+#[test]
+fn running_a_file_only_prints_explicit_output() {
+    let output = run_script("silent", "let a = 1;\na + 1;");
+
+    assert!(
+        output.stdout.is_empty(),
+        "{}",
+        String::from_utf8_lossy(&output.stdout)
+    );
+}
+
+#[test]
+fn scan_error_goes_to_stderr() {
+    let output = run_script("scan-error", "print 1;\n\"unterminated");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("unterminated string"));
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("unterminated string"));
+}
+
+#[test]
+fn print_callables() {
+    let output = run_script(
+        "print-callables",
+        "fun add(a, b) { return a + b; }\nprint add;\nprint string;",
+    );
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.starts_with("<fn add>\n<native string>\n"));
+}
+
+#[test]
+fn errors_include_the_file_name() {
+    let output = run_script("file-name", "print 1;\nprint -\"a\";");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let name = format!("oxa-file-name-{}.oxa:2:7:", std::process::id());
+    assert!(stderr.contains(&name), "{}", stderr);
+}
+
+#[test]
+fn runtime_error_line() {
+    let output = run_script("runtime-line", "print [1, 2][5];");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let line = stderr
+        .lines()
+        .find(|line| line.contains("Runtime error"))
+        .unwrap_or_else(|| panic!("{}", stderr));
+    let path = std::env::temp_dir().join(format!("oxa-runtime-line-{}.oxa", std::process::id()));
+    assert_eq!(
+        line,
+        format!(
+            "{}:1:15: Runtime error: Index 5 out of range for length 2.",
+            path.display()
+        )
+    );
+}
+
+#[test]
+fn warnings_keep_a_successful_exit_code() {
+    let output = run_script(
+        "warning",
+        "fun f() {\n  return 1;\n  print 2;\n}\nprint f();",
+    );
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("warning: unreachable code after 'return'"),
+        "{}",
+        stderr
+    );
+    assert!(output.status.success());
+}
+
+#[test]
+fn runtime_errors_fail_the_exit_code() {
+    for source in ["print [1, 2][5];\nprint 1;", "assert_eq(1, 2);"] {
+        let output = run_script("runtime-error", source);
+
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("Runtime error"), "{}", stderr);
+        assert_eq!(output.status.code(), Some(2));
+    }
+}
+
+#[test]
+fn recursion_up_to_the_default_call_depth() {
+    let count = "fun f(n) { if (n == 0) return 0; return 1 + f(n - 1); }\n";
+
+    let output = run_script("deep-recursion", &format!("{}print f(990);", count));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "990\n");
+    assert!(output.status.success());
+
+    // past the limit the script reports an error rather than overflowing the stack
+    let output = run_script("unbounded-recursion", &format!("{}print f(2000);", count));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("stack overflow: recursion too deep"),
+        "{}",
+        stderr
+    );
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn tokens_flag_dumps_the_scanner_output() {
+    let output = run_script_with("tokens", "print 1;\nundefined;", &["--tokens"]);
+
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "1 print\n1 number 1\n1 ;\n2 identifier undefined\n2 ;\n2 Eof\n"
+    );
+    assert!(output.status.success());
+}
+
+#[test]
+fn ast_flag_prints_the_parsed_statements() {
+    let output = run_script_with("ast", "print 1 + 2;", &["--ast"]);
+
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "print (+ 1 2)\n");
+    assert!(output.status.success());
+}
+
+#[test]
+fn bad_arguments_print_usage_and_fail() {
+    let unknown_flag = run_script_with("unknown-flag", "print 1;", &["--tokns"]);
+    let missing_script = Command::new(env!("CARGO_BIN_EXE_oxa"))
+        .arg("--ast")
+        .env("RUST_LOG", "off")
+        .output()
+        .unwrap();
+
+    for output in [unknown_flag, missing_script] {
+        assert_eq!(output.status.code(), Some(64));
+        assert!(output.stdout.is_empty());
+        assert!(String::from_utf8_lossy(&output.stderr).contains("Usage: oxa"));
+    }
+}